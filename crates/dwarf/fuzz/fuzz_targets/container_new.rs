@@ -0,0 +1,13 @@
+//! Feed arbitrary bytes to the loader behind `DwarfDebugSymbolContainer::new`
+//!
+//! Malformed binaries have to be rejected with an error, any panic or stack
+//! overflow is a bug. `parse` is used since the JS error `new` throws can't be
+//! built outside of wasm. Run with `cargo fuzz run container_new`, seeding the
+//! corpus with `tests/fixtures/*.wasm`.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use wasm_dwarf_alanyser::DwarfDebugSymbolContainer;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = DwarfDebugSymbolContainer::parse(data);
+});
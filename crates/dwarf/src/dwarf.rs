@@ -1,15 +1,21 @@
 use anyhow::{anyhow, Result};
 use gimli::{
-    AttributeValue, EndianRcSlice, LittleEndian, Reader, Unit, UnitHeader, UnitOffset,
-    UnitSectionOffset,
+    AttributeValue, DebugInfoOffset, DebuggingInformationEntry, EndianRcSlice, Endianity, Reader,
+    RunTimeEndian, Section, Unit, UnitHeader, UnitOffset, UnitSectionOffset,
 };
-use num_bigint::BigUint;
+use num_bigint::BigInt;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
-use wasmparser::{Parser, Payload};
+use wasmparser::Payload;
 
+pub mod demangle;
+pub mod error;
+pub mod index;
 pub mod sourcemap;
+pub mod split;
 pub mod subroutine;
 pub mod utils;
 pub mod variables;
@@ -18,41 +24,70 @@ pub mod wasm_bindings;
 mod format;
 
 use crate::console_log;
-use format::format_object;
+use demangle::demangle;
+use error::{to_js_error, BridgeError};
+use format::{address_from_bytes, format_object, format_remote_object, scalar_value};
+pub(crate) use format::{format_memory, format_variable_list, FormatLimits, Scalar};
 use sourcemap::{transform_debug_line, DwarfSourceMap};
-use subroutine::{transform_subprogram, DwarfSubroutineMap};
-use utils::{clone_string_attribute, error};
-use variables::{DwarfGlobalVariables, VariableLocation};
+use split::{index_split_units, split_unit, SplitUnits};
+use subroutine::{read_code_ranges, read_file_attribute, DwarfSubroutineMap, UnitIndexEntry};
+use utils::{clone_string_attribute, decompress_section, error, module_payloads};
+use variables::{DwarfGlobalVariables, VariableLocation, VariableName};
+use wasm_bindings::{ScalarValue, VariableInfoVector, VariableVector};
 
 /// Dwarf reader definitions for wasm-dwarf-alanyser
-pub type DwarfReader = EndianRcSlice<LittleEndian>;
+pub type DwarfReader = EndianRcSlice<RunTimeEndian>;
 pub type DwarfReaderOffset = <DwarfReader as Reader>::Offset;
 pub type Dwarf = gimli::Dwarf<DwarfReader>;
 pub type DwarfUnit = gimli::Unit<DwarfReader>;
 
+/// A parsed unit with the types resolved in it so far
+pub type ParsedUnit = (Dwarf, DwarfUnit, UnitTypes<DwarfReaderOffset>);
+
+/// Parsed units keyed by their offset in `.debug_info`
+type UnitCache = HashMap<UnitSectionOffset, Rc<ParsedUnit>>;
+
+/// Custom sections of a module keyed by name
+pub type SectionMap = HashMap<String, Rc<[u8]>>;
+
 /// Dwarf debug data utility
 #[derive(Clone)]
 pub struct DwarfDebugData {
-    program_raw_data: HashMap<String, Rc<[u8]>>,
+    /// Sections loaded once and shared by every clone
+    program_raw_data: Rc<SectionMap>,
+    /// Sections of the companion `.dwo` file or `.dwp` package, if any
+    split_raw_data: Option<Rc<SectionMap>>,
+    /// Units of the split data by `dwo_id`, found once when loading it
+    split_units: Rc<SplitUnits>,
+    endian: RunTimeEndian,
+    /// Units parsed by `unit_offset`, shared by every clone
+    unit_cache: Rc<RefCell<UnitCache>>,
 }
 
 impl DwarfDebugData {
-    /// Load webassembly binary and copy custom section data
-    pub fn new(wasm_binary: &[u8]) -> Result<Self> {
-        let parser = Parser::new(0);
-        let mut sections = HashMap::new();
-
-        for payload in parser.parse_all(wasm_binary) {
-            match payload? {
-                Payload::CustomSection { name, data, .. } => {
-                    sections.insert(String::from(name), Rc::from(data));
-                }
-                _ => continue,
+    /// Wrap the custom sections of a module, copied out of the binary while
+    /// scanning it, and load the split data from its own binary
+    pub fn from_sections(
+        program_raw_data: SectionMap,
+        split_binary: Option<&[u8]>,
+    ) -> Result<Self> {
+        // WebAssembly is little-endian by definition
+        let endian = RunTimeEndian::Little;
+        let (split_raw_data, split_units) = match split_binary {
+            Some(binary) => {
+                let sections = load_custom_sections(binary, 0)?;
+                let units = index_split_units(&sections, &program_raw_data, endian)?;
+                (Some(Rc::new(sections)), units)
             }
-        }
+            None => (None, HashMap::new()),
+        };
 
         Ok(Self {
-            program_raw_data: sections,
+            program_raw_data: Rc::new(program_raw_data),
+            split_raw_data,
+            split_units: Rc::new(split_units),
+            endian,
+            unit_cache: Rc::new(RefCell::new(HashMap::new())),
         })
     }
 
@@ -63,13 +98,18 @@ impl DwarfDebugData {
                 None => Rc::from(&[][..]),
             };
 
-            Ok(EndianRcSlice::new(data, LittleEndian))
+            Ok(EndianRcSlice::new(data, self.endian))
         };
 
         Dwarf::load(&load_section)
     }
 
-    pub fn unit_offset(&self, offset: UnitSectionOffset) -> Result<Option<(Dwarf, DwarfUnit)>> {
+    /// Parse the unit at `offset`, or reuse it if it was parsed before
+    pub fn unit_offset(&self, offset: UnitSectionOffset) -> Result<Option<Rc<ParsedUnit>>> {
+        if let Some(unit) = self.unit_cache.borrow().get(&offset) {
+            return Ok(Some(unit.clone()));
+        }
+
         let dwarf = self.parse_dwarf()?;
         let header = match header_from_offset(&dwarf, offset)? {
             Some(header) => header,
@@ -79,10 +119,97 @@ impl DwarfDebugData {
         };
 
         let unit = dwarf.unit(header)?;
-        Ok(Some((dwarf, unit)))
+        let (dwarf, unit) = match self.split_unit(&unit)? {
+            Some(split) => split,
+            None => (dwarf, unit),
+        };
+        let unit = Rc::new((dwarf, unit, UnitTypes::default()));
+        self.unit_cache.borrow_mut().insert(offset, unit.clone());
+        Ok(Some(unit))
+    }
+
+    /// Hash of the custom sections, telling whether an index was written for
+    /// this binary
+    pub fn fingerprint(&self) -> u64 {
+        // 64-bit FNV-1a
+        fn feed(hash: u64, bytes: &[u8]) -> u64 {
+            bytes.iter().fold(hash, |hash, byte| {
+                (hash ^ *byte as u64).wrapping_mul(0x100_0000_01b3)
+            })
+        }
+
+        let mut hash = 0xcbf2_9ce4_8422_2325;
+        let split_raw_data = self.split_raw_data.as_deref();
+        for sections in std::iter::once(&*self.program_raw_data).chain(split_raw_data) {
+            let mut names: Vec<_> = sections.keys().collect();
+            names.sort();
+            for name in names {
+                let data = &sections[name];
+                hash = feed(hash, name.as_bytes());
+                hash = feed(hash, &(data.len() as u64).to_le_bytes());
+                hash = feed(hash, data);
+            }
+            // keeps the sections of the split data apart
+            hash = feed(hash, &[0]);
+        }
+        hash
+    }
+
+    /// Find the split unit of a skeleton unit in the companion data
+    ///
+    /// Returns `None` for other units, and for skeletons whose split unit can't
+    /// be found.
+    pub fn split_unit(&self, skeleton: &DwarfUnit) -> Result<Option<(Dwarf, DwarfUnit)>> {
+        let (split_raw_data, dwo_id) = match (&self.split_raw_data, skeleton.dwo_id) {
+            (Some(split_raw_data), Some(dwo_id)) => (split_raw_data, dwo_id),
+            _ => return Ok(None),
+        };
+
+        match self.split_units.get(&dwo_id) {
+            Some(entry) => split_unit(
+                split_raw_data,
+                &self.program_raw_data,
+                entry,
+                skeleton,
+                self.endian,
+            )
+            .map(Some),
+            None => {
+                console_log!("split unit {:#x} is not found", dwo_id.0);
+                Ok(None)
+            }
+        }
     }
 }
 
+/// Copy the custom sections of a webassembly binary
+pub(crate) fn load_custom_sections(wasm_binary: &[u8], module_index: usize) -> Result<SectionMap> {
+    let mut sections = HashMap::new();
+
+    for payload in module_payloads(wasm_binary, module_index) {
+        if let Payload::CustomSection { name, data, .. } = payload? {
+            add_custom_section(&mut sections, name, data)?;
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Copy one custom section
+///
+/// Compressed `.zdebug_*` sections are inflated and stored under the name
+/// gimli looks them up by.
+pub(crate) fn add_custom_section(sections: &mut SectionMap, name: &str, data: &[u8]) -> Result<()> {
+    if let Some(suffix) = name.strip_prefix(".zdebug_") {
+        let data = decompress_section(data)
+            .map_err(|e| anyhow!("failed to decompress {}: {}", name, e))?;
+        sections.insert(format!(".debug_{}", suffix), Rc::from(data));
+    } else {
+        sections.insert(String::from(name), Rc::from(data));
+    }
+    Ok(())
+}
+
 /// Parsed dwarf debug data container
 pub struct DwarfDebugInfo {
     pub sourcemap: DwarfSourceMap,
@@ -90,12 +217,13 @@ pub struct DwarfDebugInfo {
     pub global_variables: DwarfGlobalVariables,
 }
 
-pub fn transform_dwarf(buffer: &[u8]) -> Result<DwarfDebugInfo> {
-    let dwarf_data = DwarfDebugData::new(buffer)?;
+/// Parse the line tables and unit ranges of a module's debug info
+pub fn transform_dwarf(dwarf_data: DwarfDebugData) -> Result<DwarfDebugInfo> {
     let dwarf = dwarf_data.parse_dwarf()?;
     let mut headers = dwarf.units();
     let mut sourcemaps = Vec::new();
-    let mut subroutines = Vec::new();
+    let mut units = Vec::new();
+    let mut has_partial_units = false;
     let mut entry_num = 0;
 
     while let Some(header) = headers.next()? {
@@ -107,27 +235,113 @@ pub fn transform_dwarf(buffer: &[u8]) -> Result<DwarfDebugInfo> {
             None => continue,
         };
         entry_num += 1;
-        sourcemaps.push(transform_debug_line(
-            &unit,
-            root,
-            &dwarf,
-            &dwarf.debug_line,
-        )?);
-        subroutines.append(&mut transform_subprogram(&dwarf, &unit, header_offset)?);
+        match root.tag() {
+            // type units hold no code, and are parsed when a type is read
+            gimli::DW_TAG_type_unit => continue,
+            // the code of a partial unit is in the line programs of the units
+            // importing it
+            gimli::DW_TAG_partial_unit => has_partial_units = true,
+            _ => match transform_debug_line(&unit, root, &dwarf, &dwarf.debug_line) {
+                Ok(sourcemap) => sourcemaps.push(sourcemap),
+                Err(e) => console_log!("skipping line program of {:?}: {}", header_offset, e),
+            },
+        }
+
+        // subprograms are only read once a query lands in the unit's ranges
+        let ranges = read_code_ranges(&dwarf, &unit, root).unwrap_or_else(|e| {
+            console_log!("ignoring ranges of {:?}: {}", header_offset, e);
+            Vec::new()
+        });
+        units.push(UnitIndexEntry::new(header_offset, ranges));
+    }
+
+    // only binaries with partial units pay for reading the children of roots
+    if has_partial_units {
+        if let Err(e) = link_partial_units(&dwarf, &mut units) {
+            console_log!("ignoring imported units: {}", e);
+        }
     }
 
     console_log!("found {} entries", entry_num);
 
     Ok(DwarfDebugInfo {
         sourcemap: DwarfSourceMap::new(sourcemaps, dwarf_data.clone()),
-        subroutine: DwarfSubroutineMap {
-            subroutines,
-            dwarf_data: dwarf_data.clone(),
-        },
+        subroutine: DwarfSubroutineMap::new(units, dwarf_data.clone()),
         global_variables: DwarfGlobalVariables { dwarf_data },
     })
 }
 
+/// Record the first importer of every partial unit pulled in with
+/// `DW_TAG_imported_unit`, and let partial units listing no ranges of their
+/// own cover the ranges of the units importing them
+fn link_partial_units(dwarf: &Dwarf, units: &mut [UnitIndexEntry]) -> Result<()> {
+    let mut imports = Vec::new();
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let importer = header.offset();
+        let unit = dwarf.unit(header)?;
+        let mut tree = unit.entries_tree(None)?;
+        let mut children = tree.root()?.children();
+        while let Some(child) = children.next()? {
+            if child.entry().tag() != gimli::DW_TAG_imported_unit {
+                continue;
+            }
+            if let Some(AttributeValue::DebugInfoRef(offset)) =
+                child.entry().attr_value(gimli::DW_AT_import)?
+            {
+                imports.push((importer, offset));
+            }
+        }
+    }
+
+    let inherits_ranges: Vec<bool> = units.iter().map(|entry| entry.ranges.is_empty()).collect();
+    for (importer, offset) in imports {
+        let imported = match unit_from_debug_info_offset(dwarf, offset)? {
+            Some((unit, _)) => unit.header.offset(),
+            None => {
+                console_log!("ignoring import of missing unit at {:?}", offset);
+                continue;
+            }
+        };
+        let importer = units.iter().position(|entry| entry.offset == importer);
+        let imported = units.iter().position(|entry| entry.offset == imported);
+        let (importer, imported) = match (importer, imported) {
+            (Some(importer), Some(imported)) if importer != imported => (importer, imported),
+            _ => continue,
+        };
+        if units[imported].importer.is_none() {
+            units[imported].importer = Some(units[importer].offset);
+        }
+        if inherits_ranges[imported] {
+            let ranges = units[importer].ranges.clone();
+            units[imported].ranges.extend(ranges);
+        }
+    }
+    Ok(())
+}
+
+/// Byte order of the target the debug info describes
+fn dwarf_endian<R: gimli::Reader>(dwarf: &gimli::Dwarf<R>) -> RunTimeEndian {
+    if dwarf.debug_info.reader().endian().is_big_endian() {
+        RunTimeEndian::Big
+    } else {
+        RunTimeEndian::Little
+    }
+}
+
+/// Language of a unit, assumed to be C when the root DIE doesn't say
+pub(crate) fn unit_language<R: gimli::Reader>(unit: &Unit<R>) -> SourceLanguage {
+    let mut entries = unit.entries();
+    let language = match entries.next_dfs() {
+        Ok(Some((_, root))) => root.attr_value(gimli::DW_AT_language).ok().flatten(),
+        _ => None,
+    };
+    match language {
+        Some(AttributeValue::Language(language)) => SourceLanguage::from_dw_lang(language),
+        _ => SourceLanguage::C,
+    }
+}
+
 fn header_from_offset<R: gimli::Reader>(
     dwarf: &gimli::Dwarf<R>,
     offset: UnitSectionOffset<R::Offset>,
@@ -140,13 +354,385 @@ fn header_from_offset<R: gimli::Reader>(
             continue;
         }
     }
+    // DWARF 4 type units live in .debug_types
+    let mut headers = dwarf.type_units();
+    while let Some(header) = headers.next()? {
+        if header.offset() == offset {
+            return Ok(Some(header));
+        }
+    }
     Ok(None)
 }
 
+fn unit_from_debug_info_offset(
+    dwarf: &Dwarf,
+    offset: DebugInfoOffset<DwarfReaderOffset>,
+) -> Result<Option<(DwarfUnit, UnitOffset<DwarfReaderOffset>)>> {
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        if let Some(unit_offset) = offset.to_unit_offset(&header) {
+            return Ok(Some((dwarf.unit(header)?, unit_offset)));
+        }
+    }
+    Ok(None)
+}
+
+/// Resolve the name of an entry, following DW_AT_abstract_origin and
+/// DW_AT_specification if needed
+///
+/// Mangled names are demangled, and entries without DW_AT_name fall back to
+/// their demangled linkage name.
+fn entry_name(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<Option<String>> {
+    let name = with_origins(dwarf, unit, entry, |dwarf, unit, entry| {
+        match entry.attr_value(gimli::DW_AT_name)? {
+            Some(attr) => Ok(Some(clone_string_attribute(dwarf, unit, attr)?)),
+            None => Ok(None),
+        }
+    })?;
+    if let Some(name) = name {
+        return Ok(Some(demangle(&name).unwrap_or(name)));
+    }
+
+    Ok(entry_linkage_name(dwarf, unit, entry)?.map(|name| demangle(&name).unwrap_or(name)))
+}
+
+/// Name of an entry prefixed with the namespaces and types declaring it, e.g.
+/// `ns::Widget::resize`
+///
+/// Out-of-line definitions are qualified by where their declaration is, and
+/// anonymous namespaces are named `(anonymous namespace)`.
+fn entry_qualified_name(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<Option<String>> {
+    let name = match entry_name(dwarf, unit, entry)? {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    // the last entry of the origin chain is the one nested in its scopes
+    let scopes = with_origins(
+        dwarf,
+        unit,
+        entry,
+        |dwarf, unit, origin| match origin_reference(origin)? {
+            Some(_) => Ok(None),
+            None => Ok(Some(enclosing_scope_names(dwarf, unit, origin.offset())?)),
+        },
+    )?
+    .unwrap_or_default();
+    if scopes.is_empty() {
+        return Ok(Some(name));
+    }
+    Ok(Some(format!("{}::{}", scopes.join("::"), name)))
+}
+
+/// Names of the namespaces, classes, structures and unions enclosing an
+/// entry, outermost first
+fn enclosing_scope_names(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    offset: UnitOffset<DwarfReaderOffset>,
+) -> Result<Vec<String>> {
+    // the scope each ancestor of the current entry opens, if any
+    let mut scopes: Vec<Option<String>> = Vec::new();
+    let mut depth = 0;
+    let mut entries = unit.entries();
+    while let Some((delta, entry)) = entries.next_dfs()? {
+        depth += delta;
+        scopes.truncate(depth.max(0) as usize);
+        if entry.offset() == offset {
+            return Ok(scopes.into_iter().flatten().collect());
+        }
+        let scope = match entry.tag() {
+            gimli::DW_TAG_namespace => Some(
+                entry_name(dwarf, unit, entry)?
+                    .unwrap_or_else(|| "(anonymous namespace)".to_string()),
+            ),
+            gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
+                entry_name(dwarf, unit, entry)?
+            }
+            _ => None,
+        };
+        scopes.push(scope);
+    }
+    Ok(Vec::new())
+}
+
+/// Resolve the raw (mangled) linkage name of an entry
+fn entry_linkage_name(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<Option<String>> {
+    with_origins(dwarf, unit, entry, |dwarf, unit, entry| {
+        for name in [gimli::DW_AT_linkage_name, gimli::DW_AT_MIPS_linkage_name].iter() {
+            if let Some(attr) = entry.attr_value(*name)? {
+                return Ok(Some(clone_string_attribute(dwarf, unit, attr)?));
+            }
+        }
+        Ok(None)
+    })
+}
+
+/// Resolve the file an entry was declared in, following DW_AT_abstract_origin
+/// and DW_AT_specification if needed
+fn entry_decl_file(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<Option<String>> {
+    with_origins(dwarf, unit, entry, |dwarf, unit, entry| {
+        read_file_attribute(dwarf, unit, entry, gimli::DW_AT_decl_file)
+    })
+}
+
+/// Resolve the line an entry was declared at, following DW_AT_abstract_origin
+/// and DW_AT_specification if needed
+fn entry_decl_line(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<Option<u64>> {
+    with_origins(dwarf, unit, entry, |_, _, entry| {
+        Ok(entry
+            .attr_value(gimli::DW_AT_decl_line)?
+            .and_then(|attr| attr.udata_value()))
+    })
+}
+
+/// Resolve the name of an entry's DW_AT_type, following DW_AT_abstract_origin
+/// and DW_AT_specification if needed
+///
+/// Entries without a type, such as functions returning void, give `None`.
+fn entry_type_name(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<Option<String>> {
+    with_origins(dwarf, unit, entry, |dwarf, unit, entry| {
+        match entry.attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(offset)) => {
+                Ok(Some(unit_type_name(dwarf, unit, Some(offset.0))?))
+            }
+            _ => Ok(None),
+        }
+    })
+}
+
+/// Whether an entry is marked DW_AT_artificial, like the `this` parameter of
+/// methods
+fn entry_is_artificial(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<bool> {
+    let artificial = with_origins(dwarf, unit, entry, |_, _, entry| {
+        match entry.attr_value(gimli::DW_AT_artificial)? {
+            Some(AttributeValue::Flag(flag)) => Ok(Some(flag)),
+            _ => Ok(None),
+        }
+    })?;
+    Ok(artificial.unwrap_or(false))
+}
+
+/// Bound on DW_AT_abstract_origin/DW_AT_specification chains, which are
+/// cyclic in malformed DWARF
+const MAX_ORIGIN_DEPTH: usize = 16;
+
+/// Look up a property of an entry, or else of the entries it refers to
+///
+/// Inlined and out-of-line definitions point at their abstract instance with
+/// DW_AT_abstract_origin, and definitions of declared members point at the
+/// declaration with DW_AT_specification. The referenced entry may be in
+/// another unit, in which case `f` is passed that unit.
+fn with_origins<T>(
+    dwarf: &Dwarf,
+    unit: &DwarfUnit,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+    f: impl Fn(&Dwarf, &DwarfUnit, &DebuggingInformationEntry<DwarfReader>) -> Result<Option<T>>,
+) -> Result<Option<T>> {
+    if let Some(value) = f(dwarf, unit, entry)? {
+        return Ok(Some(value));
+    }
+
+    let mut origin_unit: Option<DwarfUnit> = None;
+    let mut reference = origin_reference(entry)?;
+    for _ in 0..MAX_ORIGIN_DEPTH {
+        let offset = match reference {
+            Some(AttributeValue::UnitRef(offset)) => offset,
+            Some(AttributeValue::DebugInfoRef(offset)) => {
+                match unit_from_debug_info_offset(dwarf, offset)? {
+                    Some((unit, offset)) => {
+                        origin_unit = Some(unit);
+                        offset
+                    }
+                    None => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+        let unit = origin_unit.as_ref().unwrap_or(unit);
+        let origin = unit.entry(offset)?;
+        if let Some(value) = f(dwarf, unit, &origin)? {
+            return Ok(Some(value));
+        }
+        reference = origin_reference(&origin)?;
+    }
+    Ok(None)
+}
+
+fn origin_reference(
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> Result<Option<AttributeValue<DwarfReader>>> {
+    match entry.attr_value(gimli::DW_AT_abstract_origin)? {
+        Some(attr) => Ok(Some(attr)),
+        None => Ok(entry.attr_value(gimli::DW_AT_specification)?),
+    }
+}
+
+/// Bound on typedef/qualifier/pointer chains, which are cyclic in malformed DWARF
+const MAX_TYPE_NAME_DEPTH: usize = 32;
+
+/// Build a C-style name for a type, e.g. `const int *` or `Foo[4]`
 fn unit_type_name<R: gimli::Reader>(
     dwarf: &gimli::Dwarf<R>,
     unit: &Unit<R>,
     type_offset: Option<R::Offset>,
+) -> Result<String> {
+    unit_type_name_rec(dwarf, unit, type_offset, 0)
+}
+
+/// Element count of a DW_TAG_subrange_type, unknown for flexible array members
+pub(crate) fn subrange_count<R: gimli::Reader>(
+    entry: &gimli::DebuggingInformationEntry<R>,
+) -> Result<Option<u64>> {
+    let count = match entry.attr_value(gimli::DW_AT_count)? {
+        Some(attr) => attr.udata_value(),
+        None => entry
+            .attr_value(gimli::DW_AT_upper_bound)?
+            .and_then(|attr| attr.udata_value())
+            .map(|upper_bound| upper_bound + 1),
+    };
+    Ok(count)
+}
+
+/// Size in bytes of a type DIE, following typedefs and qualifiers
+///
+/// Pointers and references without `DW_AT_byte_size` take the unit's address size.
+pub(crate) fn unit_type_byte_size<R: gimli::Reader>(
+    unit: &Unit<R>,
+    type_offset: R::Offset,
+) -> Result<Option<u64>> {
+    unit_type_byte_size_rec(unit, type_offset, 0)
+}
+
+/// Type names and sizes of one unit, resolved on first use
+///
+/// Lives as long as the parsed unit in `DwarfDebugData`, so lookups from
+/// different queries against the same unit share it.
+#[derive(Default)]
+pub struct UnitTypes<O> {
+    names: RefCell<HashMap<O, String>>,
+    byte_sizes: RefCell<HashMap<O, Option<u64>>>,
+}
+
+impl<O: gimli::ReaderOffset> UnitTypes<O> {
+    /// `unit_type_name` of the type at `type_offset`
+    pub fn type_name<R: gimli::Reader<Offset = O>>(
+        &self,
+        dwarf: &gimli::Dwarf<R>,
+        unit: &Unit<R>,
+        type_offset: Option<O>,
+    ) -> Result<String> {
+        let offset = match type_offset {
+            Some(offset) => offset,
+            None => return unit_type_name(dwarf, unit, None),
+        };
+        if let Some(name) = self.names.borrow().get(&offset) {
+            return Ok(name.clone());
+        }
+        let name = unit_type_name(dwarf, unit, type_offset)?;
+        self.names.borrow_mut().insert(offset, name.clone());
+        Ok(name)
+    }
+
+    /// `unit_type_byte_size` of the type at `type_offset`
+    pub fn byte_size<R: gimli::Reader<Offset = O>>(
+        &self,
+        unit: &Unit<R>,
+        type_offset: O,
+    ) -> Result<Option<u64>> {
+        if let Some(byte_size) = self.byte_sizes.borrow().get(&type_offset) {
+            return Ok(*byte_size);
+        }
+        let byte_size = unit_type_byte_size(unit, type_offset)?;
+        self.byte_sizes.borrow_mut().insert(type_offset, byte_size);
+        Ok(byte_size)
+    }
+}
+
+fn unit_type_byte_size_rec<R: gimli::Reader>(
+    unit: &Unit<R>,
+    type_offset: R::Offset,
+    depth: usize,
+) -> Result<Option<u64>> {
+    if depth > MAX_TYPE_NAME_DEPTH {
+        Err(anyhow!("type chain at {:?} is too deep", type_offset))?
+    }
+
+    let mut tree = unit.entries_tree(Some(UnitOffset::<R::Offset>(type_offset)))?;
+    let root = tree.root()?;
+    let entry = root.entry();
+
+    if let Some(byte_size) = entry
+        .attr_value(gimli::DW_AT_byte_size)?
+        .and_then(|attr| attr.udata_value())
+    {
+        return Ok(Some(byte_size));
+    }
+    let inner_size = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(ref offset)) => {
+            unit_type_byte_size_rec(unit, offset.0, depth + 1)?
+        }
+        _ => None,
+    };
+
+    match entry.tag() {
+        gimli::DW_TAG_pointer_type
+        | gimli::DW_TAG_reference_type
+        | gimli::DW_TAG_rvalue_reference_type
+        | gimli::DW_TAG_ptr_to_member_type => Ok(Some(unit.header.address_size() as u64)),
+        gimli::DW_TAG_array_type => {
+            let mut element_count = Some(1);
+            let mut children = root.children();
+            while let Some(child) = children.next()? {
+                if child.entry().tag() == gimli::DW_TAG_subrange_type {
+                    let count = subrange_count(child.entry())?;
+                    element_count = element_count.and_then(|acc| count.map(|count| acc * count));
+                }
+            }
+            Ok(inner_size.and_then(|size| element_count.map(|count| count * size)))
+        }
+        gimli::DW_TAG_typedef
+        | gimli::DW_TAG_const_type
+        | gimli::DW_TAG_volatile_type
+        | gimli::DW_TAG_restrict_type
+        | gimli::DW_TAG_atomic_type
+        | gimli::DW_TAG_enumeration_type => Ok(inner_size),
+        _ => Ok(None),
+    }
+}
+
+fn unit_type_name_rec<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    type_offset: Option<R::Offset>,
+    depth: usize,
 ) -> Result<String> {
     let type_offset = match type_offset {
         Some(offset) => offset,
@@ -154,28 +740,206 @@ fn unit_type_name<R: gimli::Reader>(
             return Ok("void".to_string());
         }
     };
+    if depth > MAX_TYPE_NAME_DEPTH {
+        Err(anyhow!("type chain at {:?} is too deep", type_offset))?
+    }
+
     let mut tree = unit.entries_tree(Some(UnitOffset::<R::Offset>(type_offset)))?;
     let root = tree.root()?;
+    let entry = root.entry();
 
-    match root.entry().tag() {
+    let inner_offset = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(ref offset)) => Some(offset.0),
+        _ => None,
+    };
+    let inner_name = |dwarf, unit| unit_type_name_rec(dwarf, unit, inner_offset, depth + 1);
+
+    match entry.tag() {
         gimli::DW_TAG_base_type
         | gimli::DW_TAG_class_type
         | gimli::DW_TAG_structure_type
-        | gimli::DW_TAG_union_type => {
-            if let Some(attr) = root.entry().attr_value(gimli::DW_AT_name)? {
+        | gimli::DW_TAG_union_type
+        | gimli::DW_TAG_enumeration_type
+        | gimli::DW_TAG_unspecified_type => {
+            if let Some(attr) = entry.attr_value(gimli::DW_AT_name)? {
                 clone_string_attribute(dwarf, unit, attr)
             } else {
                 Ok(String::from("<no-type-name>"))
             }
         }
+        gimli::DW_TAG_typedef => {
+            if let Some(attr) = entry.attr_value(gimli::DW_AT_name)? {
+                clone_string_attribute(dwarf, unit, attr)
+            } else {
+                inner_name(dwarf, unit)
+            }
+        }
+        gimli::DW_TAG_const_type
+        | gimli::DW_TAG_volatile_type
+        | gimli::DW_TAG_restrict_type
+        | gimli::DW_TAG_atomic_type => {
+            let qualifier = match entry.tag() {
+                gimli::DW_TAG_const_type => "const",
+                gimli::DW_TAG_volatile_type => "volatile",
+                gimli::DW_TAG_restrict_type => "restrict",
+                _ => "_Atomic",
+            };
+            let inner = inner_name(dwarf, unit)?;
+            // qualifiers of pointers go after the `*`
+            if inner.ends_with('*') {
+                Ok(format!("{}{}", inner, qualifier))
+            } else {
+                Ok(format!("{} {}", qualifier, inner))
+            }
+        }
+        gimli::DW_TAG_pointer_type
+        | gimli::DW_TAG_reference_type
+        | gimli::DW_TAG_rvalue_reference_type
+        | gimli::DW_TAG_ptr_to_member_type => {
+            let declarator = match entry.tag() {
+                gimli::DW_TAG_pointer_type => "*".to_string(),
+                gimli::DW_TAG_reference_type => "&".to_string(),
+                gimli::DW_TAG_rvalue_reference_type => "&&".to_string(),
+                _ => {
+                    let class = match entry.attr_value(gimli::DW_AT_containing_type)? {
+                        Some(AttributeValue::UnitRef(offset)) => {
+                            unit_type_name_rec(dwarf, unit, Some(offset.0), depth + 1)?
+                        }
+                        _ => "<unknown class>".to_string(),
+                    };
+                    format!("{}::*", class)
+                }
+            };
+            // pointers to functions wrap the declarator, e.g. `int (*)(char)`
+            if let Some(offset) = inner_offset {
+                let mut tree = unit.entries_tree(Some(UnitOffset::<R::Offset>(offset)))?;
+                let inner = tree.root()?;
+                if inner.entry().tag() == gimli::DW_TAG_subroutine_type {
+                    return function_type_name(dwarf, unit, inner, &declarator, depth + 1);
+                }
+            }
+            let inner = inner_name(dwarf, unit)?;
+            if inner.ends_with('*') {
+                Ok(format!("{}{}", inner, declarator))
+            } else {
+                Ok(format!("{} {}", inner, declarator))
+            }
+        }
+        gimli::DW_TAG_subroutine_type => function_type_name(dwarf, unit, root, "", depth),
+        gimli::DW_TAG_array_type => {
+            let mut name = inner_name(dwarf, unit)?;
+            let mut children = root.children();
+            while let Some(child) = children.next()? {
+                if child.entry().tag() != gimli::DW_TAG_subrange_type {
+                    continue;
+                }
+                match subrange_count(child.entry())? {
+                    Some(count) => name.push_str(&format!("[{}]", count)),
+                    None => name.push_str("[]"),
+                }
+            }
+            Ok(name)
+        }
         _ => {
-            if let Some(AttributeValue::UnitRef(ref offset)) =
-                root.entry().attr_value(gimli::DW_AT_type)?
-            {
-                unit_type_name(dwarf, unit, Some(offset.0))
+            if inner_offset.is_some() {
+                console_log!(
+                    "showing the type {} at {:?} as the type it qualifies",
+                    entry.tag(),
+                    type_offset
+                );
+                inner_name(dwarf, unit)
             } else {
-                Err(anyhow!(format!("failed to seek at {:?}", type_offset)))
+                // e.g. a type of a language unit this doesn't know, which
+                // mustn't keep the types around it from being named
+                Ok(match entry.tag().static_string() {
+                    Some(tag) => format!("<{}>", tag),
+                    None => format!("<unknown tag {:#x}>", entry.tag().0),
+                })
+            }
+        }
+    }
+}
+
+/// Name of a function type such as `int (char, ...)`, with `declarator` in
+/// parentheses before the parameters when it is pointed to
+///
+/// The artificial `this` parameter of methods is left out.
+fn function_type_name<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    node: gimli::EntriesTreeNode<R>,
+    declarator: &str,
+    depth: usize,
+) -> Result<String> {
+    let return_offset = match node.entry().attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(ref offset)) => Some(offset.0),
+        _ => None,
+    };
+    let return_name = unit_type_name_rec(dwarf, unit, return_offset, depth + 1)?;
+
+    let mut parameters = Vec::new();
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        match entry.tag() {
+            gimli::DW_TAG_formal_parameter => {
+                if let Some(AttributeValue::Flag(true)) =
+                    entry.attr_value(gimli::DW_AT_artificial)?
+                {
+                    continue;
+                }
+                let offset = match entry.attr_value(gimli::DW_AT_type)? {
+                    Some(AttributeValue::UnitRef(ref offset)) => Some(offset.0),
+                    _ => None,
+                };
+                parameters.push(unit_type_name_rec(dwarf, unit, offset, depth + 1)?);
             }
+            gimli::DW_TAG_unspecified_parameters => parameters.push("...".to_string()),
+            _ => {}
+        }
+    }
+
+    if declarator.is_empty() {
+        Ok(format!("{} ({})", return_name, parameters.join(", ")))
+    } else {
+        Ok(format!(
+            "{} ({})({})",
+            return_name,
+            declarator,
+            parameters.join(", ")
+        ))
+    }
+}
+
+/// Source language of a compilation unit, from `DW_AT_language`
+///
+/// Languages without their own formatting rules are `Other`, and are
+/// formatted like C.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceLanguage {
+    C,
+    Cpp,
+    Rust,
+    Other,
+}
+
+impl SourceLanguage {
+    fn from_dw_lang(language: gimli::DwLang) -> Self {
+        match language {
+            gimli::DW_LANG_C89
+            | gimli::DW_LANG_C
+            | gimli::DW_LANG_C99
+            | gimli::DW_LANG_C11
+            | gimli::DW_LANG_C17 => SourceLanguage::C,
+            gimli::DW_LANG_C_plus_plus
+            | gimli::DW_LANG_C_plus_plus_03
+            | gimli::DW_LANG_C_plus_plus_11
+            | gimli::DW_LANG_C_plus_plus_14
+            | gimli::DW_LANG_C_plus_plus_17
+            | gimli::DW_LANG_C_plus_plus_20 => SourceLanguage::Cpp,
+            gimli::DW_LANG_Rust => SourceLanguage::Rust,
+            _ => SourceLanguage::Other,
         }
     }
 }
@@ -210,8 +974,14 @@ impl MemorySlice {
     pub fn set_memory_slice(&mut self, data: &[u8]) {
         self.memory_slice = data.to_vec();
     }
+
+    /// Bytes read for the slice, in the target's byte order
+    pub fn memory_slice(&self) -> Vec<u8> {
+        self.memory_slice.clone()
+    }
 }
 
+#[derive(Clone)]
 enum VariableEvaluationResult {
     Ready,
     Complete,
@@ -219,6 +989,7 @@ enum VariableEvaluationResult {
 }
 
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct VariableInfo {
     name: String,
 
@@ -230,6 +1001,143 @@ pub struct VariableInfo {
 
     tag: gimli::DwTag,
     encoding: gimli::DwAte,
+
+    pub(crate) members: Vec<VariableName>,
+
+    /// Value a pointer refers to, dereferenced one level during evaluation
+    pub(crate) pointee: Option<Box<VariableInfo>>,
+    /// Target of a pointer, known once the pointer itself has been read
+    pointee_address: Option<u64>,
+
+    /// Names and values of an enumeration's enumerators
+    pub(crate) enumerators: Vec<(String, BigInt)>,
+
+    /// Element type of an array, formatted once per element
+    pub(crate) element: Option<Box<VariableInfo>>,
+    /// Element counts of an array, outermost first
+    pub(crate) dimensions: Vec<Option<u64>>,
+    /// `DW_AT_byte_stride` of each dimension, where the producer gave one
+    pub(crate) byte_strides: Vec<Option<u64>>,
+
+    /// Byte order of values in `memory_slice`
+    endian: RunTimeEndian,
+    /// Size of an address in the unit: 4 for wasm32 and 8 for memory64
+    pub(crate) address_size: u8,
+    /// Language of the unit declaring the type
+    language: SourceLanguage,
+    /// Where the characters of a Rust string are
+    pub(crate) string: Option<StringLayout>,
+    /// Variants of a Rust enum, one of which is active
+    pub(crate) variant_part: Option<VariantPart>,
+    /// Bit ranges of the value that no piece of its location supplied
+    pub(crate) unavailable_bits: Vec<std::ops::Range<usize>>,
+    /// Bits of a bit-field member within the `byte_size` bytes read for it
+    pub(crate) bit_field: Option<BitField>,
+    /// How much of the value is read and formatted
+    pub(crate) limits: FormatLimits,
+    /// Linear memory captured up front, which the slices the evaluation asks
+    /// for are read from
+    memory_snapshot: Option<Rc<[u8]>>,
+}
+
+impl VariableInfo {
+    /// A value of a type without members or a location, held in no memory
+    /// yet, in a little-endian wasm32 C unit
+    pub(crate) fn new(
+        tag: gimli::DwTag,
+        name: String,
+        byte_size: usize,
+        encoding: gimli::DwAte,
+    ) -> Self {
+        VariableInfo {
+            name,
+            address_expr: Vec::new(),
+            byte_size,
+            memory_slice: MemorySlice::new(),
+            state: VariableEvaluationResult::Ready,
+            tag,
+            encoding,
+            members: Vec::new(),
+            pointee: None,
+            pointee_address: None,
+            enumerators: Vec::new(),
+            element: None,
+            dimensions: Vec::new(),
+            byte_strides: Vec::new(),
+            endian: RunTimeEndian::Little,
+            address_size: 4,
+            language: SourceLanguage::C,
+            string: None,
+            variant_part: None,
+            unavailable_bits: Vec::new(),
+            bit_field: None,
+            limits: FormatLimits::default(),
+            memory_snapshot: None,
+        }
+    }
+}
+
+/// Where a bit-field's value sits in the bytes it spans
+#[derive(Clone, Copy)]
+pub(crate) struct BitField {
+    /// First bit, counted from the least significant bit of the first byte on
+    /// little-endian targets and from the most significant one on big-endian
+    pub(crate) bit_offset: usize,
+    pub(crate) bit_size: usize,
+    /// Byte size of the declared type the bits are widened to
+    pub(crate) value_size: usize,
+}
+
+/// How a Rust string value locates its characters
+#[derive(Clone)]
+pub(crate) enum StringLayout {
+    /// Byte offsets of the pointer and length inside `&str` or `String`
+    Slice {
+        pointer_offset: usize,
+        length_offset: usize,
+        word_size: usize,
+    },
+    /// The characters a slice points to, of which at most the string length
+    /// the container shows are read
+    Characters { length: u64 },
+    /// The characters a C string points to, read up to the string length the
+    /// container shows and one more to tell whether the string goes on
+    Terminated,
+}
+
+/// Bytes of a memory snapshot at an address, `None` past its end
+pub(crate) fn read_memory_snapshot(snapshot: &[u8], address: u64, size: usize) -> Option<Vec<u8>> {
+    let start = usize::try_from(address).ok()?;
+    snapshot
+        .get(start..start.checked_add(size)?)
+        .map(<[u8]>::to_vec)
+}
+
+/// Truncate an address to `address_size` bytes
+pub(crate) fn wrap_address(address: u64, address_size: u8) -> u64 {
+    match address_size {
+        8 => address,
+        size => address & ((1 << (size as u32 * 8)) - 1),
+    }
+}
+
+/// A `DW_TAG_variant_part`, as Rust emits for enums
+#[derive(Clone)]
+pub(crate) struct VariantPart {
+    /// Byte offset and type of the discriminant, absent for a single variant
+    pub(crate) discriminant: Option<(usize, Box<VariableInfo>)>,
+    pub(crate) variants: Vec<Variant>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Variant {
+    pub(crate) name: String,
+    /// Inclusive ranges of discriminant values selecting the variant, `None`
+    /// for the variant taken when no other matches
+    pub(crate) discriminants: Option<Vec<(u64, u64)>>,
+    /// Names, byte offsets within the enum and types of the fields, without a
+    /// type where it isn't supported
+    pub(crate) fields: Vec<(String, usize, Option<VariableInfo>)>,
 }
 
 #[wasm_bindgen]
@@ -242,20 +1150,21 @@ impl VariableInfo {
             }
         }
 
-        if self.address_expr.is_empty() {
-            self.state = VariableEvaluationResult::Complete;
-
-            match format_object(self) {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            }
+        let result = if self.address_expr.is_empty() {
+            self.complete()
         } else {
             self.evaluate_internal();
             None
-        }
+        };
+        self.read_memory_snapshot(result)
     }
 
     pub fn resume_with_memory_slice(&mut self, memory: MemorySlice) -> Option<String> {
+        let result = self.resume(memory);
+        self.read_memory_snapshot(result)
+    }
+
+    fn resume(&mut self, memory: MemorySlice) -> Option<String> {
         match self.state {
             VariableEvaluationResult::RequireMemorySlice(_) => {}
             _ => {
@@ -263,31 +1172,308 @@ impl VariableInfo {
             }
         }
 
-        if let Some(VariableLocation::Pointer) = self.address_expr.first() {
+        if self.pointee_address.is_some() {
+            let value = self.pointee.as_mut()?.resume_with_memory_slice(memory);
+            return self.sync_with_pointee(value);
+        }
+
+        if let Some(&VariableLocation::Count { upper_bound, .. }) = self.address_expr.first() {
+            self.address_expr.remove(0);
+            let bytes = &memory.memory_slice;
+            let mut count = address_from_bytes(bytes, self.endian);
+            if upper_bound {
+                count = count.wrapping_add(1);
+            }
+            if bytes.len() < 8 {
+                count &= (1 << (bytes.len() * 8)) - 1;
+            }
+            self.set_element_count(count as usize);
+
+            if self.address_expr.is_empty() {
+                return self.complete();
+            }
+            self.evaluate_internal();
+            return None;
+        }
+
+        if let Some(VariableLocation::Pointer(_)) = self.address_expr.first() {
             self.address_expr.remove(0);
             self.address_expr.insert(
                 0,
-                VariableLocation::Address(
-                    BigUint::from_bytes_le(&memory.memory_slice).to_u64_digits()[0],
-                ),
+                VariableLocation::Address(address_from_bytes(&memory.memory_slice, self.endian)),
             );
         }
 
         self.memory_slice = memory;
 
         if self.address_expr.is_empty() {
-            self.state = VariableEvaluationResult::Complete;
-
-            match format_object(self) {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            }
+            self.complete()
         } else {
             self.evaluate_internal();
             None
         }
     }
 
+    /// Size in bytes of the value, e.g. to decode `required_memory_slice` once
+    /// the variable is evaluated
+    pub fn byte_size(&self) -> usize {
+        self.byte_size
+    }
+
+    /// `DW_ATE_*` constant the value is encoded with, such as `DW_ATE_signed`
+    /// for a base type or `DW_ATE_address` for a pointer
+    pub fn encoding_code(&self) -> u8 {
+        self.encoding.0
+    }
+
+    /// Tag of the type's DIE, such as `DW_TAG_base_type`
+    pub fn tag_code(&self) -> u16 {
+        self.tag.0
+    }
+
+    /// Address a pointer refers to, once the pointer has been read
+    pub fn pointee_address(&self) -> Option<usize> {
+        self.pointee_address.map(|address| address as usize)
+    }
+
+    /// JSON of a CDP `Runtime.RemoteObject` describing the evaluated variable
+    pub fn to_json(&self) -> Option<String> {
+        if !self.is_completed() {
+            return None;
+        }
+        format_remote_object(self).ok()
+    }
+
+    /// Value of an evaluated base type, enumerator or pointer
+    ///
+    /// Throws for aggregates, which aren't scalars, and for values not
+    /// evaluated yet.
+    pub fn scalar_value(&self) -> Result<ScalarValue, JsValue> {
+        self.scalar().map(ScalarValue::new).map_err(to_js_error)
+    }
+
+    pub(crate) fn scalar(&self) -> Result<Scalar> {
+        if !self.is_completed() {
+            Err(BridgeError::Unavailable(format!(
+                "{} is not evaluated yet",
+                self.name
+            )))?
+        }
+        scalar_value(self)
+    }
+
+    /// Read the memory the evaluation asks for from `snapshot` rather than
+    /// leaving it to the caller, before evaluating it
+    pub(crate) fn set_memory_snapshot(&mut self, snapshot: Rc<[u8]>) {
+        self.memory_snapshot = Some(snapshot);
+    }
+
+    /// Supply the slices the evaluation asks for from the memory snapshot
+    /// until it completes, or asks for memory past the end of the snapshot,
+    /// which is left to the caller
+    fn read_memory_snapshot(&mut self, mut result: Option<String>) -> Option<String> {
+        let snapshot = match &self.memory_snapshot {
+            Some(snapshot) => snapshot.clone(),
+            None => return result,
+        };
+        while let VariableEvaluationResult::RequireMemorySlice(slice) = &self.state {
+            let mut slice = slice.clone();
+            slice.memory_slice =
+                match read_memory_snapshot(&snapshot, slice.address as u64, slice.byte_size) {
+                    Some(bytes) => bytes,
+                    None => break,
+                };
+            result = self.resume(slice).or(result);
+        }
+        result
+    }
+
+    /// Read and format the value and the values it refers to within `limits`,
+    /// before evaluating it
+    pub(crate) fn set_format_limits(&mut self, limits: FormatLimits) {
+        self.limits = limits;
+        // a C string is read as far as it is shown
+        if let Some(StringLayout::Terminated) = self.string {
+            let count = limits.max_string_length + 1;
+            let element_size = self.element.as_ref().map_or(1, |element| element.byte_size);
+            self.byte_size = count * element_size;
+            self.dimensions = vec![Some(count as u64)];
+        }
+        if let Some(pointee) = self.pointee.as_mut() {
+            pointee.set_format_limits(limits);
+        }
+        if let Some(element) = self.element.as_mut() {
+            element.set_format_limits(limits);
+        }
+    }
+
+    /// Give the outermost dimension of an array whose length is only known at
+    /// run time `count` elements, before evaluating it
+    ///
+    /// Replaces reading the count from the variable or member its bound
+    /// refers to. Only the elements that are formatted are read.
+    pub fn set_element_count(&mut self, count: usize) {
+        if self.tag != gimli::DW_TAG_array_type {
+            return;
+        }
+        if let Some(VariableLocation::Count { .. }) = self.address_expr.first() {
+            self.address_expr.remove(0);
+        }
+        if let Some(first) = self.dimensions.first_mut() {
+            *first = Some(count as u64);
+        }
+        let stride = self.strides().first().copied().unwrap_or(0);
+        self.byte_size = count.min(self.limits.max_children) * stride;
+    }
+
+    /// Number of elements in the outermost dimension of an array
+    pub fn element_count(&self) -> Option<usize> {
+        self.dimensions
+            .first()
+            .copied()
+            .flatten()
+            .map(|count| count as usize)
+    }
+
+    /// Elements `start..start + count` of the outermost dimension of an array
+    ///
+    /// Each element is ready to evaluate on its own, at its address within the
+    /// array. Elements of an array without an address, such as a constant, are
+    /// cut from its bytes instead. Returns `None` until the array is evaluated.
+    pub fn elements(&self, start: usize, count: usize) -> Option<VariableInfoVector> {
+        if self.tag != gimli::DW_TAG_array_type || !self.is_completed() {
+            return None;
+        }
+        let element = self.element.as_ref()?;
+        let (_, inner) = self.dimensions.split_first()?;
+        let strides = self.strides();
+        let stride = *strides.first()?;
+
+        let template = match inner.is_empty() {
+            true => element.as_ref().clone(),
+            false => {
+                let mut template = self.clone();
+                template.name = element.name.clone();
+                for count in inner {
+                    match count {
+                        Some(count) => template.name += &format!("[{}]", count),
+                        None => template.name += "[]",
+                    }
+                }
+                template.dimensions = inner.to_vec();
+                template.byte_strides = self.byte_strides.iter().skip(1).copied().collect();
+                template.byte_size = match inner[0] {
+                    Some(count) => count as usize * strides[1],
+                    None => 0,
+                };
+                template
+            }
+        };
+
+        let end = match self.element_count() {
+            Some(total) => total.min(start.saturating_add(count)),
+            None => start.saturating_add(count),
+        };
+        let mut elements = Vec::new();
+        for index in start..end {
+            let offset = index * stride;
+            let mut element = template.clone();
+            element.state = VariableEvaluationResult::Ready;
+            element.pointee_address = None;
+
+            let address = self.memory_slice.address;
+            if address != 0 {
+                element.address_expr = vec![VariableLocation::Address((address + offset) as u64)];
+                element.memory_slice = MemorySlice::new();
+            } else {
+                let bytes = &self.memory_slice.memory_slice;
+                let chunk = bytes.get(offset..(offset + stride).min(bytes.len()))?;
+                element.address_expr = Vec::new();
+                element.memory_slice = MemorySlice::from_u8_vec(chunk.to_vec());
+            }
+            elements.push(element);
+        }
+        Some(VariableInfoVector::from_vec(elements))
+    }
+
+    /// Distance in bytes between consecutive items of each array dimension
+    pub(crate) fn strides(&self) -> Vec<usize> {
+        let element_size = self.element.as_ref().map_or(0, |x| x.byte_size);
+        let mut strides = vec![0; self.dimensions.len()];
+        let mut stride = element_size;
+        for level in (0..self.dimensions.len()).rev() {
+            if let Some(Some(explicit)) = self.byte_strides.get(level) {
+                stride = *explicit as usize;
+            }
+            strides[level] = stride;
+            stride *= self.dimensions[level].unwrap_or(0) as usize;
+        }
+        strides
+    }
+
+    fn complete(&mut self) -> Option<String> {
+        self.state = VariableEvaluationResult::Complete;
+
+        if let Some(StringLayout::Slice {
+            pointer_offset,
+            length_offset,
+            word_size,
+        }) = self.string
+        {
+            let bytes = &self.memory_slice.memory_slice;
+            let word = |offset: usize| bytes.get(offset..offset + word_size);
+            let address = address_from_bytes(word(pointer_offset)?, self.endian);
+            let length = address_from_bytes(word(length_offset)?, self.endian);
+
+            if address != 0 {
+                self.pointee_address = Some(address);
+                if let Some(pointee) = self.pointee.as_mut() {
+                    let count = length.min(self.limits.max_string_length as u64);
+                    pointee.byte_size = count as usize;
+                    pointee.dimensions = vec![Some(count)];
+                    pointee.string = Some(StringLayout::Characters { length });
+                    pointee.address_expr = if count == 0 {
+                        Vec::new()
+                    } else {
+                        vec![VariableLocation::Address(address)]
+                    };
+                    let value = pointee.evaluate();
+                    return self.sync_with_pointee(value);
+                }
+            }
+        }
+
+        if self.tag == gimli::DW_TAG_pointer_type && self.unavailable_bits.is_empty() {
+            let bytes = self.memory_slice.memory_slice.get(0..self.byte_size)?;
+            let address = address_from_bytes(bytes, self.endian);
+
+            // null pointers are never read
+            if address != 0 {
+                self.pointee_address = Some(address);
+                if let Some(pointee) = self.pointee.as_mut() {
+                    pointee.address_expr = vec![VariableLocation::Address(address)];
+                    let value = pointee.evaluate();
+                    return self.sync_with_pointee(value);
+                }
+            }
+        }
+
+        format_object(self).ok()
+    }
+
+    /// Forward the pointee's memory requests until it is complete
+    fn sync_with_pointee(&mut self, value: Option<String>) -> Option<String> {
+        let pointee = self.pointee.as_ref()?;
+        if let VariableEvaluationResult::RequireMemorySlice(ref slice) = pointee.state {
+            self.memory_slice = slice.clone();
+            self.state = VariableEvaluationResult::RequireMemorySlice(slice.clone());
+            return value;
+        }
+
+        self.state = VariableEvaluationResult::Complete;
+        format_object(self).ok()
+    }
+
     fn evaluate_internal(&mut self) {
         let mut address = 0;
         let mut byte_size = self.byte_size;
@@ -297,10 +1483,32 @@ impl VariableInfo {
                 VariableLocation::Address(addr) => {
                     address = addr;
                 }
-                VariableLocation::Offset(off) => address = (address as i64 + off) as u64,
-                VariableLocation::Pointer => {
-                    byte_size = 4;
-                    self.address_expr.insert(0, VariableLocation::Pointer);
+                // address arithmetic wraps around the module's address space
+                VariableLocation::Offset(off) => {
+                    address = wrap_address(address.wrapping_add(off as u64), self.address_size)
+                }
+                VariableLocation::Pointer(pointer_size) => {
+                    byte_size = pointer_size;
+                    self.address_expr
+                        .insert(0, VariableLocation::Pointer(pointer_size));
+                    break;
+                }
+                // the count is read first, from an address of its own
+                VariableLocation::Count {
+                    address: count_address,
+                    byte_size: count_size,
+                    upper_bound,
+                } => {
+                    address = count_address;
+                    byte_size = count_size;
+                    self.address_expr.insert(
+                        0,
+                        VariableLocation::Count {
+                            address: count_address,
+                            byte_size: count_size,
+                            upper_bound,
+                        },
+                    );
                     break;
                 }
             }
@@ -333,4 +1541,522 @@ impl VariableInfo {
     pub fn required_memory_slice(&self) -> MemorySlice {
         self.memory_slice.clone()
     }
+
+    pub fn members(&self) -> VariableVector {
+        VariableVector::from_vec(self.members.clone())
+    }
+
+    /// Members laid out from `byte_offset`, such as every arm of a union
+    ///
+    /// Each arm can then be evaluated by appending its name to the path of
+    /// the union, e.g. `bits.float`.
+    pub fn members_at(&self, byte_offset: usize) -> VariableVector {
+        VariableVector::from_vec(
+            self.members
+                .iter()
+                .filter(|member| member.layout.map(|x| x.byte_offset) == Some(byte_offset))
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gimli::write::{self, EndianVec};
+    use gimli::LittleEndian;
+
+    /// `const myint *` and `myint[3]` over a 4-byte `int`
+    fn typedef_unit() -> (Dwarf, DwarfUnit) {
+        write_unit(4, |unit| {
+            let root = unit.root();
+            let mut add = |tag, name: Option<&str>, ty: Option<write::UnitEntryId>| {
+                let id = unit.add(root, tag);
+                let entry = unit.get_mut(id);
+                if let Some(name) = name {
+                    entry.set(
+                        gimli::DW_AT_name,
+                        write::AttributeValue::String(name.as_bytes().to_vec()),
+                    );
+                }
+                if let Some(ty) = ty {
+                    entry.set(gimli::DW_AT_type, write::AttributeValue::UnitRef(ty));
+                }
+                id
+            };
+            let int = add(gimli::DW_TAG_base_type, Some("int"), None);
+            let myint = add(gimli::DW_TAG_typedef, Some("myint"), Some(int));
+            let const_myint = add(gimli::DW_TAG_const_type, None, Some(myint));
+            add(gimli::DW_TAG_pointer_type, None, Some(const_myint));
+            let array = add(gimli::DW_TAG_array_type, None, Some(myint));
+            let subrange = unit.add(array, gimli::DW_TAG_subrange_type);
+            unit.get_mut(subrange)
+                .set(gimli::DW_AT_count, write::AttributeValue::Udata(3));
+            unit.get_mut(int)
+                .set(gimli::DW_AT_byte_size, write::AttributeValue::Udata(4));
+        })
+    }
+
+    /// Write the units `build` adds and load them back
+    fn write_dwarf(version: u16, build: impl FnOnce(&mut write::Dwarf, gimli::Encoding)) -> Dwarf {
+        let encoding = gimli::Encoding {
+            format: gimli::Format::Dwarf32,
+            version,
+            address_size: 4,
+        };
+        let mut dwarf = write::Dwarf::new();
+        build(&mut dwarf, encoding);
+        let mut sections = write::Sections::new(EndianVec::new(LittleEndian));
+        dwarf.write(&mut sections).unwrap();
+        load(sections)
+    }
+
+    /// Write a unit holding the entries `build` adds and load it back
+    fn write_unit(version: u16, build: impl FnOnce(&mut write::Unit)) -> (Dwarf, DwarfUnit) {
+        let dwarf = write_dwarf(version, |dwarf, encoding| {
+            let unit = write::Unit::new(encoding, write::LineProgram::none());
+            let unit = dwarf.units.add(unit);
+            build(dwarf.units.get_mut(unit));
+        });
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+        (dwarf, unit)
+    }
+
+    fn load(sections: write::Sections<EndianVec<LittleEndian>>) -> Dwarf {
+        let mut data = HashMap::new();
+        sections
+            .for_each(|id, section| -> Result<()> {
+                data.insert(id, Rc::from(section.slice()));
+                Ok(())
+            })
+            .unwrap();
+
+        Dwarf::load(|id| -> Result<DwarfReader> {
+            let section = data.get(&id).cloned().unwrap_or_else(|| Rc::from(&[][..]));
+            Ok(EndianRcSlice::new(section, RunTimeEndian::Little))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn cached_types_match_uncached() {
+        let (dwarf, unit) = typedef_unit();
+        let types = UnitTypes::default();
+
+        let mut offsets = Vec::new();
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            if entry.tag() != gimli::DW_TAG_compile_unit
+                && entry.tag() != gimli::DW_TAG_subrange_type
+            {
+                offsets.push(entry.offset().0);
+            }
+        }
+        assert_eq!(offsets.len(), 5);
+
+        // the second round is answered from the cache
+        for _ in 0..2 {
+            for &offset in &offsets {
+                assert_eq!(
+                    types.type_name(&dwarf, &unit, Some(offset)).unwrap(),
+                    unit_type_name(&dwarf, &unit, Some(offset)).unwrap()
+                );
+                assert_eq!(
+                    types.byte_size(&unit, offset).unwrap(),
+                    unit_type_byte_size(&unit, offset).unwrap()
+                );
+            }
+        }
+        assert_eq!(types.names.borrow().len(), offsets.len());
+        assert_eq!(types.byte_sizes.borrow().len(), offsets.len());
+
+        let names: Vec<_> = offsets
+            .iter()
+            .map(|&offset| types.type_name(&dwarf, &unit, Some(offset)).unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["int", "myint", "const myint", "const myint *", "myint[3]"]
+        );
+        assert_eq!(types.byte_size(&unit, offsets[4]).unwrap(), Some(12));
+    }
+
+    #[test]
+    fn unknown_type_tags() {
+        let (dwarf, unit) = write_unit(4, |unit| {
+            let root = unit.root();
+            // a vendor tag, as another language's producer may emit
+            let vendor = unit.add(root, gimli::DwTag(0x4242));
+            let pointer = unit.add(root, gimli::DW_TAG_pointer_type);
+            unit.get_mut(pointer)
+                .set(gimli::DW_AT_type, write::AttributeValue::UnitRef(vendor));
+            unit.add(root, gimli::DW_TAG_string_type);
+        });
+
+        let mut entries = unit.entries();
+        let mut names = Vec::new();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            if entry.tag() != gimli::DW_TAG_compile_unit {
+                names.push(unit_type_name(&dwarf, &unit, Some(entry.offset().0)).unwrap());
+            }
+        }
+        assert_eq!(
+            names,
+            [
+                "<unknown tag 0x4242>",
+                "<unknown tag 0x4242> *",
+                "<DW_TAG_string_type>"
+            ]
+        );
+    }
+
+    #[test]
+    fn c11_qualifiers() {
+        let (dwarf, unit) = write_unit(5, |unit| {
+            let root = unit.root();
+            let mut add = |tag, ty: Option<write::UnitEntryId>| {
+                let id = unit.add(root, tag);
+                if let Some(ty) = ty {
+                    unit.get_mut(id)
+                        .set(gimli::DW_AT_type, write::AttributeValue::UnitRef(ty));
+                }
+                id
+            };
+            let int = add(gimli::DW_TAG_base_type, None);
+            let pointer = add(gimli::DW_TAG_pointer_type, Some(int));
+            add(gimli::DW_TAG_restrict_type, Some(pointer));
+            let atomic_int = add(gimli::DW_TAG_atomic_type, Some(int));
+            add(gimli::DW_TAG_const_type, Some(atomic_int));
+            // D's `immutable`, which isn't rendered
+            add(gimli::DW_TAG_immutable_type, Some(int));
+            unit.get_mut(int).set(
+                gimli::DW_AT_name,
+                write::AttributeValue::String(b"int".to_vec()),
+            );
+            unit.get_mut(int)
+                .set(gimli::DW_AT_byte_size, write::AttributeValue::Udata(4));
+        });
+
+        let mut entries = unit.entries();
+        let mut types = Vec::new();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            if entry.tag() != gimli::DW_TAG_compile_unit {
+                let offset = entry.offset().0;
+                types.push((
+                    unit_type_name(&dwarf, &unit, Some(offset)).unwrap(),
+                    unit_type_byte_size(&unit, offset).unwrap(),
+                ));
+            }
+        }
+        assert_eq!(
+            types,
+            [
+                ("int".to_string(), Some(4)),
+                ("int *".to_string(), Some(4)),
+                ("int *restrict".to_string(), Some(4)),
+                ("_Atomic int".to_string(), Some(4)),
+                ("const _Atomic int".to_string(), Some(4)),
+                ("int".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn member_and_function_pointers() {
+        let (dwarf, unit) = write_unit(4, |unit| {
+            let root = unit.root();
+            let mut add = |parent, tag, name: Option<&str>, ty: Option<write::UnitEntryId>| {
+                let id = unit.add(parent, tag);
+                let entry = unit.get_mut(id);
+                if let Some(name) = name {
+                    entry.set(
+                        gimli::DW_AT_name,
+                        write::AttributeValue::String(name.as_bytes().to_vec()),
+                    );
+                }
+                if let Some(ty) = ty {
+                    entry.set(gimli::DW_AT_type, write::AttributeValue::UnitRef(ty));
+                }
+                id
+            };
+            let int = add(root, gimli::DW_TAG_base_type, Some("int"), None);
+            let char = add(root, gimli::DW_TAG_base_type, Some("char"), None);
+            let class = add(root, gimli::DW_TAG_class_type, Some("Foo"), None);
+            let this = add(root, gimli::DW_TAG_pointer_type, None, Some(class));
+
+            // `int Foo::get(int)`, whose `this` is artificial
+            let method = add(root, gimli::DW_TAG_subroutine_type, None, Some(int));
+            let this_parameter = add(method, gimli::DW_TAG_formal_parameter, None, Some(this));
+            add(method, gimli::DW_TAG_formal_parameter, None, Some(int));
+            let method_pointer = add(root, gimli::DW_TAG_ptr_to_member_type, None, Some(method));
+            let field_pointer = add(root, gimli::DW_TAG_ptr_to_member_type, None, Some(int));
+
+            // `void (*)(char, ...)`
+            let callback = add(root, gimli::DW_TAG_subroutine_type, None, None);
+            add(callback, gimli::DW_TAG_formal_parameter, None, Some(char));
+            add(callback, gimli::DW_TAG_unspecified_parameters, None, None);
+            add(root, gimli::DW_TAG_pointer_type, None, Some(callback));
+
+            unit.get_mut(this_parameter)
+                .set(gimli::DW_AT_artificial, write::AttributeValue::Flag(true));
+            for pointer in [method_pointer, field_pointer].iter() {
+                unit.get_mut(*pointer).set(
+                    gimli::DW_AT_containing_type,
+                    write::AttributeValue::UnitRef(class),
+                );
+            }
+        });
+
+        let mut names = Vec::new();
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            match entry.tag() {
+                gimli::DW_TAG_subroutine_type
+                | gimli::DW_TAG_ptr_to_member_type
+                | gimli::DW_TAG_pointer_type => {
+                    names.push(unit_type_name(&dwarf, &unit, Some(entry.offset().0)).unwrap())
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(
+            names,
+            [
+                "Foo *",
+                "int (int)",
+                "int (Foo::*)(int)",
+                "int Foo::*",
+                "void (char, ...)",
+                "void (*)(char, ...)"
+            ]
+        );
+    }
+
+    #[test]
+    fn names_through_origins() {
+        let dwarf = write_dwarf(4, |dwarf, encoding| {
+            let decl_unit = dwarf
+                .units
+                .add(write::Unit::new(encoding, write::LineProgram::none()));
+            let unit = dwarf.units.get_mut(decl_unit);
+            let root = unit.root();
+            let declaration = unit.add(root, gimli::DW_TAG_subprogram);
+            let entry = unit.get_mut(declaration);
+            entry.set(
+                gimli::DW_AT_name,
+                write::AttributeValue::String(b"method".to_vec()),
+            );
+            entry.set(gimli::DW_AT_decl_line, write::AttributeValue::Udata(7));
+            entry.set(gimli::DW_AT_declaration, write::AttributeValue::Flag(true));
+
+            let def_unit = dwarf
+                .units
+                .add(write::Unit::new(encoding, write::LineProgram::none()));
+            let unit = dwarf.units.get_mut(def_unit);
+            let root = unit.root();
+            // an out-of-line definition in another unit, and an inlined copy of it
+            let definition = unit.add(root, gimli::DW_TAG_subprogram);
+            unit.get_mut(definition).set(
+                gimli::DW_AT_specification,
+                write::AttributeValue::DebugInfoRef(write::Reference::Entry(
+                    decl_unit,
+                    declaration,
+                )),
+            );
+            let inlined = unit.add(root, gimli::DW_TAG_inlined_subroutine);
+            unit.get_mut(inlined).set(
+                gimli::DW_AT_abstract_origin,
+                write::AttributeValue::UnitRef(definition),
+            );
+            let cyclic = unit.add(root, gimli::DW_TAG_subprogram);
+            unit.get_mut(cyclic).set(
+                gimli::DW_AT_abstract_origin,
+                write::AttributeValue::UnitRef(cyclic),
+            );
+        });
+        let mut headers = dwarf.units();
+        headers.next().unwrap().unwrap();
+        let unit = dwarf.unit(headers.next().unwrap().unwrap()).unwrap();
+
+        let mut entries = unit.entries();
+        entries.next_dfs().unwrap();
+        let mut found = Vec::new();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            found.push((
+                entry_name(&dwarf, &unit, entry).unwrap(),
+                entry_decl_line(&dwarf, &unit, entry).unwrap(),
+            ));
+        }
+        assert_eq!(
+            found,
+            [
+                (Some("method".to_string()), Some(7)),
+                (Some("method".to_string()), Some(7)),
+                (None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn qualified_names() {
+        let (dwarf, unit) = write_unit(4, |unit| {
+            let root = unit.root();
+            let name = |unit: &mut write::Unit, id, name: &str| {
+                unit.get_mut(id).set(
+                    gimli::DW_AT_name,
+                    write::AttributeValue::String(name.as_bytes().to_vec()),
+                )
+            };
+            let namespace = unit.add(root, gimli::DW_TAG_namespace);
+            name(unit, namespace, "ns");
+            let class = unit.add(namespace, gimli::DW_TAG_class_type);
+            name(unit, class, "Widget");
+            let declaration = unit.add(class, gimli::DW_TAG_subprogram);
+            name(unit, declaration, "resize");
+            // an out-of-line definition is qualified by its declaration
+            let definition = unit.add(root, gimli::DW_TAG_subprogram);
+            unit.get_mut(definition).set(
+                gimli::DW_AT_specification,
+                write::AttributeValue::UnitRef(declaration),
+            );
+            let anonymous = unit.add(root, gimli::DW_TAG_namespace);
+            let helper = unit.add(anonymous, gimli::DW_TAG_subprogram);
+            name(unit, helper, "helper");
+            let free = unit.add(root, gimli::DW_TAG_subprogram);
+            name(unit, free, "main");
+        });
+
+        let mut entries = unit.entries();
+        let mut found = Vec::new();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            if entry.tag() == gimli::DW_TAG_subprogram {
+                found.push(entry_qualified_name(&dwarf, &unit, entry).unwrap());
+            }
+        }
+        assert_eq!(
+            found,
+            [
+                Some("ns::Widget::resize".to_string()),
+                Some("ns::Widget::resize".to_string()),
+                Some("(anonymous namespace)::helper".to_string()),
+                Some("main".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn declarations_have_no_code() {
+        let (dwarf, unit) = write_unit(4, |unit| {
+            let root = unit.root();
+            let subprogram = |unit: &mut write::Unit, name: &str, low_pc| {
+                let id = unit.add(root, gimli::DW_TAG_subprogram);
+                let entry = unit.get_mut(id);
+                entry.set(
+                    gimli::DW_AT_name,
+                    write::AttributeValue::String(name.as_bytes().to_vec()),
+                );
+                entry.set(
+                    gimli::DW_AT_low_pc,
+                    write::AttributeValue::Address(write::Address::Constant(low_pc)),
+                );
+                entry.set(gimli::DW_AT_high_pc, write::AttributeValue::Udata(0x10));
+                id
+            };
+            // a declaration some producers give the address of its definition
+            let declaration = subprogram(unit, "method", 0x10);
+            unit.get_mut(declaration)
+                .set(gimli::DW_AT_declaration, write::AttributeValue::Flag(true));
+            subprogram(unit, "method", 0x10);
+            subprogram(unit, "other", 0x20);
+        });
+        let subroutines =
+            subroutine::transform_subprogram(&dwarf, &unit, unit.header.offset()).unwrap();
+        let found: Vec<_> = subroutines
+            .iter()
+            .map(|subroutine| (subroutine.name.as_deref(), subroutine.ranges[0].start))
+            .collect();
+        assert_eq!(found, [(Some("method"), 0x10), (Some("other"), 0x20)]);
+    }
+
+    #[test]
+    fn indexed_block_variables() {
+        let (dwarf, unit) = write_unit(4, |unit| {
+            let root = unit.root();
+            let scope = |unit: &mut write::Unit, parent, tag, name: &str, range: (u64, u64)| {
+                let id = unit.add(parent, tag);
+                let entry = unit.get_mut(id);
+                entry.set(
+                    gimli::DW_AT_name,
+                    write::AttributeValue::String(name.as_bytes().to_vec()),
+                );
+                entry.set(
+                    gimli::DW_AT_low_pc,
+                    write::AttributeValue::Address(write::Address::Constant(range.0)),
+                );
+                entry.set(
+                    gimli::DW_AT_high_pc,
+                    write::AttributeValue::Udata(range.1 - range.0),
+                );
+                id
+            };
+            let variable = |unit: &mut write::Unit, parent, name: &str| {
+                let id = unit.add(parent, gimli::DW_TAG_variable);
+                unit.get_mut(id).set(
+                    gimli::DW_AT_name,
+                    write::AttributeValue::String(name.as_bytes().to_vec()),
+                );
+            };
+            let function = scope(unit, root, gimli::DW_TAG_subprogram, "f", (0x10, 0x40));
+            variable(unit, function, "n");
+            let outer = scope(
+                unit,
+                function,
+                gimli::DW_TAG_lexical_block,
+                "",
+                (0x10, 0x20),
+            );
+            variable(unit, outer, "a");
+            let inner = scope(unit, outer, gimli::DW_TAG_lexical_block, "", (0x18, 0x20));
+            variable(unit, inner, "b");
+            let sibling = scope(
+                unit,
+                function,
+                gimli::DW_TAG_inlined_subroutine,
+                "",
+                (0x20, 0x30),
+            );
+            variable(unit, sibling, "c");
+            // a nested function keeps its locals to itself
+            let nested = scope(unit, function, gimli::DW_TAG_subprogram, "g", (0x40, 0x50));
+            variable(unit, nested, "d");
+        });
+        let subroutines =
+            subroutine::transform_subprogram(&dwarf, &unit, unit.header.offset()).unwrap();
+        let function = subroutines
+            .iter()
+            .find(|subroutine| subroutine.name.as_deref() == Some("f"))
+            .unwrap();
+        let names = |variables: &[&subroutine::IndexedVariable]| -> Vec<String> {
+            variables
+                .iter()
+                .map(|variable| {
+                    let entry = unit.entry(variable.entry_offset).unwrap();
+                    entry_name(&dwarf, &unit, &entry).unwrap().unwrap()
+                })
+                .collect()
+        };
+        assert_eq!(names(&function.variables.in_scope(0x1a)), ["n", "a", "b"]);
+        assert_eq!(names(&function.variables.in_scope(0x12)), ["n", "a"]);
+        assert_eq!(names(&function.variables.in_scope(0x24)), ["n", "c"]);
+        assert_eq!(names(&function.variables.in_scope(0x38)), ["n"]);
+        let scopes: Vec<_> = function
+            .variables
+            .scopes(0x1a)
+            .iter()
+            .map(|scope| names(scope))
+            .collect();
+        assert_eq!(scopes, [vec!["b"], vec!["a"], vec!["n"]]);
+    }
 }
@@ -0,0 +1,938 @@
+/// Demangle an Itanium C++ (`_Z`) or Rust (`_ZN...17h<hash>E`, `_R`) symbol name.
+///
+/// Returns `None` for names without a known mangling prefix, and for the
+/// constructs this small demangler doesn't understand, so callers can fall
+/// back to the raw name.
+pub fn demangle(name: &str) -> Option<String> {
+    if let Some(body) = name.strip_prefix("_R") {
+        return demangle_rust_v0(body);
+    }
+    if let Some(body) = name.strip_prefix("_ZN") {
+        if let Some(demangled) = demangle_rust_legacy(body) {
+            return Some(demangled);
+        }
+    }
+    if let Some(body) = name.strip_prefix("_Z") {
+        let mut demangler = ItaniumDemangler::new(body);
+        let demangled = demangler.encoding().ok()?;
+        return if is_end_or_suffix(&demangler.input[demangler.pos..]) {
+            Some(demangled)
+        } else {
+            None
+        };
+    }
+    None
+}
+
+/// Compilers append suffixes such as `.llvm.1234` or `.cold` to cloned functions
+fn is_end_or_suffix(rest: &[u8]) -> bool {
+    rest.is_empty() || rest[0] == b'.'
+}
+
+fn read_decimal(input: &[u8], pos: &mut usize) -> Option<usize> {
+    let start = *pos;
+    while *pos < input.len() && input[*pos].is_ascii_digit() {
+        *pos += 1;
+    }
+    std::str::from_utf8(&input[start..*pos]).ok()?.parse().ok()
+}
+
+/// Rust symbols before v0 mangling: an Itanium nested name with a hash suffix.
+fn demangle_rust_legacy(body: &str) -> Option<String> {
+    let input = body.as_bytes();
+    let mut pos = 0;
+    let mut components = Vec::new();
+
+    while pos < input.len() && input[pos] != b'E' {
+        let len = read_decimal(input, &mut pos)?;
+        let component = body.get(pos..pos + len)?;
+        components.push(component);
+        pos += len;
+    }
+
+    if pos >= input.len() || !is_end_or_suffix(&input[pos + 1..]) {
+        return None;
+    }
+
+    let hash = components.pop()?;
+    let is_hash = hash.len() == 17
+        && hash.starts_with('h')
+        && hash[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !is_hash || components.is_empty() {
+        return None;
+    }
+
+    let components: Option<Vec<String>> =
+        components.iter().map(|c| unescape_rust_legacy(c)).collect();
+    Some(components?.join("::"))
+}
+
+fn unescape_rust_legacy(component: &str) -> Option<String> {
+    // a leading `_` protects components starting with `$`
+    let component = if component.starts_with("_$") {
+        &component[1..]
+    } else {
+        component
+    };
+    let mut result = String::new();
+    let mut rest = component;
+
+    while !rest.is_empty() {
+        if let Some(stripped) = rest.strip_prefix("..") {
+            result.push_str("::");
+            rest = stripped;
+        } else if rest.starts_with('$') {
+            let end = rest[1..].find('$')? + 1;
+            let escaped = &rest[1..end];
+            let decoded = match escaped {
+                "SP" => '@',
+                "BP" => '*',
+                "RF" => '&',
+                "LT" => '<',
+                "GT" => '>',
+                "LP" => '(',
+                "RP" => ')',
+                "C" => ',',
+                _ => {
+                    let code = escaped.strip_prefix('u')?;
+                    std::char::from_u32(u32::from_str_radix(code, 16).ok()?)?
+                }
+            };
+            result.push(decoded);
+            rest = &rest[end + 1..];
+        } else {
+            let c = rest.chars().next()?;
+            result.push(if c == '.' { '-' } else { c });
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Some(result)
+}
+
+/// Rust v0 mangling, limited to paths made of crate roots and nested identifiers.
+fn demangle_rust_v0(body: &str) -> Option<String> {
+    let input = body.as_bytes();
+    let mut pos = 0;
+
+    // optional encoding version
+    if input.first()?.is_ascii_digit() {
+        read_decimal(input, &mut pos)?;
+    }
+
+    let path = rust_v0_path(body, &mut pos, 0)?;
+
+    // an optional instantiating crate may follow, anything else is unsupported
+    if pos < input.len() && input[pos] != b'.' {
+        rust_v0_path(body, &mut pos, 0)?;
+    }
+
+    if is_end_or_suffix(&input[pos..]) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn rust_v0_path(body: &str, pos: &mut usize, depth: usize) -> Option<String> {
+    let input = body.as_bytes();
+    if depth > 64 {
+        return None;
+    }
+
+    match *input.get(*pos)? {
+        b'C' => {
+            *pos += 1;
+            rust_v0_disambiguator(input, pos)?;
+            rust_v0_identifier(body, pos)
+        }
+        b'N' => {
+            *pos += 2;
+            let namespace = *input.get(*pos - 1)?;
+            let parent = rust_v0_path(body, pos, depth + 1)?;
+            rust_v0_disambiguator(input, pos)?;
+            let name = rust_v0_identifier(body, pos)?;
+
+            Some(match namespace {
+                b'C' => format!("{}::{{closure}}", parent),
+                b'S' => format!("{}::{{shim}}", parent),
+                _ => format!("{}::{}", parent, name),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn rust_v0_disambiguator(input: &[u8], pos: &mut usize) -> Option<()> {
+    if input.get(*pos) == Some(&b's') {
+        while *input.get(*pos)? != b'_' {
+            *pos += 1;
+        }
+        *pos += 1;
+    }
+    Some(())
+}
+
+fn rust_v0_identifier(body: &str, pos: &mut usize) -> Option<String> {
+    let input = body.as_bytes();
+    // punycode identifiers are not supported
+    if input.get(*pos) == Some(&b'u') {
+        return None;
+    }
+
+    let len = read_decimal(input, pos)?;
+    if input.get(*pos) == Some(&b'_') {
+        *pos += 1;
+    }
+    let ident = body.get(*pos..*pos + len)?;
+    *pos += len;
+    Some(ident.to_string())
+}
+
+struct ItaniumDemangler<'a> {
+    input: &'a [u8],
+    pos: usize,
+    substitutions: Vec<String>,
+    template_args: Vec<String>,
+    /// Template parameters inside a function signature refer to the function's arguments
+    in_function_params: bool,
+    depth: usize,
+}
+
+struct NestedName {
+    name: String,
+    /// Template functions other than ctors, dtors and conversions encode a return type
+    has_return_type: bool,
+    cv_qualifiers: String,
+}
+
+type DemangleResult<T> = Result<T, ()>;
+
+impl<'a> ItaniumDemangler<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+            substitutions: Vec::new(),
+            template_args: Vec::new(),
+            in_function_params: false,
+            depth: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.input.get(self.pos + offset).copied()
+    }
+
+    fn consume(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> DemangleResult<()> {
+        if self.consume(c) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn enter(&mut self) -> DemangleResult<()> {
+        self.depth += 1;
+        if self.depth > 128 {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// <encoding> ::= <name> [<bare-function-type>] | <special-name>
+    fn encoding(&mut self) -> DemangleResult<String> {
+        self.enter()?;
+        if let Some(special) = self.special_name()? {
+            self.leave();
+            return Ok(special);
+        }
+
+        let name = self.name()?;
+
+        let result = match self.peek() {
+            None | Some(b'E') | Some(b'.') => name.name,
+            Some(_) => {
+                let in_function_params = self.in_function_params;
+                self.in_function_params = true;
+                if name.has_return_type {
+                    self.type_()?;
+                }
+                let params = self.bare_function_params()?;
+                self.in_function_params = in_function_params;
+                format!("{}({}){}", name.name, params, name.cv_qualifiers)
+            }
+        };
+        self.leave();
+        Ok(result)
+    }
+
+    /// Virtual tables, typeinfo and guard variables; thunks are not supported
+    fn special_name(&mut self) -> DemangleResult<Option<String>> {
+        let prefix = match (self.peek(), self.peek_at(1)) {
+            (Some(b'T'), Some(b'V')) => "vtable for ",
+            (Some(b'T'), Some(b'T')) => "VTT for ",
+            (Some(b'T'), Some(b'I')) => "typeinfo for ",
+            (Some(b'T'), Some(b'S')) => "typeinfo name for ",
+            (Some(b'G'), Some(b'V')) => {
+                self.pos += 2;
+                let name = self.name()?;
+                return Ok(Some(format!("guard variable for {}", name.name)));
+            }
+            (Some(b'T'), Some(b'_')) | (Some(b'T'), Some(b'0'..=b'9')) => return Ok(None),
+            (Some(b'T'), _) | (Some(b'G'), _) => return Err(()),
+            _ => return Ok(None),
+        };
+        self.pos += 2;
+        let ty = self.type_()?;
+        Ok(Some(format!("{}{}", prefix, ty)))
+    }
+
+    fn bare_function_params(&mut self) -> DemangleResult<String> {
+        let mut params = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == b'E' || c == b'.' {
+                break;
+            }
+            params.push(self.type_()?);
+        }
+
+        if params.is_empty() {
+            return Err(());
+        }
+        if params.len() == 1 && params[0] == "void" {
+            return Ok(String::new());
+        }
+        Ok(params.join(", "))
+    }
+
+    fn name(&mut self) -> DemangleResult<NestedName> {
+        match self.peek().ok_or(())? {
+            b'N' => self.nested_name(),
+            b'Z' => {
+                let name = self.local_name()?;
+                Ok(NestedName {
+                    name,
+                    has_return_type: false,
+                    cv_qualifiers: String::new(),
+                })
+            }
+            _ => {
+                let is_substitution = self.peek() == Some(b'S') && self.peek_at(1) != Some(b't');
+                let mut name = if self.peek() == Some(b'S') {
+                    self.substitution_or_std()?
+                } else {
+                    self.unqualified_name("")?
+                };
+
+                let mut has_return_type = false;
+                if self.peek() == Some(b'I') {
+                    if !is_substitution {
+                        self.substitutions.push(name.clone());
+                    }
+                    let args = self.template_args()?;
+                    push_template_args(&mut name, &args);
+                    has_return_type = !name.starts_with("operator ");
+                }
+
+                Ok(NestedName {
+                    name,
+                    has_return_type,
+                    cv_qualifiers: String::new(),
+                })
+            }
+        }
+    }
+
+    /// <nested-name> ::= N [<CV-qualifiers>] [<ref-qualifier>] <prefix> <unqualified-name> E
+    fn nested_name(&mut self) -> DemangleResult<NestedName> {
+        self.expect(b'N')?;
+
+        let mut cv_qualifiers = String::new();
+        if self.consume(b'r') {
+            cv_qualifiers.push_str(" restrict");
+        }
+        if self.consume(b'V') {
+            cv_qualifiers.push_str(" volatile");
+        }
+        if self.consume(b'K') {
+            cv_qualifiers.push_str(" const");
+        }
+        if self.consume(b'R') {
+            cv_qualifiers.push_str(" &");
+        } else if self.consume(b'O') {
+            cv_qualifiers.push_str(" &&");
+        }
+
+        let mut prefix = String::new();
+        let mut last_component = String::new();
+        let mut has_return_type = false;
+        let mut is_structor = false;
+        // substitutions are already in the table
+        let mut is_substitution = false;
+
+        loop {
+            let c = self.peek().ok_or(())?;
+            if c == b'E' {
+                self.pos += 1;
+                break;
+            }
+
+            if !prefix.is_empty() && !is_substitution {
+                self.substitutions.push(prefix.clone());
+            }
+            is_substitution = false;
+
+            match c {
+                b'S' if prefix.is_empty() => {
+                    is_substitution = self.peek_at(1) != Some(b't');
+                    prefix = self.substitution_or_std()?;
+                    last_component = match prefix.as_str() {
+                        // ctors of the abbreviated std classes use the template name
+                        "std::string" | "std::istream" | "std::ostream" | "std::iostream" => {
+                            format!("basic_{}", &prefix[5..])
+                        }
+                        _ => strip_template_args(&prefix).to_string(),
+                    };
+                    has_return_type = false;
+                    is_structor = false;
+                }
+                b'I' => {
+                    if prefix.is_empty() {
+                        return Err(());
+                    }
+                    let args = self.template_args()?;
+                    push_template_args(&mut prefix, &args);
+                    has_return_type = !is_structor;
+                }
+                b'T' => {
+                    prefix = self.template_param()?;
+                    last_component = prefix.clone();
+                    has_return_type = false;
+                    is_structor = false;
+                    is_substitution = true;
+                }
+                _ => {
+                    is_structor = matches!(
+                        (c, self.peek_at(1)),
+                        (b'C', _) | (b'D', Some(b'0'..=b'5')) | (b'c', Some(b'v'))
+                    );
+                    prefix = self.nested_component(prefix, &mut last_component)?;
+                    has_return_type = false;
+                }
+            }
+        }
+
+        if prefix.is_empty() {
+            return Err(());
+        }
+
+        Ok(NestedName {
+            name: prefix,
+            has_return_type,
+            cv_qualifiers,
+        })
+    }
+
+    fn nested_component(
+        &mut self,
+        prefix: String,
+        last_component: &mut String,
+    ) -> DemangleResult<String> {
+        let component = self.unqualified_name(last_component)?;
+        *last_component = match component.strip_prefix('~') {
+            Some(name) => name.to_string(),
+            None => component.clone(),
+        };
+
+        Ok(if prefix.is_empty() {
+            component
+        } else {
+            format!("{}::{}", prefix, component)
+        })
+    }
+
+    /// <local-name> ::= Z <encoding> E <entity name> [<discriminator>]
+    fn local_name(&mut self) -> DemangleResult<String> {
+        self.expect(b'Z')?;
+        let function = self.encoding()?;
+        self.expect(b'E')?;
+
+        let entity = if self.consume(b's') {
+            "string literal".to_string()
+        } else {
+            self.name()?.name
+        };
+
+        if self.consume(b'_') {
+            if self.consume(b'_') {
+                read_decimal(self.input, &mut self.pos).ok_or(())?;
+                self.expect(b'_')?;
+            } else {
+                read_decimal(self.input, &mut self.pos).ok_or(())?;
+            }
+        }
+        Ok(format!("{}::{}", function, entity))
+    }
+
+    fn unqualified_name(&mut self, enclosing_class: &str) -> DemangleResult<String> {
+        let mut name = self.unqualified_name_without_tags(enclosing_class)?;
+        while self.consume(b'B') {
+            let tag = self.source_name()?;
+            name.push_str(&format!("[abi:{}]", tag));
+        }
+        Ok(name)
+    }
+
+    fn unqualified_name_without_tags(&mut self, enclosing_class: &str) -> DemangleResult<String> {
+        match self.peek().ok_or(())? {
+            b'0'..=b'9' => self.source_name(),
+            // names with internal linkage, e.g. of `static` functions
+            b'L' => {
+                self.pos += 1;
+                self.source_name()
+            }
+            b'C' => {
+                self.pos += 1;
+                self.consume(b'I');
+                match self.peek() {
+                    Some(b'1'..=b'5') => self.pos += 1,
+                    _ => return Err(()),
+                }
+                Ok(strip_template_args(enclosing_class).to_string())
+            }
+            b'D' if matches!(self.peek_at(1), Some(b'0'..=b'5')) => {
+                self.pos += 2;
+                Ok(format!("~{}", strip_template_args(enclosing_class)))
+            }
+            b'a'..=b'z' => self.operator_name(),
+            _ => Err(()),
+        }
+    }
+
+    fn source_name(&mut self) -> DemangleResult<String> {
+        let len = read_decimal(self.input, &mut self.pos).ok_or(())?;
+        let name = self.input.get(self.pos..self.pos + len).ok_or(())?;
+        self.pos += len;
+
+        let name = std::str::from_utf8(name).map_err(|_| ())?;
+        if name.starts_with("_GLOBAL__N") {
+            Ok("(anonymous namespace)".to_string())
+        } else {
+            Ok(name.to_string())
+        }
+    }
+
+    fn operator_name(&mut self) -> DemangleResult<String> {
+        let code = self.input.get(self.pos..self.pos + 2).ok_or(())?;
+        self.pos += 2;
+
+        let op = match code {
+            b"nw" => " new",
+            b"na" => " new[]",
+            b"dl" => " delete",
+            b"da" => " delete[]",
+            b"ps" | b"pl" => "+",
+            b"ng" | b"mi" => "-",
+            b"ad" | b"an" => "&",
+            b"de" | b"ml" => "*",
+            b"co" => "~",
+            b"dv" => "/",
+            b"rm" => "%",
+            b"or" => "|",
+            b"eo" => "^",
+            b"aS" => "=",
+            b"pL" => "+=",
+            b"mI" => "-=",
+            b"mL" => "*=",
+            b"dV" => "/=",
+            b"rM" => "%=",
+            b"aN" => "&=",
+            b"oR" => "|=",
+            b"eO" => "^=",
+            b"ls" => "<<",
+            b"rs" => ">>",
+            b"lS" => "<<=",
+            b"rS" => ">>=",
+            b"eq" => "==",
+            b"ne" => "!=",
+            b"lt" => "<",
+            b"gt" => ">",
+            b"le" => "<=",
+            b"ge" => ">=",
+            b"ss" => "<=>",
+            b"nt" => "!",
+            b"aa" => "&&",
+            b"oo" => "||",
+            b"pp" => "++",
+            b"mm" => "--",
+            b"cm" => ",",
+            b"pm" => "->*",
+            b"pt" => "->",
+            b"cl" => "()",
+            b"ix" => "[]",
+            b"cv" => {
+                let ty = self.type_()?;
+                return Ok(format!("operator {}", ty));
+            }
+            b"li" => {
+                let name = self.source_name()?;
+                return Ok(format!("operator\"\" {}", name));
+            }
+            _ => return Err(()),
+        };
+        Ok(format!("operator{}", op))
+    }
+
+    fn template_args(&mut self) -> DemangleResult<String> {
+        self.enter()?;
+        self.expect(b'I')?;
+
+        let mut args = Vec::new();
+        while !self.consume(b'E') {
+            args.push(self.template_arg()?);
+        }
+        self.leave();
+
+        if !self.in_function_params {
+            self.template_args = args.clone();
+        }
+        let joined = args.join(", ");
+        // keep `>>` apart like c++filt does
+        Ok(if joined.ends_with('>') {
+            format!("<{} >", joined)
+        } else {
+            format!("<{}>", joined)
+        })
+    }
+
+    fn template_arg(&mut self) -> DemangleResult<String> {
+        match self.peek().ok_or(())? {
+            b'L' => self.expr_primary(),
+            b'J' => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                while !self.consume(b'E') {
+                    args.push(self.template_arg()?);
+                }
+                Ok(args.join(", "))
+            }
+            // template argument expressions are not supported
+            b'X' => Err(()),
+            _ => self.type_(),
+        }
+    }
+
+    /// <expr-primary> ::= L <type> <value number> E | L <mangled-name> E
+    fn expr_primary(&mut self) -> DemangleResult<String> {
+        self.expect(b'L')?;
+
+        if self.consume(b'_') {
+            self.expect(b'Z')?;
+            let name = self.encoding()?;
+            self.expect(b'E')?;
+            return Ok(name);
+        }
+        if self.consume(b'Z') {
+            let name = self.encoding()?;
+            self.expect(b'E')?;
+            return Ok(name);
+        }
+
+        let ty = self.type_()?;
+        let negative = self.consume(b'n');
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c == b'E' {
+                break;
+            }
+            self.pos += 1;
+        }
+        let value = std::str::from_utf8(&self.input[start..self.pos]).map_err(|_| ())?;
+        self.expect(b'E')?;
+
+        let sign = if negative { "-" } else { "" };
+        Ok(match ty.as_str() {
+            "bool" if value == "0" => "false".to_string(),
+            "bool" if value == "1" => "true".to_string(),
+            "int" => format!("{}{}", sign, value),
+            "unsigned int" => format!("{}{}u", sign, value),
+            "long" => format!("{}{}l", sign, value),
+            "unsigned long" => format!("{}{}ul", sign, value),
+            _ => format!("({}){}{}", ty, sign, value),
+        })
+    }
+
+    fn template_param(&mut self) -> DemangleResult<String> {
+        self.expect(b'T')?;
+        let index = if self.consume(b'_') {
+            0
+        } else {
+            let index = read_decimal(self.input, &mut self.pos).ok_or(())? + 1;
+            self.expect(b'_')?;
+            index
+        };
+
+        let param = self.template_args.get(index).cloned().ok_or(())?;
+        self.substitutions.push(param.clone());
+        Ok(param)
+    }
+
+    /// <substitution>, or `St` for names in `std::`
+    fn substitution_or_std(&mut self) -> DemangleResult<String> {
+        self.expect(b'S')?;
+
+        let c = self.peek().ok_or(())?;
+        let special = match c {
+            b't' => {
+                self.pos += 1;
+                let name = self.unqualified_name("")?;
+                return Ok(format!("std::{}", name));
+            }
+            b'a' => "std::allocator",
+            b'b' => "std::basic_string",
+            b's' => "std::string",
+            b'i' => "std::istream",
+            b'o' => "std::ostream",
+            b'd' => "std::iostream",
+            b'_' => {
+                self.pos += 1;
+                return self.substitutions.first().cloned().ok_or(());
+            }
+            _ => {
+                let start = self.pos;
+                while let Some(c) = self.peek() {
+                    if c == b'_' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                let seq_id = std::str::from_utf8(&self.input[start..self.pos]).map_err(|_| ())?;
+                let index = usize::from_str_radix(seq_id, 36).map_err(|_| ())? + 1;
+                self.expect(b'_')?;
+                return self.substitutions.get(index).cloned().ok_or(());
+            }
+        };
+        self.pos += 1;
+        Ok(special.to_string())
+    }
+
+    fn type_(&mut self) -> DemangleResult<String> {
+        self.enter()?;
+        let ty = self.type_inner();
+        self.leave();
+        ty
+    }
+
+    fn type_inner(&mut self) -> DemangleResult<String> {
+        let c = self.peek().ok_or(())?;
+        if let Some(builtin) = builtin_type(c) {
+            self.pos += 1;
+            return Ok(builtin.to_string());
+        }
+
+        let ty = match c {
+            b'P' | b'R' | b'O' => {
+                self.pos += 1;
+                let inner = self.type_()?;
+                let suffix = match c {
+                    b'P' => "*",
+                    b'R' => "&",
+                    _ => "&&",
+                };
+                match inner.find('(') {
+                    // pointers to functions: `void (*)(int)`
+                    Some(pos) if inner.ends_with(')') && !inner.contains("(*") => {
+                        format!("{}({}){}", &inner[..pos], suffix, &inner[pos..])
+                    }
+                    _ => format!("{}{}", inner, suffix),
+                }
+            }
+            b'K' | b'V' | b'r' => {
+                self.pos += 1;
+                let inner = self.type_()?;
+                let qualifier = match c {
+                    b'K' => "const",
+                    b'V' => "volatile",
+                    _ => "restrict",
+                };
+                format!("{} {}", inner, qualifier)
+            }
+            b'A' => {
+                self.pos += 1;
+                let size = if self.peek() == Some(b'_') {
+                    String::new()
+                } else {
+                    read_decimal(self.input, &mut self.pos)
+                        .ok_or(())?
+                        .to_string()
+                };
+                self.expect(b'_')?;
+                let inner = self.type_()?;
+                format!("{} [{}]", inner, size)
+            }
+            b'F' => {
+                self.pos += 1;
+                self.consume(b'Y');
+                let ret = self.type_()?;
+                let params = self.bare_function_params()?;
+                self.expect(b'E')?;
+                format!("{} ({})", ret, params)
+            }
+            b'M' => {
+                self.pos += 1;
+                let class = self.type_()?;
+                let member = self.type_()?;
+                match member.find(" (") {
+                    // member functions: `void (Foo::*)(int)`
+                    Some(pos) if member.ends_with(')') => {
+                        format!("{} ({}::*){}", &member[..pos], class, &member[pos + 1..])
+                    }
+                    _ => format!("{} {}::*", member, class),
+                }
+            }
+            b'T' => return self.template_param(),
+            b'S' if self.peek_at(1) != Some(b't') => {
+                let mut ty = self.substitution_or_std()?;
+                if self.peek() == Some(b'I') {
+                    ty.push_str(&self.template_args()?);
+                } else {
+                    return Ok(ty);
+                }
+                ty
+            }
+            b'D' => {
+                let ty = match self.peek_at(1).ok_or(())? {
+                    b'n' => "decltype(nullptr)",
+                    b'a' => "auto",
+                    b'c' => "decltype(auto)",
+                    b's' => "char16_t",
+                    b'i' => "char32_t",
+                    b'u' => "char8_t",
+                    b'h' => "half",
+                    b'p' => {
+                        self.pos += 2;
+                        let inner = self.type_()?;
+                        return Ok(format!("{}...", inner));
+                    }
+                    _ => return Err(()),
+                };
+                self.pos += 2;
+                return Ok(ty.to_string());
+            }
+            b'u' => {
+                self.pos += 1;
+                self.source_name()?
+            }
+            b'N' | b'Z' | b'S' | b'0'..=b'9' => self.name()?.name,
+            _ => return Err(()),
+        };
+
+        self.substitutions.push(ty.clone());
+        Ok(ty)
+    }
+}
+
+fn builtin_type(c: u8) -> Option<&'static str> {
+    Some(match c {
+        b'v' => "void",
+        b'w' => "wchar_t",
+        b'b' => "bool",
+        b'c' => "char",
+        b'a' => "signed char",
+        b'h' => "unsigned char",
+        b's' => "short",
+        b't' => "unsigned short",
+        b'i' => "int",
+        b'j' => "unsigned int",
+        b'l' => "long",
+        b'm' => "unsigned long",
+        b'x' => "long long",
+        b'y' => "unsigned long long",
+        b'n' => "__int128",
+        b'o' => "unsigned __int128",
+        b'f' => "float",
+        b'd' => "double",
+        b'e' => "long double",
+        b'g' => "__float128",
+        b'z' => "...",
+        _ => return None,
+    })
+}
+
+fn push_template_args(name: &mut String, args: &str) {
+    // `operator<< <int>` rather than `operator<<<int>`
+    if name.ends_with('<') {
+        name.push(' ');
+    }
+    name.push_str(args);
+}
+
+fn strip_template_args(name: &str) -> &str {
+    let name = name.rsplit("::").next().unwrap_or(name);
+    match name.find('<') {
+        Some(pos) => &name[..pos],
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demangle_names() {
+        let names = [
+            (
+                "_ZN2ns6Widget6resizeEii",
+                Some("ns::Widget::resize(int, int)"),
+            ),
+            ("_ZNK3Foo3getEv", Some("Foo::get() const")),
+            ("_ZL3bazv", Some("baz()")),
+            ("_Z3fooPKcRi", Some("foo(char const*, int&)")),
+            ("_ZN5outer5innerIiE3getEv", Some("outer::inner<int>::get()")),
+            ("_ZN2ns6WidgetD1Ev", Some("ns::Widget::~Widget()")),
+            (
+                "_ZN12_GLOBAL__N_16helperEv",
+                Some("(anonymous namespace)::helper()"),
+            ),
+            // clones keep the name of the function they were cloned from
+            (
+                "_ZN2ns6Widget6resizeEii.cold",
+                Some("ns::Widget::resize(int, int)"),
+            ),
+            (
+                "_ZN4core3fmt5write17h0123456789abcdefE",
+                Some("core::fmt::write"),
+            ),
+            (
+                "_ZN4core3ptr13drop_in_place17hffffffffffffffffE.llvm.123",
+                Some("core::ptr::drop_in_place"),
+            ),
+            ("_RNvCs1234_7mycrate3foo", Some("mycrate::foo")),
+            ("_Zbogus", None),
+            ("_ZN3foo", None),
+            ("main", None),
+        ];
+        for (mangled, demangled) in names.iter() {
+            assert_eq!(demangle(mangled).as_deref(), *demangled, "{}", mangled);
+        }
+    }
+}
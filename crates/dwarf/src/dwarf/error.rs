@@ -0,0 +1,91 @@
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// Error kinds reported to JS callers
+#[derive(Debug)]
+pub enum BridgeError {
+    /// The requested symbol (variable, subroutine, ...) doesn't exist
+    NotFound(String),
+    /// The debug information itself can't be parsed
+    InvalidDwarf(String),
+    /// The debug information uses a construct this crate can't evaluate yet
+    Unsupported(String),
+    /// The variable exists but has no location at the current pc
+    OptimizedOut(String),
+    /// The value depends on data the caller didn't supply, such as memory
+    Unavailable(String),
+    /// Any other failure during evaluation
+    Internal(String),
+}
+
+impl BridgeError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BridgeError::NotFound(_) => "NotFound",
+            BridgeError::InvalidDwarf(_) => "InvalidDwarf",
+            BridgeError::Unsupported(_) => "Unsupported",
+            BridgeError::OptimizedOut(_) => "OptimizedOut",
+            BridgeError::Unavailable(_) => "Unavailable",
+            BridgeError::Internal(_) => "Internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            BridgeError::NotFound(msg)
+            | BridgeError::InvalidDwarf(msg)
+            | BridgeError::Unsupported(msg)
+            | BridgeError::OptimizedOut(msg)
+            | BridgeError::Unavailable(msg)
+            | BridgeError::Internal(msg) => msg,
+        }
+    }
+
+    /// Classify an error raised anywhere in the crate
+    pub fn from_anyhow(err: anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<BridgeError>() {
+                return match e {
+                    BridgeError::NotFound(_) => BridgeError::NotFound(err.to_string()),
+                    BridgeError::InvalidDwarf(_) => BridgeError::InvalidDwarf(err.to_string()),
+                    BridgeError::Unsupported(_) => BridgeError::Unsupported(err.to_string()),
+                    BridgeError::OptimizedOut(_) => BridgeError::OptimizedOut(err.to_string()),
+                    BridgeError::Unavailable(_) => BridgeError::Unavailable(err.to_string()),
+                    BridgeError::Internal(_) => BridgeError::Internal(err.to_string()),
+                };
+            }
+            if cause.downcast_ref::<gimli::Error>().is_some()
+                || cause
+                    .downcast_ref::<wasmparser::BinaryReaderError>()
+                    .is_some()
+            {
+                return BridgeError::InvalidDwarf(err.to_string());
+            }
+        }
+        BridgeError::Internal(err.to_string())
+    }
+
+    /// Convert into a JS `Error` carrying a `kind` property
+    pub fn into_js_value(self) -> JsValue {
+        let error = js_sys::Error::new(self.message());
+        error.set_name("BridgeError");
+        let _ = js_sys::Reflect::set(
+            &error,
+            &JsValue::from_str("kind"),
+            &JsValue::from_str(self.kind()),
+        );
+        error.into()
+    }
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for BridgeError {}
+
+pub(crate) fn to_js_error(err: anyhow::Error) -> JsValue {
+    BridgeError::from_anyhow(err).into_js_value()
+}
@@ -1,53 +1,1893 @@
 use anyhow::{anyhow, Result};
+use gimli::{Endianity, RunTimeEndian};
 use num_bigint::{BigInt, BigUint};
+use std::convert::TryFrom;
 
-use super::VariableInfo;
+use super::error::BridgeError;
+use super::variables::VariableName;
+use super::{MemorySlice, SourceLanguage, StringLayout, VariableInfo, VariantPart};
+
+/// Raw dumps of values no formatter understands stop after this many bytes
+const MAX_DUMP_BYTES: usize = 64;
+
+/// Nesting of aggregates shown before `[...]` or `{...}` stands in for the rest
+pub(crate) const MAX_FORMAT_DEPTH: usize = 8;
+
+/// How much of a value the formatters show, set on the container
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FormatLimits {
+    /// Levels of arrays, enum fields and pointees expanded
+    pub(crate) max_depth: usize,
+    /// Elements or fields shown of each aggregate
+    pub(crate) max_children: usize,
+    /// Characters shown of a string
+    pub(crate) max_string_length: usize,
+}
+
+impl Default for FormatLimits {
+    fn default() -> Self {
+        FormatLimits {
+            max_depth: MAX_FORMAT_DEPTH,
+            max_children: MAX_ARRAY_ELEMENTS,
+            max_string_length: MAX_STRING_LENGTH,
+        }
+    }
+}
+
+impl FormatLimits {
+    /// Limits of the values nested one level down, `None` once no more levels
+    /// are expanded
+    fn nested(&self) -> Option<FormatLimits> {
+        Some(FormatLimits {
+            max_depth: self.max_depth.checked_sub(1)?,
+            ..*self
+        })
+    }
+}
 
 pub fn format_object(varinfo: &VariableInfo) -> Result<String> {
+    format_nested(varinfo, &varinfo.limits)
+}
+
+fn format_nested(varinfo: &VariableInfo, limits: &FormatLimits) -> Result<String> {
+    if let Some(marker) = unavailable_marker(varinfo) {
+        return Ok(format!("({}){}", varinfo.name, marker));
+    }
+    if let Some(value) = unpack_bit_field(varinfo)? {
+        return format_nested(&value, limits);
+    }
+    // values of unknown types are still shown as their bytes, if they were read
+    format_typed_object(varinfo, limits).or_else(|e| match value_bytes(varinfo) {
+        Ok(bytes) if !bytes.is_empty() => Ok(format_raw_bytes(varinfo, bytes)),
+        _ => Err(e),
+    })
+}
+
+/// Marker shown instead of a value whose location lacks some of its pieces
+fn unavailable_marker(varinfo: &VariableInfo) -> Option<&'static str> {
+    if varinfo.unavailable_bits.is_empty() {
+        return None;
+    }
+    let missing: usize = varinfo.unavailable_bits.iter().map(|bits| bits.len()).sum();
+    if missing >= varinfo.byte_size * 8 {
+        Some("<optimized out>")
+    } else {
+        Some("<partially available>")
+    }
+}
+
+/// Hex dump of a value, e.g. `(long double)0x01 0x00 0xff @ 0x1000`
+fn format_raw_bytes(varinfo: &VariableInfo, bytes: &[u8]) -> String {
+    let mut dump: Vec<_> = bytes
+        .iter()
+        .take(MAX_DUMP_BYTES)
+        .map(|b| format!("{:#04x}", b))
+        .collect();
+    if bytes.len() > MAX_DUMP_BYTES {
+        dump.push("...".to_string());
+    }
+    // constants have no address
+    match varinfo.memory_slice.address {
+        0 => format!("({}){}", varinfo.name, dump.join(" ")),
+        address => format!("({}){} @ {:#x}", varinfo.name, dump.join(" "), address),
+    }
+}
+
+/// Bytes shown per row of a memory dump
+const MEMORY_ROW_BYTES: usize = 16;
+
+/// Hex dump of raw memory, one row of up to 16 bytes per line, e.g.
+/// `0x1000: 0x2a 0x00 0x00 0x00`
+pub(crate) fn format_memory(slice: &MemorySlice) -> Result<String> {
+    let bytes = slice.memory_slice.get(0..slice.byte_size).ok_or_else(|| {
+        anyhow!(
+            "{} bytes at {:#x} were not read",
+            slice.byte_size,
+            slice.address
+        )
+    })?;
+    let rows: Vec<_> = bytes
+        .chunks(MEMORY_ROW_BYTES)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let dump: Vec<_> = chunk.iter().map(|b| format!("{:#04x}", b)).collect();
+            let address = slice.address + row * MEMORY_ROW_BYTES;
+            format!("{:#x}: {}", address, dump.join(" "))
+        })
+        .collect();
+    Ok(rows.join("\n"))
+}
+
+/// The value of a bit-field as its declared type, with the bits it occupies
+/// shifted down and sign-extended for signed types
+fn unpack_bit_field(varinfo: &VariableInfo) -> Result<Option<VariableInfo>> {
+    let field = match varinfo.bit_field {
+        Some(field) => field,
+        None => return Ok(None),
+    };
+    let bytes = value_bytes(varinfo)?;
+    let shift = if varinfo.endian.is_big_endian() {
+        (bytes.len() * 8)
+            .checked_sub(field.bit_offset + field.bit_size)
+            .ok_or_else(|| anyhow!("bit-field {} overruns its bytes", varinfo.name))?
+    } else {
+        field.bit_offset
+    };
+    let one = BigUint::from(1u8);
+    let mut value =
+        (unsigned_from_bytes(bytes, varinfo.endian) >> shift) & ((&one << field.bit_size) - 1u8);
+
+    let value_bits = field.value_size * 8;
+    let signed = matches!(
+        varinfo.encoding,
+        gimli::DW_ATE_signed | gimli::DW_ATE_signed_char
+    );
+    if signed && (1..value_bits).contains(&field.bit_size) && value.bit(field.bit_size as u64 - 1) {
+        value += (&one << value_bits) - (&one << field.bit_size);
+    }
+    let mut value_bytes = value.to_bytes_le();
+    value_bytes.resize(field.value_size, 0);
+    if varinfo.endian.is_big_endian() {
+        value_bytes.reverse();
+    }
+
+    let mut unpacked = varinfo.clone();
+    unpacked.byte_size = field.value_size;
+    unpacked.memory_slice.memory_slice = value_bytes;
+    unpacked.bit_field = None;
+    Ok(Some(unpacked))
+}
+
+fn format_typed_object(varinfo: &VariableInfo, limits: &FormatLimits) -> Result<String> {
+    if let Some((text, truncated)) = string_text(varinfo, limits) {
+        return Ok(quote_text(&text, truncated));
+    }
+
     match varinfo.tag {
         gimli::DW_TAG_base_type => {
             let name = &varinfo.name;
             let byte_size = varinfo.byte_size;
             let encoding = varinfo.encoding;
-            let mut bytes = Vec::new();
-            bytes.extend_from_slice(&varinfo.memory_slice.memory_slice[0..(byte_size as usize)]);
+            let bytes = value_bytes(varinfo)?;
 
             match encoding {
+                gimli::DW_ATE_signed_char | gimli::DW_ATE_unsigned_char if byte_size == 1 => {
+                    let c = bytes[0];
+                    if c.is_ascii_graphic() || c == b' ' {
+                        Ok(format!("({})'{}'", name, c as char))
+                    } else if encoding == gimli::DW_ATE_signed_char {
+                        Ok(format!("({}){}", name, c as i8))
+                    } else {
+                        Ok(format!("({}){}", name, c))
+                    }
+                }
                 gimli::DW_ATE_signed | gimli::DW_ATE_signed_char => {
-                    let value = BigInt::from_signed_bytes_le(&bytes);
+                    let value = signed_from_bytes(bytes, varinfo.endian);
                     Ok(format!("({}){}", name, value))
                 }
                 gimli::DW_ATE_unsigned | gimli::DW_ATE_unsigned_char => {
-                    let value = BigUint::from_bytes_le(&bytes);
+                    let value = unsigned_from_bytes(bytes, varinfo.endian);
                     Ok(format!("({}){}", name, value))
                 }
                 gimli::DW_ATE_boolean => {
-                    let value = match bytes[0] {
-                        0 => false,
-                        _ => true,
-                    };
+                    let value = bytes.iter().any(|b| *b != 0);
                     Ok(format!("({}){}", name, value))
                 }
+                gimli::DW_ATE_UTF => {
+                    let value = unsigned_from_bytes(bytes, varinfo.endian);
+                    let c = match u32::try_from(&value).ok().and_then(char::from_u32) {
+                        Some(c) => c,
+                        None => return Ok(format!("({}){}", name, value)),
+                    };
+                    match varinfo.language {
+                        SourceLanguage::Rust => Ok(format!("({})'{}'", name, c.escape_debug())),
+                        // char8_t, char16_t and char32_t literals
+                        _ if !c.is_control() => {
+                            let prefix = match byte_size {
+                                1 => "u8",
+                                2 => "u",
+                                _ => "U",
+                            };
+                            Ok(format!("({}){}'{}'", name, prefix, c))
+                        }
+                        _ => Ok(format!("({}){}", name, value)),
+                    }
+                }
                 gimli::DW_ATE_float => match byte_size {
                     4 => {
-                        let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                        let value = varinfo.endian.read_f32(bytes);
                         Ok(format!("({}){}", name, value))
                     }
                     8 => {
-                        let value = f64::from_le_bytes([
-                            bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6],
-                            bytes[7],
-                        ]);
+                        let value = varinfo.endian.read_f64(bytes);
                         Ok(format!("({}){}", name, value))
                     }
-                    _ => unimplemented!(),
+                    other => Err(anyhow!(format!("unsupported float size: {}", other))),
                 },
                 other => Err(anyhow!(format!("unsupported attribute type: {}", other))),
             }
         }
+        gimli::DW_TAG_enumeration_type => {
+            let bytes = value_bytes(varinfo)?;
+            let value = match varinfo.encoding {
+                gimli::DW_ATE_signed | gimli::DW_ATE_signed_char => {
+                    signed_from_bytes(bytes, varinfo.endian)
+                }
+                _ => BigInt::from(unsigned_from_bytes(bytes, varinfo.endian)),
+            };
+
+            // flag combinations and out-of-range values have no enumerator
+            match varinfo.enumerators.iter().find(|(_, v)| *v == value) {
+                Some((enumerator, _)) => {
+                    Ok(format!("{}::{} ({})", varinfo.name, enumerator, value))
+                }
+                None => Ok(format!("({}){}", varinfo.name, value)),
+            }
+        }
+        gimli::DW_TAG_array_type => {
+            let element = varinfo
+                .element
+                .as_ref()
+                .ok_or_else(|| anyhow!("array without element type"))?;
+            let bytes = value_bytes(varinfo)?;
+            format_array(
+                element,
+                &varinfo.dimensions,
+                &varinfo.strides(),
+                bytes,
+                limits,
+            )
+        }
+        gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
+            match varinfo.variant_part {
+                Some(ref part) => format_variant(varinfo, part, limits),
+                None => Ok(varinfo.name.clone()),
+            }
+        }
+        gimli::DW_TAG_pointer_type => {
+            let address = pointer_address(varinfo)?;
+            if address == 0 {
+                return Ok("null".to_string());
+            }
+
+            match (&varinfo.pointee, limits.nested()) {
+                (Some(pointee), Some(nested)) if pointee.is_completed() => {
+                    let value =
+                        format_nested(pointee, &nested).unwrap_or_else(|_| "<failure>".to_string());
+                    Ok(format!("{:#x} -> {}", address, value))
+                }
+                _ => Ok(format!("({}){:#x}", varinfo.name, address)),
+            }
+        }
+        _ => Err(anyhow!("unsupported DIE type")),
+    }
+}
+
+fn pointer_address(varinfo: &VariableInfo) -> Result<u64> {
+    match varinfo.pointee_address {
+        Some(address) => Ok(address),
+        None => {
+            let bytes = varinfo
+                .memory_slice
+                .memory_slice
+                .get(0..varinfo.byte_size)
+                .ok_or_else(|| anyhow!("memory slice is too short for a pointer"))?;
+            Ok(address_from_bytes(bytes, varinfo.endian))
+        }
+    }
+}
+
+/// Format the active variant of a Rust enum, e.g. `Some { 0: (i32)5 }`
+fn format_variant(
+    varinfo: &VariableInfo,
+    part: &VariantPart,
+    limits: &FormatLimits,
+) -> Result<String> {
+    let bytes = value_bytes(varinfo)?;
+    let variant = match part.discriminant {
+        Some((offset, ref discriminant)) => {
+            let size = discriminant.byte_size;
+            let raw = bytes
+                .get(offset..offset + size)
+                .filter(|raw| raw.len() <= 8)
+                .ok_or_else(|| anyhow!("discriminant of {} is out of reach", varinfo.name))?;
+            let signed = matches!(
+                discriminant.encoding,
+                gimli::DW_ATE_signed | gimli::DW_ATE_signed_char
+            );
+            let value = u64::try_from(unsigned_from_bytes(raw, varinfo.endian))?;
+            let value = discriminant_value(value, size, signed);
+
+            let selected = part
+                .variants
+                .iter()
+                .find(|variant| match variant.discriminants {
+                    Some(ref ranges) => ranges.iter().any(|(low, high)| {
+                        (discriminant_value(*low, size, signed)
+                            ..=discriminant_value(*high, size, signed))
+                            .contains(&value)
+                    }),
+                    None => false,
+                })
+                .or_else(|| {
+                    part.variants
+                        .iter()
+                        .find(|variant| variant.discriminants.is_none())
+                });
+            match selected {
+                Some(variant) => variant,
+                None => return Ok(format!("({}){}", varinfo.name, value)),
+            }
+        }
+        None => part
+            .variants
+            .first()
+            .ok_or_else(|| anyhow!("{} has no variants", varinfo.name))?,
+    };
+
+    if variant.fields.is_empty() {
+        return Ok(variant.name.clone());
+    }
+    let nested = match limits.nested() {
+        Some(nested) => nested,
+        None => return Ok(format!("{} {{...}}", variant.name)),
+    };
+    let mut fields = Vec::new();
+    for (name, offset, field) in variant.fields.iter().take(limits.max_children) {
+        let value = match field {
+            Some(field) => {
+                let mut field = field.clone();
+                let end = (*offset + field.byte_size).min(bytes.len());
+                field.memory_slice =
+                    MemorySlice::from_u8_vec(bytes.get(*offset..end).unwrap_or_default().to_vec());
+                format_nested(&field, &nested).unwrap_or_else(|_| "<failure>".to_string())
+            }
+            None => "<failure>".to_string(),
+        };
+        // tuple fields are named `__0`, `__1`, ...
+        let name = match name.strip_prefix("__") {
+            Some(index) if index.chars().all(|c| c.is_ascii_digit()) => index,
+            _ => name,
+        };
+        fields.push(format!("{}: {}", name, value));
+    }
+    if variant.fields.len() > limits.max_children {
+        fields.push("...".to_string());
+    }
+    Ok(format!("{} {{ {} }}", variant.name, fields.join(", ")))
+}
+
+/// A discriminant of `size` bytes, sign extended if it is signed
+fn discriminant_value(raw: u64, size: usize, signed: bool) -> i128 {
+    let bits = (size * 8).min(64) as u32;
+    if bits == 0 {
+        return 0;
+    }
+    let unused = 64 - bits;
+    match signed {
+        true => (((raw << unused) as i64) >> unused) as i128,
+        false => ((raw << unused) >> unused) as i128,
+    }
+}
+
+/// Longer arrays are cut off with an ellipsis, unless the container is set to
+/// show more
+pub(crate) const MAX_ARRAY_ELEMENTS: usize = 100;
+
+/// Elements shown of an array whose length couldn't be determined
+pub(crate) const UNKNOWN_LENGTH_ELEMENTS: usize = 8;
+
+fn format_array(
+    element: &VariableInfo,
+    dimensions: &[Option<u64>],
+    strides: &[usize],
+    bytes: &[u8],
+    limits: &FormatLimits,
+) -> Result<String> {
+    let (count, inner) = match dimensions.split_first() {
+        Some((count, inner)) => (count, inner),
+        None => {
+            let mut element = element.clone();
+            element.memory_slice = MemorySlice::from_u8_vec(bytes.to_vec());
+            return format_nested(&element, limits);
+        }
+    };
+    // character arrays are strings at any depth
+    match count {
+        Some(count) if inner.is_empty() && is_character(element) => {
+            let end = (*count as usize * element.byte_size).min(bytes.len());
+            let (text, truncated) = terminated_text(element, &bytes[..end], limits);
+            return Ok(quote_text(&text, truncated));
+        }
+        _ => {}
+    }
+    let nested = match limits.nested() {
+        Some(nested) => nested,
+        None => return Ok("[...]".to_string()),
+    };
+    let stride = strides.first().copied().unwrap_or(0);
+    let count = match count {
+        Some(count) => *count as usize,
+        // only the elements read in place of the unknown length are shown
+        None if stride == 0 || bytes.len() < stride => return Ok("[...]".to_string()),
+        None => {
+            let mut items = Vec::new();
+            for index in 0..(bytes.len() / stride).min(limits.max_children) {
+                let chunk = element_bytes(bytes, index, stride)?;
+                items.push(format_array(element, inner, &strides[1..], chunk, &nested)?);
+            }
+            return Ok(format!("[{}, ...] (length unknown)", items.join(", ")));
+        }
+    };
+
+    let mut items = Vec::new();
+    for index in 0..count.min(limits.max_children) {
+        let chunk = element_bytes(bytes, index, stride)?;
+        items.push(format_array(element, inner, &strides[1..], chunk, &nested)?);
+    }
+    if count > limits.max_children {
+        items.push("...".to_string());
+    }
+    Ok(format!("[{}]", items.join(", ")))
+}
+
+/// Bytes of the `index`th item of an array dimension
+///
+/// The last item may lack the padding a stride adds after it.
+fn element_bytes(bytes: &[u8], index: usize, stride: usize) -> Result<&[u8]> {
+    let start = index * stride;
+    bytes
+        .get(start..(start + stride).min(bytes.len()))
+        .filter(|chunk| !chunk.is_empty() || stride == 0)
+        .ok_or_else(|| anyhow!("memory slice is too short for element {}", index))
+}
+
+/// Characters of a string longer than this are cut off with an ellipsis, unless
+/// the container is set to show more
+pub(crate) const MAX_STRING_LENGTH: usize = 256;
+
+/// Whether values of a type are C characters, whose arrays are strings
+pub(crate) fn is_c_char(varinfo: &VariableInfo) -> bool {
+    varinfo.tag == gimli::DW_TAG_base_type
+        && varinfo.byte_size == 1
+        && matches!(
+            varinfo.encoding,
+            gimli::DW_ATE_signed_char | gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF
+        )
+}
+
+/// Whether values of a type are 2-byte `char16_t` or `wchar_t` characters,
+/// whose arrays are UTF-16 strings
+pub(crate) fn is_wide_char(varinfo: &VariableInfo) -> bool {
+    if varinfo.tag != gimli::DW_TAG_base_type || varinfo.byte_size != 2 {
+        return false;
+    }
+    match varinfo.encoding {
+        gimli::DW_ATE_UTF => true,
+        // C declares these as typedefs of integers, and Windows' wchar_t is one
+        gimli::DW_ATE_signed
+        | gimli::DW_ATE_unsigned
+        | gimli::DW_ATE_signed_char
+        | gimli::DW_ATE_unsigned_char => matches!(
+            varinfo.name.rsplit(' ').next(),
+            Some("char16_t") | Some("wchar_t")
+        ),
+        _ => false,
+    }
+}
+
+/// Whether values of a type are narrow or wide characters
+pub(crate) fn is_character(varinfo: &VariableInfo) -> bool {
+    is_c_char(varinfo) || is_wide_char(varinfo)
+}
+
+/// Characters of a string as they are stored
+enum Text<'a> {
+    /// UTF-8, or bytes of some other narrow encoding
+    Bytes(&'a [u8]),
+    /// UTF-16 code units
+    Wide(Vec<u16>),
+}
+
+impl Text<'_> {
+    fn to_string_lossy(&self) -> String {
+        match self {
+            Text::Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            Text::Wide(units) => String::from_utf16_lossy(units),
+        }
+    }
+}
+
+/// Characters before the NUL terminator, and whether they were cut short
+fn c_string<'a>(bytes: &'a [u8], limits: &FormatLimits) -> (&'a [u8], bool) {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    (
+        &bytes[..end.min(limits.max_string_length)],
+        end > limits.max_string_length,
+    )
+}
+
+/// UTF-16 code units before a 2-byte NUL terminator, and whether they were
+/// cut short
+fn wide_string(bytes: &[u8], endian: RunTimeEndian, limits: &FormatLimits) -> (Vec<u16>, bool) {
+    let mut units: Vec<_> = bytes
+        .chunks_exact(2)
+        .map(|unit| endian.read_u16(unit))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    let truncated = units.len() > limits.max_string_length;
+    units.truncate(limits.max_string_length);
+    (units, truncated)
+}
+
+/// Characters of `element`s before a terminator as wide as one of them
+fn terminated_text<'a>(
+    element: &VariableInfo,
+    bytes: &'a [u8],
+    limits: &FormatLimits,
+) -> (Text<'a>, bool) {
+    if is_wide_char(element) {
+        let (units, truncated) = wide_string(bytes, element.endian, limits);
+        (Text::Wide(units), truncated)
+    } else {
+        let (text, truncated) = c_string(bytes, limits);
+        (Text::Bytes(text), truncated)
+    }
+}
+
+/// Characters of a C string or a Rust string, and whether they were cut short
+fn string_text<'a>(varinfo: &'a VariableInfo, limits: &FormatLimits) -> Option<(Text<'a>, bool)> {
+    match varinfo.string {
+        Some(StringLayout::Characters { length }) => {
+            let bytes = value_bytes(varinfo).ok()?;
+            let shown = &bytes[..bytes.len().min(limits.max_string_length)];
+            Some((Text::Bytes(shown), length > shown.len() as u64))
+        }
+        Some(StringLayout::Slice { .. }) => match varinfo.pointee {
+            Some(ref pointee) if pointee.is_completed() => string_text(pointee, limits),
+            _ => None,
+        },
+        Some(StringLayout::Terminated) => {
+            let element = varinfo.element.as_deref().unwrap_or(varinfo);
+            Some(terminated_text(element, value_bytes(varinfo).ok()?, limits))
+        }
+        None => {
+            let element = varinfo.element.as_ref()?;
+            if varinfo.tag != gimli::DW_TAG_array_type
+                || varinfo.dimensions.len() != 1
+                || !is_character(element)
+            {
+                return None;
+            }
+            Some(terminated_text(element, value_bytes(varinfo).ok()?, limits))
+        }
+    }
+}
+
+/// Append a character to a string literal, escaped as needed
+fn push_quoted(quoted: &mut String, c: char) {
+    match c {
+        '"' => quoted.push_str("\\\""),
+        '\\' => quoted.push_str("\\\\"),
+        '\n' => quoted.push_str("\\n"),
+        '\r' => quoted.push_str("\\r"),
+        '\t' => quoted.push_str("\\t"),
+        c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        c => quoted.push(c),
+    }
+}
+
+/// Quote text like a string literal
+///
+/// Bytes that aren't valid UTF-8 are escaped as `\xNN` instead, and unpaired
+/// UTF-16 surrogates as `\u{dNNN}`.
+fn quote_text(text: &Text, truncated: bool) -> String {
+    let mut quoted = String::from('"');
+    match text {
+        Text::Bytes(bytes) => quote_bytes(&mut quoted, bytes),
+        Text::Wide(units) => {
+            for c in std::char::decode_utf16(units.iter().copied()) {
+                match c {
+                    Ok(c) => push_quoted(&mut quoted, c),
+                    Err(e) => quoted.push_str(&format!("\\u{{{:x}}}", e.unpaired_surrogate())),
+                }
+            }
+        }
+    }
+    quoted.push('"');
+    if truncated {
+        quoted.push_str("...");
+    }
+    quoted
+}
+
+/// Append bytes to a string literal, escaping those that aren't valid UTF-8
+fn quote_bytes(quoted: &mut String, mut bytes: &[u8]) {
+    loop {
+        let (valid, invalid) = match std::str::from_utf8(bytes) {
+            Ok(text) => (text, &[][..]),
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                let invalid_len = e.error_len().unwrap_or(rest.len());
+                bytes = &rest[invalid_len..];
+                (
+                    std::str::from_utf8(valid).unwrap_or_default(),
+                    &rest[..invalid_len],
+                )
+            }
+        };
+        for c in valid.chars() {
+            push_quoted(quoted, c);
+        }
+        if invalid.is_empty() {
+            break;
+        }
+        for b in invalid {
+            quoted.push_str(&format!("\\x{:02x}", b));
+        }
+    }
+}
+
+/// Describe a variable as a CDP `Runtime.RemoteObject` JSON string
+///
+/// Primitives carry their `value`, or an `unserializableValue` for 64-bit
+/// integers and non-finite floats. Arrays and structures get a `preview`.
+pub fn format_remote_object(varinfo: &VariableInfo) -> Result<String> {
+    if varinfo.unavailable_bits.is_empty() {
+        if let Some(value) = unpack_bit_field(varinfo)? {
+            return format_remote_object(&value);
+        }
+    }
+    let limits = &varinfo.limits;
+    let description = format_object(varinfo)?;
+    let remote = remote_value(varinfo, limits)?;
+
+    let mut fields = remote.type_fields();
+    if let Some(ref value) = remote.value {
+        fields.push(("value", value.clone()));
+    }
+    if let Some(ref value) = remote.unserializable_value {
+        fields.push(("unserializableValue", json_string(value)));
+    }
+    if remote.object_type == "object" {
+        fields.push(("className", json_string(&varinfo.name)));
+    }
+    fields.push(("description", json_string(&description)));
+    if let Some(preview) = object_preview(varinfo, &remote, &description, limits)? {
+        fields.push(("preview", preview));
+    }
+    Ok(json_object(&fields))
+}
+
+/// Describe a variable list as a JSON array of
+/// `{name, typeName, kind, declFile, declLine}` objects
+///
+/// `name` is the display name `get_variable_info` resolves, `kind` is
+/// `"parameter"`, `"variable"` or `"member"`, and the declaration fields are
+/// `null` when unknown. Later versions only add fields.
+pub(crate) fn format_variable_list(variables: &[VariableName]) -> String {
+    let entries: Vec<_> = variables
+        .iter()
+        .map(|variable| {
+            let kind = if variable.is_parameter {
+                "parameter"
+            } else if variable.die_offset.is_some() {
+                "variable"
+            } else {
+                "member"
+            };
+            json_object(&[
+                ("name", json_string(&variable.display_name)),
+                ("typeName", json_string(&variable.type_name)),
+                ("kind", json_string(kind)),
+                (
+                    "declFile",
+                    variable
+                        .decl_file
+                        .as_deref()
+                        .map_or_else(|| "null".to_string(), json_string),
+                ),
+                (
+                    "declLine",
+                    variable
+                        .decl_line
+                        .map_or_else(|| "null".to_string(), |line| line.to_string()),
+                ),
+                (
+                    "availableRanges",
+                    variable.available_ranges.as_ref().map_or_else(
+                        || "null".to_string(),
+                        |ranges| {
+                            let ranges: Vec<_> = ranges
+                                .iter()
+                                .map(|range| format!("[{},{}]", range.start, range.end))
+                                .collect();
+                            format!("[{}]", ranges.join(","))
+                        },
+                    ),
+                ),
+            ])
+        })
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+/// The parts of a `RemoteObject` that depend on the kind of value
+struct RemoteValue {
+    object_type: &'static str,
+    subtype: Option<&'static str>,
+    /// JSON literal of a primitive value
+    value: Option<String>,
+    unserializable_value: Option<String>,
+}
+
+impl RemoteValue {
+    fn object(subtype: Option<&'static str>) -> Self {
+        RemoteValue {
+            object_type: "object",
+            subtype,
+            value: None,
+            unserializable_value: None,
+        }
+    }
+
+    fn primitive(object_type: &'static str, value: String) -> Self {
+        RemoteValue {
+            object_type,
+            subtype: None,
+            value: Some(value),
+            unserializable_value: None,
+        }
+    }
+
+    fn unserializable(object_type: &'static str, value: String) -> Self {
+        RemoteValue {
+            object_type,
+            subtype: None,
+            value: None,
+            unserializable_value: Some(value),
+        }
+    }
+
+    fn type_fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("type", json_string(self.object_type))];
+        if let Some(subtype) = self.subtype {
+            fields.push(("subtype", json_string(subtype)));
+        }
+        fields
+    }
+}
+
+fn remote_value(varinfo: &VariableInfo, limits: &FormatLimits) -> Result<RemoteValue> {
+    if let Some((text, _)) = string_text(varinfo, limits) {
+        let value = json_string(&text.to_string_lossy());
+        return Ok(RemoteValue::primitive("string", value));
+    }
+
+    // a value with pieces missing has nothing trustworthy to serialize, while
+    // structures still list their members, which are evaluated on their own
+    let aggregate = matches!(
+        varinfo.tag,
+        gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type
+    );
+    if unavailable_marker(varinfo).is_some() && !aggregate {
+        return Ok(RemoteValue {
+            object_type: "undefined",
+            subtype: None,
+            value: None,
+            unserializable_value: None,
+        });
+    }
+
+    match varinfo.tag {
+        gimli::DW_TAG_base_type | gimli::DW_TAG_enumeration_type => primitive_value(varinfo),
+        gimli::DW_TAG_pointer_type => {
+            if pointer_address(varinfo)? == 0 {
+                let mut remote = RemoteValue::object(Some("null"));
+                remote.value = Some("null".to_string());
+                Ok(remote)
+            } else {
+                Ok(RemoteValue::object(None))
+            }
+        }
+        gimli::DW_TAG_array_type => Ok(RemoteValue::object(Some("array"))),
         gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
-            Ok(varinfo.name.clone())
+            Ok(RemoteValue::object(None))
         }
         _ => Err(anyhow!("unsupported DIE type")),
     }
 }
+
+fn primitive_value(varinfo: &VariableInfo) -> Result<RemoteValue> {
+    let bytes = value_bytes(varinfo)?;
+
+    match varinfo.encoding {
+        gimli::DW_ATE_boolean => {
+            let value = bytes.iter().any(|b| *b != 0);
+            Ok(RemoteValue::primitive("boolean", value.to_string()))
+        }
+        // a Rust char is a string of one character, C++ character types stay numbers
+        gimli::DW_ATE_UTF if varinfo.language == SourceLanguage::Rust => {
+            let value = unsigned_from_bytes(bytes, varinfo.endian);
+            match u32::try_from(&value).ok().and_then(char::from_u32) {
+                Some(c) => Ok(RemoteValue::primitive(
+                    "string",
+                    json_string(&c.to_string()),
+                )),
+                None => Ok(RemoteValue::primitive("number", value.to_string())),
+            }
+        }
+        gimli::DW_ATE_float => {
+            let (value, text) = match varinfo.byte_size {
+                4 => {
+                    let value = varinfo.endian.read_f32(bytes);
+                    (value as f64, value.to_string())
+                }
+                8 => {
+                    let value = varinfo.endian.read_f64(bytes);
+                    (value, value.to_string())
+                }
+                other => Err(anyhow!(format!("unsupported float size: {}", other)))?,
+            };
+            if value.is_nan() {
+                Ok(RemoteValue::unserializable("number", "NaN".to_string()))
+            } else if value.is_infinite() {
+                let text = if value > 0.0 { "Infinity" } else { "-Infinity" };
+                Ok(RemoteValue::unserializable("number", text.to_string()))
+            } else if value == 0.0 && value.is_sign_negative() {
+                Ok(RemoteValue::unserializable("number", "-0".to_string()))
+            } else {
+                Ok(RemoteValue::primitive("number", text))
+            }
+        }
+        gimli::DW_ATE_signed
+        | gimli::DW_ATE_signed_char
+        | gimli::DW_ATE_unsigned
+        | gimli::DW_ATE_unsigned_char
+        | gimli::DW_ATE_UTF => {
+            let value = match varinfo.encoding {
+                gimli::DW_ATE_signed | gimli::DW_ATE_signed_char => {
+                    signed_from_bytes(bytes, varinfo.endian)
+                }
+                _ => BigInt::from(unsigned_from_bytes(bytes, varinfo.endian)),
+            };
+            // doubles hold every 32-bit integer exactly, wider ones become BigInts
+            if varinfo.byte_size <= 4 {
+                Ok(RemoteValue::primitive("number", value.to_string()))
+            } else {
+                Ok(RemoteValue::unserializable("bigint", format!("{}n", value)))
+            }
+        }
+        other => Err(anyhow!(format!("unsupported attribute type: {}", other))),
+    }
+}
+
+/// `ObjectPreview` of an array with its leading elements, or of a structure
+/// with its member names
+///
+/// Lists as many properties as aggregates show children, and none past the
+/// nesting the limits expand.
+fn object_preview(
+    varinfo: &VariableInfo,
+    remote: &RemoteValue,
+    description: &str,
+    limits: &FormatLimits,
+) -> Result<Option<String>> {
+    // strings are primitives even when stored as arrays
+    if remote.object_type != "object" {
+        return Ok(None);
+    }
+    let (max_properties, nested) = match limits.nested() {
+        Some(nested) => (limits.max_children, nested),
+        None => (0, *limits),
+    };
+
+    let mut properties = Vec::new();
+    let overflow;
+
+    match varinfo.tag {
+        gimli::DW_TAG_array_type => {
+            let element = varinfo
+                .element
+                .as_ref()
+                .ok_or_else(|| anyhow!("array without element type"))?;
+            let (declared_count, inner) = match varinfo.dimensions.split_first() {
+                Some((count, inner)) => (*count, inner),
+                None => (None, &[][..]),
+            };
+            let strides = varinfo.strides();
+            let stride = strides.first().copied().unwrap_or(0);
+            let bytes = value_bytes(varinfo)?;
+            // arrays of unknown length list the elements read in its place
+            let count = match declared_count {
+                Some(count) => count as usize,
+                None => bytes.len().checked_div(stride).unwrap_or(0),
+            };
+
+            for index in 0..count.min(max_properties) {
+                let chunk = element_bytes(bytes, index, stride)?;
+                let mut fields = vec![("name", json_string(&index.to_string()))];
+                if inner.is_empty() {
+                    let mut element = element.as_ref().clone();
+                    element.memory_slice = MemorySlice::from_u8_vec(chunk.to_vec());
+                    let remote = remote_value(&element, &nested)?;
+                    let value = match (remote.value.clone(), remote.unserializable_value.clone()) {
+                        (Some(value), _) | (None, Some(value)) => value,
+                        (None, None) => format_nested(&element, &nested)?,
+                    };
+                    fields.append(&mut remote.type_fields());
+                    fields.push(("value", json_string(&value)));
+                } else {
+                    let value = format_array(element, inner, &strides[1..], chunk, &nested)?;
+                    fields.append(&mut RemoteValue::object(Some("array")).type_fields());
+                    fields.push(("value", json_string(&value)));
+                }
+                properties.push(json_object(&fields));
+            }
+            overflow = declared_count.is_none() || count > max_properties;
+        }
+        gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
+            // member values are read separately, so only their names are known here
+            for member in varinfo.members.iter().take(max_properties) {
+                properties.push(json_object(&[
+                    ("name", json_string(&member.name)),
+                    ("type", json_string("accessor")),
+                ]));
+            }
+            overflow = varinfo.members.len() > max_properties;
+        }
+        _ => return Ok(None),
+    }
+
+    let mut fields = remote.type_fields();
+    fields.push(("description", json_string(description)));
+    fields.push(("overflow", overflow.to_string()));
+    fields.push(("properties", format!("[{}]", properties.join(","))));
+    Ok(Some(json_object(&fields)))
+}
+
+/// Fields are already encoded as JSON
+fn json_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|(name, value)| format!("{}:{}", json_string(name), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Value of a scalar variable, decoded from its bytes
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Scalar {
+    Signed(i64),
+    Unsigned(u64),
+    Float(f64),
+    Boolean(bool),
+    /// Integers wider than 64 bits and floats other than `float` and `double`
+    Bytes(Vec<u8>),
+}
+
+/// Decode the value of a base type, an enumeration or a pointer, which is its
+/// address
+pub(crate) fn scalar_value(varinfo: &VariableInfo) -> Result<Scalar> {
+    if unavailable_marker(varinfo).is_some() {
+        Err(BridgeError::OptimizedOut(format!(
+            "{} is not fully available",
+            varinfo.name
+        )))?
+    }
+    if let Some(value) = unpack_bit_field(varinfo)? {
+        return scalar_value(&value);
+    }
+    match varinfo.tag {
+        gimli::DW_TAG_pointer_type => return Ok(Scalar::Unsigned(pointer_address(varinfo)?)),
+        gimli::DW_TAG_base_type | gimli::DW_TAG_enumeration_type => {}
+        _ => Err(BridgeError::Unsupported(format!(
+            "{} is not a scalar",
+            varinfo.name
+        )))?,
+    }
+
+    let bytes = value_bytes(varinfo)?;
+    let value = match (varinfo.encoding, bytes.len()) {
+        (gimli::DW_ATE_boolean, _) => Scalar::Boolean(bytes.iter().any(|b| *b != 0)),
+        (gimli::DW_ATE_float, 4) => Scalar::Float(varinfo.endian.read_f32(bytes) as f64),
+        (gimli::DW_ATE_float, 8) => Scalar::Float(varinfo.endian.read_f64(bytes)),
+        (gimli::DW_ATE_signed, _) | (gimli::DW_ATE_signed_char, _) => {
+            match i64::try_from(&signed_from_bytes(bytes, varinfo.endian)) {
+                Ok(value) => Scalar::Signed(value),
+                Err(_) => Scalar::Bytes(bytes.to_vec()),
+            }
+        }
+        (gimli::DW_ATE_unsigned, _)
+        | (gimli::DW_ATE_unsigned_char, _)
+        | (gimli::DW_ATE_UTF, _)
+        | (gimli::DW_ATE_address, _) => {
+            match u64::try_from(&unsigned_from_bytes(bytes, varinfo.endian)) {
+                Ok(value) => Scalar::Unsigned(value),
+                Err(_) => Scalar::Bytes(bytes.to_vec()),
+            }
+        }
+        _ => Scalar::Bytes(bytes.to_vec()),
+    };
+    Ok(value)
+}
+
+fn signed_from_bytes(bytes: &[u8], endian: RunTimeEndian) -> BigInt {
+    if endian.is_big_endian() {
+        BigInt::from_signed_bytes_be(bytes)
+    } else {
+        BigInt::from_signed_bytes_le(bytes)
+    }
+}
+
+fn unsigned_from_bytes(bytes: &[u8], endian: RunTimeEndian) -> BigUint {
+    if endian.is_big_endian() {
+        BigUint::from_bytes_be(bytes)
+    } else {
+        BigUint::from_bytes_le(bytes)
+    }
+}
+
+/// Addresses wider than 64 bits are truncated
+pub(crate) fn address_from_bytes(bytes: &[u8], endian: RunTimeEndian) -> u64 {
+    unsigned_from_bytes(bytes, endian)
+        .to_u64_digits()
+        .first()
+        .copied()
+        .unwrap_or(0)
+}
+
+fn value_bytes(varinfo: &VariableInfo) -> Result<&[u8]> {
+    varinfo
+        .memory_slice
+        .memory_slice
+        .get(0..varinfo.byte_size)
+        .ok_or_else(|| {
+            anyhow!(
+                "memory slice holds {} bytes but {} needs {}",
+                varinfo.memory_slice.memory_slice.len(),
+                varinfo.name,
+                varinfo.byte_size
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dwarf::{BitField, VariableEvaluationResult, VariableLocation, Variant};
+
+    fn base_type(name: &str, encoding: gimli::DwAte, bytes: &[u8]) -> VariableInfo {
+        VariableInfo {
+            memory_slice: MemorySlice::from_u8_vec(bytes.to_vec()),
+            ..VariableInfo::new(
+                gimli::DW_TAG_base_type,
+                name.to_string(),
+                bytes.len(),
+                encoding,
+            )
+        }
+    }
+
+    #[test]
+    fn float() {
+        let varinfo = base_type("float", gimli::DW_ATE_float, &1.5f32.to_le_bytes());
+        assert_eq!(format_object(&varinfo).unwrap(), "(float)1.5");
+    }
+
+    #[test]
+    fn double() {
+        let varinfo = base_type("double", gimli::DW_ATE_float, &(-0.25f64).to_le_bytes());
+        assert_eq!(format_object(&varinfo).unwrap(), "(double)-0.25");
+    }
+
+    #[test]
+    fn bool() {
+        let varinfo = base_type("bool", gimli::DW_ATE_boolean, &[1]);
+        assert_eq!(format_object(&varinfo).unwrap(), "(bool)true");
+        let varinfo = base_type("bool", gimli::DW_ATE_boolean, &[0]);
+        assert_eq!(format_object(&varinfo).unwrap(), "(bool)false");
+    }
+
+    #[test]
+    fn char() {
+        let varinfo = base_type("char", gimli::DW_ATE_signed_char, b"a");
+        assert_eq!(format_object(&varinfo).unwrap(), "(char)'a'");
+        let varinfo = base_type("unsigned char", gimli::DW_ATE_unsigned_char, &[0xff]);
+        assert_eq!(format_object(&varinfo).unwrap(), "(unsigned char)255");
+        let varinfo = base_type("char", gimli::DW_ATE_signed_char, &[0xff]);
+        assert_eq!(format_object(&varinfo).unwrap(), "(char)-1");
+    }
+
+    #[test]
+    fn rust_char() {
+        let mut varinfo = base_type("char", gimli::DW_ATE_UTF, &('é' as u32).to_le_bytes());
+        varinfo.language = SourceLanguage::Rust;
+        assert_eq!(format_object(&varinfo).unwrap(), "(char)'é'");
+        assert!(format_remote_object(&varinfo)
+            .unwrap()
+            .starts_with(r#"{"type":"string","value":"é""#));
+        let mut varinfo = base_type("char", gimli::DW_ATE_UTF, &10u32.to_le_bytes());
+        varinfo.language = SourceLanguage::Rust;
+        assert_eq!(format_object(&varinfo).unwrap(), r"(char)'\n'");
+        let mut varinfo = base_type("char", gimli::DW_ATE_UTF, &0x1f600u32.to_le_bytes());
+        varinfo.language = SourceLanguage::Rust;
+        assert_eq!(format_object(&varinfo).unwrap(), "(char)'\u{1f600}'");
+
+        // past the last scalar value, so only the code point is shown
+        let mut varinfo = base_type("char", gimli::DW_ATE_UTF, &0x110000u32.to_le_bytes());
+        varinfo.language = SourceLanguage::Rust;
+        assert_eq!(format_object(&varinfo).unwrap(), "(char)1114112");
+        assert!(format_remote_object(&varinfo)
+            .unwrap()
+            .starts_with(r#"{"type":"number","value":1114112"#));
+    }
+
+    #[test]
+    fn cpp_char32() {
+        let mut varinfo = base_type("char32_t", gimli::DW_ATE_UTF, &('é' as u32).to_le_bytes());
+        varinfo.language = SourceLanguage::Cpp;
+        assert_eq!(format_object(&varinfo).unwrap(), "(char32_t)U'é'");
+        assert!(format_remote_object(&varinfo)
+            .unwrap()
+            .starts_with(r#"{"type":"number","value":233"#));
+        let varinfo = base_type("char16_t", gimli::DW_ATE_UTF, &10u16.to_le_bytes());
+        assert_eq!(format_object(&varinfo).unwrap(), "(char16_t)10");
+        let varinfo = base_type("char16_t", gimli::DW_ATE_UTF, &0x263au16.to_le_bytes());
+        assert_eq!(format_object(&varinfo).unwrap(), "(char16_t)u'\u{263a}'");
+        // half of a surrogate pair isn't a character by itself
+        let varinfo = base_type("char16_t", gimli::DW_ATE_UTF, &0xd800u16.to_le_bytes());
+        assert_eq!(format_object(&varinfo).unwrap(), "(char16_t)55296");
+    }
+
+    #[test]
+    fn null_pointer() {
+        let mut varinfo = base_type("int *", gimli::DW_ATE_address, &[0, 0, 0, 0]);
+        varinfo.tag = gimli::DW_TAG_pointer_type;
+        varinfo.pointee = Some(Box::new(base_type("int", gimli::DW_ATE_signed, &[])));
+        assert_eq!(format_object(&varinfo).unwrap(), "null");
+    }
+
+    #[test]
+    fn dereferenced_pointer() {
+        let mut pointee = base_type("int", gimli::DW_ATE_signed, &42i32.to_le_bytes());
+        pointee.state = VariableEvaluationResult::Complete;
+        let mut varinfo = base_type("int *", gimli::DW_ATE_address, &[]);
+        varinfo.tag = gimli::DW_TAG_pointer_type;
+        varinfo.pointee = Some(Box::new(pointee));
+        varinfo.pointee_address = Some(0x1234);
+        assert_eq!(format_object(&varinfo).unwrap(), "0x1234 -> (int)42");
+    }
+
+    #[test]
+    fn short_memory_slice() {
+        let mut varinfo = base_type("int", gimli::DW_ATE_signed, &[1, 0]);
+        varinfo.byte_size = 4;
+        assert!(format_object(&varinfo).is_err());
+    }
+
+    #[test]
+    fn enumeration() {
+        let mut varinfo = base_type("Color", gimli::DW_ATE_unsigned, &2u32.to_le_bytes());
+        varinfo.tag = gimli::DW_TAG_enumeration_type;
+        varinfo.enumerators = vec![
+            ("Green".to_string(), BigInt::from(1)),
+            ("Red".to_string(), BigInt::from(2)),
+        ];
+        assert_eq!(format_object(&varinfo).unwrap(), "Color::Red (2)");
+
+        varinfo.memory_slice = MemorySlice::from_u8_vec(3u32.to_le_bytes().to_vec());
+        assert_eq!(format_object(&varinfo).unwrap(), "(Color)3");
+    }
+
+    #[test]
+    fn signed_enumeration() {
+        let mut varinfo = base_type("Order", gimli::DW_ATE_signed, &(-1i8).to_le_bytes());
+        varinfo.tag = gimli::DW_TAG_enumeration_type;
+        varinfo.enumerators = vec![("Less".to_string(), BigInt::from(-1))];
+        assert_eq!(format_object(&varinfo).unwrap(), "Order::Less (-1)");
+    }
+
+    #[test]
+    fn array() {
+        let bytes: Vec<u8> = [1i32, 2, 3].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mut varinfo = base_type("int[3]", gimli::DW_ATE_signed, &bytes);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type("int", gimli::DW_ATE_signed, &[0; 4])));
+        varinfo.dimensions = vec![Some(3)];
+        assert_eq!(format_object(&varinfo).unwrap(), "[(int)1, (int)2, (int)3]");
+        assert_eq!(varinfo.element_count(), Some(3));
+    }
+
+    #[test]
+    fn array_of_unknown_length() {
+        let bytes: Vec<u8> = [1i32, 2].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mut varinfo = base_type("int[]", gimli::DW_ATE_signed, &bytes);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type("int", gimli::DW_ATE_signed, &[0; 4])));
+        varinfo.dimensions = vec![None];
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "[(int)1, (int)2, ...] (length unknown)"
+        );
+
+        // nothing is read of flexible array members
+        varinfo.byte_size = 0;
+        assert_eq!(format_object(&varinfo).unwrap(), "[...]");
+    }
+
+    #[test]
+    fn nested_array() {
+        let mut varinfo = base_type("unsigned char[2][2]", gimli::DW_ATE_unsigned, &[1, 2, 3, 4]);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type(
+            "unsigned char",
+            gimli::DW_ATE_unsigned,
+            &[0],
+        )));
+        varinfo.dimensions = vec![Some(2), Some(2)];
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "[[(unsigned char)1, (unsigned char)2], [(unsigned char)3, (unsigned char)4]]"
+        );
+    }
+
+    #[test]
+    fn format_limits() {
+        let mut varinfo = base_type("unsigned char[2][2]", gimli::DW_ATE_unsigned, &[1, 2, 3, 4]);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type(
+            "unsigned char",
+            gimli::DW_ATE_unsigned,
+            &[0],
+        )));
+        varinfo.dimensions = vec![Some(2), Some(2)];
+        varinfo.limits.max_depth = 1;
+        assert_eq!(format_object(&varinfo).unwrap(), "[[...], [...]]");
+        varinfo.limits.max_depth = 0;
+        assert_eq!(format_object(&varinfo).unwrap(), "[...]");
+        assert!(format_remote_object(&varinfo)
+            .unwrap()
+            .contains(r#""overflow":true,"properties":[]"#));
+
+        varinfo.limits = FormatLimits {
+            max_children: 1,
+            ..FormatLimits::default()
+        };
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "[[(unsigned char)1, ...], ...]"
+        );
+
+        let mut pointee = base_type("int", gimli::DW_ATE_signed, &42i32.to_le_bytes());
+        pointee.state = VariableEvaluationResult::Complete;
+        let mut pointer = base_type("int *", gimli::DW_ATE_address, &[]);
+        pointer.tag = gimli::DW_TAG_pointer_type;
+        pointer.pointee = Some(Box::new(pointee));
+        pointer.pointee_address = Some(0x1234);
+        pointer.limits.max_depth = 0;
+        assert_eq!(format_object(&pointer).unwrap(), "(int *)0x1234");
+    }
+
+    #[test]
+    fn strided_array() {
+        let bytes = [1, 0, 0, 0, 2, 0, 0, 0, 3];
+        let mut varinfo = base_type("unsigned char[3]", gimli::DW_ATE_unsigned, &bytes);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type(
+            "unsigned char",
+            gimli::DW_ATE_unsigned,
+            &[0],
+        )));
+        varinfo.dimensions = vec![Some(3)];
+        varinfo.byte_strides = vec![Some(4)];
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "[(unsigned char)1, (unsigned char)2, (unsigned char)3]"
+        );
+    }
+
+    #[test]
+    fn array_elements() {
+        let bytes: Vec<u8> = [1i32, 2, 3, 4]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let mut varinfo = base_type("int[2][2]", gimli::DW_ATE_signed, &bytes);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type("int", gimli::DW_ATE_signed, &[0; 4])));
+        varinfo.dimensions = vec![Some(2), Some(2)];
+        varinfo.state = VariableEvaluationResult::Complete;
+
+        // a constant array has no address, so its rows are cut from its bytes
+        let rows = varinfo.elements(1, 5).unwrap();
+        assert_eq!(rows.size(), 1);
+        let mut row = rows.at(0);
+        assert_eq!(row.name, "int[2]");
+        assert_eq!(row.evaluate().unwrap(), "[(int)3, (int)4]");
+
+        varinfo.memory_slice.address = 0x1000;
+        let row = varinfo.elements(1, 1).unwrap().at(0);
+        assert!(matches!(
+            row.address_expr[..],
+            [VariableLocation::Address(0x1008)]
+        ));
+        assert!(row.memory_slice.memory_slice.is_empty());
+    }
+
+    #[test]
+    fn long_array() {
+        let mut varinfo = base_type("bool[200]", gimli::DW_ATE_boolean, &[0; 200]);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type("bool", gimli::DW_ATE_boolean, &[0])));
+        varinfo.dimensions = vec![Some(200)];
+        let formatted = format_object(&varinfo).unwrap();
+        assert_eq!(formatted.matches("(bool)false").count(), MAX_ARRAY_ELEMENTS);
+        assert!(formatted.ends_with(", ...]"));
+    }
+
+    #[test]
+    fn big_endian() {
+        let mut varinfo = base_type("int", gimli::DW_ATE_signed, &(-2i32).to_be_bytes());
+        varinfo.endian = RunTimeEndian::Big;
+        assert_eq!(format_object(&varinfo).unwrap(), "(int)-2");
+
+        let mut varinfo = base_type("double", gimli::DW_ATE_float, &0.5f64.to_be_bytes());
+        varinfo.endian = RunTimeEndian::Big;
+        assert_eq!(format_object(&varinfo).unwrap(), "(double)0.5");
+    }
+
+    #[test]
+    fn remote_object_number() {
+        let varinfo = base_type("int", gimli::DW_ATE_signed, &(-3i32).to_le_bytes());
+        assert_eq!(
+            format_remote_object(&varinfo).unwrap(),
+            r#"{"type":"number","value":-3,"description":"(int)-3"}"#
+        );
+
+        let varinfo = base_type("long long", gimli::DW_ATE_signed, &(-3i64).to_le_bytes());
+        assert_eq!(
+            format_remote_object(&varinfo).unwrap(),
+            r#"{"type":"bigint","unserializableValue":"-3n","description":"(long long)-3"}"#
+        );
+
+        let varinfo = base_type("double", gimli::DW_ATE_float, &f64::NAN.to_le_bytes());
+        assert!(format_remote_object(&varinfo)
+            .unwrap()
+            .starts_with(r#"{"type":"number","unserializableValue":"NaN","#));
+    }
+
+    #[test]
+    fn remote_object_null_pointer() {
+        let mut varinfo = base_type("int *", gimli::DW_ATE_address, &[0, 0, 0, 0]);
+        varinfo.tag = gimli::DW_TAG_pointer_type;
+        assert_eq!(
+            format_remote_object(&varinfo).unwrap(),
+            r#"{"type":"object","subtype":"null","value":null,"className":"int *","description":"null"}"#
+        );
+    }
+
+    #[test]
+    fn remote_object_array() {
+        let mut varinfo = base_type("bool[2]", gimli::DW_ATE_boolean, &[1, 0]);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type("bool", gimli::DW_ATE_boolean, &[0])));
+        varinfo.dimensions = vec![Some(2)];
+        assert_eq!(
+            format_remote_object(&varinfo).unwrap(),
+            concat!(
+                r#"{"type":"object","subtype":"array","className":"bool[2]","#,
+                r#""description":"[(bool)true, (bool)false]","#,
+                r#""preview":{"type":"object","subtype":"array","#,
+                r#""description":"[(bool)true, (bool)false]","overflow":false,"properties":["#,
+                r#"{"name":"0","type":"boolean","value":"true"},"#,
+                r#"{"name":"1","type":"boolean","value":"false"}]}}"#
+            )
+        );
+    }
+
+    fn char_array(bytes: &[u8]) -> VariableInfo {
+        let mut varinfo = base_type("char[]", gimli::DW_ATE_signed_char, bytes);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type("char", gimli::DW_ATE_signed_char, &[0])));
+        varinfo.dimensions = vec![Some(bytes.len() as u64)];
+        varinfo
+    }
+
+    #[test]
+    fn c_string() {
+        assert_eq!(
+            format_object(&char_array(b"hi\"there\"\0junk")).unwrap(),
+            r#""hi\"there\"""#
+        );
+        // without a terminator the whole array is shown
+        assert_eq!(format_object(&char_array(b"abc")).unwrap(), r#""abc""#);
+        assert_eq!(
+            format_remote_object(&char_array(b"abc\0")).unwrap(),
+            r#"{"type":"string","value":"abc","description":"\"abc\""}"#
+        );
+    }
+
+    fn wide_array(element: &str, encoding: gimli::DwAte, units: &[u16]) -> VariableInfo {
+        let bytes: Vec<_> = units.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+        let mut varinfo = base_type(&format!("{}[]", element), encoding, &bytes);
+        varinfo.tag = gimli::DW_TAG_array_type;
+        varinfo.element = Some(Box::new(base_type(element, encoding, &[0, 0])));
+        varinfo.dimensions = vec![Some(units.len() as u64)];
+        varinfo
+    }
+
+    #[test]
+    fn wide_string() {
+        let text: Vec<_> = "h\u{e9}\u{1f600}\0junk".encode_utf16().collect();
+        assert_eq!(
+            format_object(&wide_array("char16_t", gimli::DW_ATE_UTF, &text)).unwrap(),
+            "\"h\u{e9}\u{1f600}\""
+        );
+        // Windows' wchar_t is an unsigned integer, and the bound ends the text
+        let text: Vec<_> = "abc".encode_utf16().collect();
+        let wchars = wide_array("wchar_t", gimli::DW_ATE_unsigned, &text);
+        assert_eq!(
+            format_remote_object(&wchars).unwrap(),
+            r#"{"type":"string","value":"abc","description":"\"abc\""}"#
+        );
+        // unpaired surrogates are escaped
+        let text = [u16::from(b'a'), 0xd800, u16::from(b'b'), 0xdc00];
+        assert_eq!(
+            format_object(&wide_array("char16_t", gimli::DW_ATE_UTF, &text)).unwrap(),
+            r#""a\u{d800}b\u{dc00}""#
+        );
+        // a pointer's text ends at the first 2-byte NUL
+        let mut pointer = base_type("char16_t *", gimli::DW_ATE_address, &[]);
+        pointer.tag = gimli::DW_TAG_pointer_type;
+        pointer.pointee_address = Some(0x1000);
+        let mut pointee = wide_array("char16_t", gimli::DW_ATE_UTF, &[104, 105, 0, 106]);
+        pointee.string = Some(StringLayout::Terminated);
+        pointee.state = VariableEvaluationResult::Complete;
+        pointer.pointee = Some(Box::new(pointee));
+        assert_eq!(format_object(&pointer).unwrap(), r#"0x1000 -> "hi""#);
+        // other 2-byte integers are still arrays
+        let shorts = wide_array("short", gimli::DW_ATE_signed, &[104, 0]);
+        assert_eq!(format_object(&shorts).unwrap(), "[(short)104, (short)0]");
+    }
+
+    #[test]
+    fn string_pointer() {
+        let mut varinfo = base_type("char *", gimli::DW_ATE_address, &[]);
+        varinfo.tag = gimli::DW_TAG_pointer_type;
+        varinfo.pointee_address = Some(0x1000);
+        let mut pointee = char_array(b"hello\0");
+        pointee.state = VariableEvaluationResult::Complete;
+        varinfo.pointee = Some(Box::new(pointee));
+        assert_eq!(format_object(&varinfo).unwrap(), r#"0x1000 -> "hello""#);
+    }
+
+    #[test]
+    fn long_string() {
+        let formatted = format_object(&char_array(&[b'a'; MAX_STRING_LENGTH + 1])).unwrap();
+        assert_eq!(formatted.matches('a').count(), MAX_STRING_LENGTH);
+        assert!(formatted.ends_with("\"..."));
+    }
+
+    #[test]
+    fn string_length_limit() {
+        let mut text = char_array(b"hello\0");
+        text.limits.max_string_length = 3;
+        assert_eq!(format_object(&text).unwrap(), r#""hel"..."#);
+
+        // a C string a pointer refers to is read as far as it is shown
+        let mut pointer = base_type("char *", gimli::DW_ATE_address, &[]);
+        pointer.tag = gimli::DW_TAG_pointer_type;
+        let mut pointee = char_array(&[0; MAX_STRING_LENGTH + 1]);
+        pointee.string = Some(StringLayout::Terminated);
+        pointer.pointee = Some(Box::new(pointee));
+        pointer.set_format_limits(FormatLimits {
+            max_string_length: 3,
+            ..FormatLimits::default()
+        });
+        let pointee = pointer.pointee.as_ref().unwrap();
+        assert_eq!(pointee.byte_size, 4);
+        assert_eq!(pointee.dimensions, [Some(4)]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert_eq!(
+            format_object(&char_array(b"caf\xe9 \xe2\x82\xac\n")).unwrap(),
+            r#""caf\xe9 €\n""#
+        );
+    }
+
+    #[test]
+    fn multibyte_string() {
+        let mut rows = char_array("é\0".as_bytes());
+        rows.dimensions = vec![Some(2), Some(3)];
+        rows.memory_slice = MemorySlice::from_u8_vec(b"ab\0cd\0".to_vec());
+        rows.byte_size = 6;
+        assert_eq!(format_object(&rows).unwrap(), r#"["ab", "cd"]"#);
+        assert_eq!(
+            format_object(&char_array("é\0".as_bytes())).unwrap(),
+            r#""é""#
+        );
+    }
+
+    #[test]
+    fn rust_str() {
+        let mut characters = base_type("u8[]", gimli::DW_ATE_unsigned, b"hello");
+        characters.tag = gimli::DW_TAG_array_type;
+        characters.element = Some(Box::new(base_type("u8", gimli::DW_ATE_unsigned, &[0])));
+        characters.dimensions = vec![Some(5)];
+        characters.string = Some(StringLayout::Characters { length: 5 });
+        characters.state = VariableEvaluationResult::Complete;
+        assert_eq!(format_object(&characters).unwrap(), r#""hello""#);
+
+        characters.string = Some(StringLayout::Characters { length: 300 });
+        let mut varinfo = base_type("&str", gimli::DW_ATE_signed, &[0; 8]);
+        varinfo.tag = gimli::DW_TAG_structure_type;
+        varinfo.string = Some(StringLayout::Slice {
+            pointer_offset: 0,
+            length_offset: 4,
+            word_size: 4,
+        });
+        varinfo.pointee = Some(Box::new(characters));
+        assert_eq!(format_object(&varinfo).unwrap(), r#""hello"..."#);
+    }
+
+    #[test]
+    fn rust_enum() {
+        let int = base_type("i32", gimli::DW_ATE_signed, &[0; 4]);
+        let mut varinfo = base_type(
+            "Option<i32>",
+            gimli::DW_ATE_signed,
+            &[1, 0, 0, 0, 7, 0, 0, 0],
+        );
+        varinfo.tag = gimli::DW_TAG_structure_type;
+        varinfo.variant_part = Some(VariantPart {
+            discriminant: Some((
+                0,
+                Box::new(base_type("u32", gimli::DW_ATE_unsigned, &[0; 4])),
+            )),
+            variants: vec![
+                Variant {
+                    name: "None".to_string(),
+                    discriminants: Some(vec![(0, 0)]),
+                    fields: Vec::new(),
+                },
+                Variant {
+                    name: "Some".to_string(),
+                    discriminants: Some(vec![(1, 1)]),
+                    fields: vec![("__0".to_string(), 4, Some(int))],
+                },
+            ],
+        });
+        assert_eq!(format_object(&varinfo).unwrap(), "Some { 0: (i32)7 }");
+
+        varinfo.memory_slice = MemorySlice::from_u8_vec(vec![0; 8]);
+        assert_eq!(format_object(&varinfo).unwrap(), "None");
+
+        varinfo.memory_slice = MemorySlice::from_u8_vec(vec![2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(format_object(&varinfo).unwrap(), "(Option<i32>)2");
+    }
+
+    #[test]
+    fn niche_enum() {
+        // Option<&i32> stores None as a null pointer, and Some is the default variant
+        let pointer = base_type("&i32", gimli::DW_ATE_unsigned, &[0; 4]);
+        let mut varinfo = base_type("Option<&i32>", gimli::DW_ATE_signed, &[0; 4]);
+        varinfo.tag = gimli::DW_TAG_structure_type;
+        varinfo.variant_part = Some(VariantPart {
+            discriminant: Some((
+                0,
+                Box::new(base_type("u32", gimli::DW_ATE_unsigned, &[0; 4])),
+            )),
+            variants: vec![
+                Variant {
+                    name: "None".to_string(),
+                    discriminants: Some(vec![(0, 0)]),
+                    fields: Vec::new(),
+                },
+                Variant {
+                    name: "Some".to_string(),
+                    discriminants: None,
+                    fields: vec![("__0".to_string(), 0, Some(pointer))],
+                },
+            ],
+        });
+        assert_eq!(format_object(&varinfo).unwrap(), "None");
+
+        varinfo.memory_slice = MemorySlice::from_u8_vec(vec![0x10, 0x20, 0, 0]);
+        assert_eq!(format_object(&varinfo).unwrap(), "Some { 0: (&i32)8208 }");
+    }
+
+    #[test]
+    fn signed_discriminant() {
+        assert_eq!(discriminant_value(0xff, 1, true), -1);
+        assert_eq!(discriminant_value(0xff, 1, false), 255);
+        assert_eq!(discriminant_value(u64::MAX, 2, true), -1);
+        assert_eq!(discriminant_value(u64::MAX, 2, false), 0xffff);
+    }
+
+    #[test]
+    fn raw_bytes() {
+        let mut varinfo = base_type("long double", gimli::DW_ATE_float, &[0x01, 0x00, 0xff]);
+        varinfo.byte_size = 3;
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "(long double)0x01 0x00 0xff"
+        );
+
+        varinfo.memory_slice.address = 0x1000;
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "(long double)0x01 0x00 0xff @ 0x1000"
+        );
+
+        // nothing to dump before the value is read
+        varinfo.memory_slice = MemorySlice::new();
+        assert!(format_object(&varinfo).is_err());
+    }
+
+    #[test]
+    fn missing_pieces() {
+        let mut varinfo = base_type("long long", gimli::DW_ATE_signed, &[7, 0, 0, 0, 0, 0, 0, 0]);
+        varinfo.unavailable_bits.push(32..64);
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "(long long)<partially available>"
+        );
+        assert_eq!(
+            format_remote_object(&varinfo).unwrap(),
+            r#"{"type":"undefined","description":"(long long)<partially available>"}"#
+        );
+
+        varinfo.unavailable_bits[0].start = 0;
+        assert_eq!(
+            format_object(&varinfo).unwrap(),
+            "(long long)<optimized out>"
+        );
+    }
+
+    #[test]
+    fn json_escape() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), r#""a\"b\\c\n\u0001""#);
+    }
+
+    #[test]
+    fn big_endian_bit_fields() {
+        // bits count from the most significant one: 0b1_101_10_0 ...
+        let field = |bit_offset, bit_size, encoding| {
+            let mut varinfo = base_type("int", encoding, &[0b1101_1000, 0b0100_0000]);
+            varinfo.endian = RunTimeEndian::Big;
+            varinfo.bit_field = Some(BitField {
+                bit_offset,
+                bit_size,
+                value_size: 4,
+            });
+            format_object(&varinfo).unwrap()
+        };
+        assert_eq!(field(0, 1, gimli::DW_ATE_unsigned), "(int)1");
+        assert_eq!(field(1, 3, gimli::DW_ATE_signed), "(int)-3");
+        assert_eq!(field(1, 3, gimli::DW_ATE_unsigned), "(int)5");
+        // a field may straddle bytes
+        assert_eq!(field(6, 4, gimli::DW_ATE_unsigned), "(int)1");
+    }
+
+    #[test]
+    fn variable_list_json() {
+        let variable = |name: &str, die_offset, is_parameter| VariableName {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            linkage_name: None,
+            type_name: "int".to_string(),
+            group_id: 0,
+            child_group_id: None,
+            layout: None,
+            die_offset,
+            unit_offset: die_offset.map(|_| 0),
+            decl_file: Some("src/\"main\".c".to_string()),
+            decl_line: Some(7),
+            is_parameter,
+            is_artificial: false,
+            available_ranges: None,
+        };
+        let mut argc = variable("argc", Some(0x2a), true);
+        argc.available_ranges = Some(vec![0x10..0x18, 0x20..0x24]);
+        let mut member = variable("p.x", None, false);
+        member.decl_file = None;
+        member.decl_line = None;
+
+        assert_eq!(format_variable_list(&[]), "[]");
+        assert_eq!(
+            format_variable_list(&[argc, member]),
+            "[{\"name\":\"argc\",\"typeName\":\"int\",\"kind\":\"parameter\",\
+             \"declFile\":\"src/\\\"main\\\".c\",\"declLine\":7,\
+             \"availableRanges\":[[16,24],[32,36]]},\
+             {\"name\":\"p.x\",\"typeName\":\"int\",\"kind\":\"member\",\
+             \"declFile\":null,\"declLine\":null,\"availableRanges\":null}]"
+        );
+    }
+
+    #[test]
+    fn scalar_values() {
+        let scalar = |varinfo: &VariableInfo| scalar_value(varinfo).unwrap();
+        let int = base_type("int", gimli::DW_ATE_signed, &(-7i32).to_le_bytes());
+        assert_eq!(scalar(&int), Scalar::Signed(-7));
+        let size = base_type("size_t", gimli::DW_ATE_unsigned, &u64::MAX.to_le_bytes());
+        assert_eq!(scalar(&size), Scalar::Unsigned(u64::MAX));
+        let float = base_type("float", gimli::DW_ATE_float, &1.5f32.to_le_bytes());
+        assert_eq!(scalar(&float), Scalar::Float(1.5));
+        let boolean = base_type("bool", gimli::DW_ATE_boolean, &[1]);
+        assert_eq!(scalar(&boolean), Scalar::Boolean(true));
+
+        let mut pointer = base_type("int *", gimli::DW_ATE_address, &0x1234u32.to_le_bytes());
+        pointer.tag = gimli::DW_TAG_pointer_type;
+        pointer.pointee = Some(Box::new(base_type("int", gimli::DW_ATE_signed, &[])));
+        assert_eq!(scalar(&pointer), Scalar::Unsigned(0x1234));
+
+        // too wide for an i64
+        let wide = [0xffu8; 15]
+            .iter()
+            .chain(&[0x7f])
+            .copied()
+            .collect::<Vec<_>>();
+        let int128 = base_type("__int128", gimli::DW_ATE_signed, &wide);
+        assert_eq!(scalar(&int128), Scalar::Bytes(wide));
+
+        let mut point = base_type("point", gimli::DW_ATE_signed, &[0; 8]);
+        point.tag = gimli::DW_TAG_structure_type;
+        let err = BridgeError::from_anyhow(scalar_value(&point).unwrap_err());
+        assert_eq!(err.kind(), "Unsupported");
+    }
+
+    /// `format_object` output for one minimal value of every kind of type,
+    /// which changes to the formatters must keep or update on purpose
+    #[test]
+    fn golden_output() {
+        let typed = |mut varinfo: VariableInfo, tag| {
+            varinfo.tag = tag;
+            varinfo
+        };
+
+        let mut color = typed(
+            base_type("Color", gimli::DW_ATE_unsigned, &[1, 0, 0, 0]),
+            gimli::DW_TAG_enumeration_type,
+        );
+        color.enumerators = vec![("Green".to_string(), BigInt::from(1))];
+
+        let mut array = typed(
+            base_type("short[2]", gimli::DW_ATE_signed, &[0xff, 0xff, 2, 0]),
+            gimli::DW_TAG_array_type,
+        );
+        array.element = Some(Box::new(base_type("short", gimli::DW_ATE_signed, &[0; 2])));
+        array.dimensions = vec![Some(2)];
+
+        let mut pointer = typed(
+            base_type("int *", gimli::DW_ATE_address, &[0x34, 0x12, 0, 0]),
+            gimli::DW_TAG_pointer_type,
+        );
+        pointer.pointee = Some(Box::new(base_type("int", gimli::DW_ATE_signed, &[0; 4])));
+
+        let cases = vec![
+            (
+                base_type("signed char", gimli::DW_ATE_signed, &[0x80]),
+                "(signed char)-128",
+            ),
+            (
+                base_type("short", gimli::DW_ATE_signed, &[0xff, 0x7f]),
+                "(short)32767",
+            ),
+            (
+                base_type("int", gimli::DW_ATE_signed, &(-42i32).to_le_bytes()),
+                "(int)-42",
+            ),
+            (
+                base_type("long long", gimli::DW_ATE_signed, &i64::MIN.to_le_bytes()),
+                "(long long)-9223372036854775808",
+            ),
+            (
+                base_type(
+                    "unsigned int",
+                    gimli::DW_ATE_unsigned,
+                    &u32::MAX.to_le_bytes(),
+                ),
+                "(unsigned int)4294967295",
+            ),
+            (
+                base_type("u64", gimli::DW_ATE_unsigned, &u64::MAX.to_le_bytes()),
+                "(u64)18446744073709551615",
+            ),
+            (
+                base_type("__int128", gimli::DW_ATE_signed, &(-1i128).to_le_bytes()),
+                "(__int128)-1",
+            ),
+            (
+                base_type("float", gimli::DW_ATE_float, &0.5f32.to_le_bytes()),
+                "(float)0.5",
+            ),
+            (
+                base_type("double", gimli::DW_ATE_float, &(-2.25f64).to_le_bytes()),
+                "(double)-2.25",
+            ),
+            (
+                base_type("bool", gimli::DW_ATE_boolean, &[0]),
+                "(bool)false",
+            ),
+            (
+                base_type("char", gimli::DW_ATE_signed_char, b"z"),
+                "(char)'z'",
+            ),
+            (color, "Color::Green (1)"),
+            (
+                typed(
+                    base_type("point", gimli::DwAte(0), &[0; 8]),
+                    gimli::DW_TAG_structure_type,
+                ),
+                "point",
+            ),
+            (array, "[(short)-1, (short)2]"),
+            (pointer, "(int *)0x1234"),
+            (char_array(b"hi\0"), "\"hi\""),
+        ];
+        for (varinfo, expected) in cases {
+            assert_eq!(
+                format_object(&varinfo).unwrap(),
+                expected,
+                "{}",
+                varinfo.name
+            );
+        }
+    }
+
+    #[test]
+    fn memory_dump() {
+        let mut slice = MemorySlice::new();
+        slice.address = 0x1000;
+        slice.byte_size = 18;
+        assert!(format_memory(&slice).is_err());
+
+        slice.set_memory_slice(&(0..18).collect::<Vec<u8>>());
+        assert_eq!(
+            format_memory(&slice).unwrap(),
+            "0x1000: 0x00 0x01 0x02 0x03 0x04 0x05 0x06 0x07 0x08 0x09 0x0a 0x0b 0x0c 0x0d 0x0e 0x0f\n\
+             0x1010: 0x10 0x11"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use anyhow::{anyhow, Result};
+use std::convert::TryFrom;
+
+use super::error::BridgeError;
+use super::sourcemap::DwarfSourceMap;
+use super::subroutine::DwarfSubroutineMap;
+use super::variables::DwarfGlobalVariables;
+use super::{DwarfDebugData, DwarfDebugInfo};
+
+/// Leading bytes of every index
+const INDEX_MAGIC: &[u8; 4] = b"WDWI";
+
+/// Layout version of the index, bumped whenever the encoding changes so that
+/// indexes written by older builds are rejected
+const INDEX_VERSION: u64 = 2;
+
+/// Encode the parts of the debug info that are parsed up front: the merged
+/// line tables and the code ranges of every unit
+///
+/// Subroutines and variables are read lazily from the DWARF sections either
+/// way, so they aren't part of the index.
+pub fn serialize_index(info: &DwarfDebugInfo, module_index: usize) -> Vec<u8> {
+    let mut writer = IndexWriter::new();
+    writer.bytes(INDEX_MAGIC);
+    writer.u64(INDEX_VERSION);
+    writer.u64(module_index as u64);
+    writer.u64(info.subroutine.dwarf_data.fingerprint());
+    info.sourcemap.write_index(&mut writer);
+    info.subroutine.write_index(&mut writer);
+    writer.data
+}
+
+/// Index of the module an index written by `serialize_index` describes, whose
+/// sections `transform_index` needs
+///
+/// Indexes of another version are rejected.
+pub fn index_module(index: &[u8]) -> Result<usize> {
+    let (module_index, _) = read_header(&mut IndexReader::new(index))?;
+    Ok(module_index)
+}
+
+/// Rebuild the debug info from an index written by `serialize_index` and the
+/// sections of the module it describes
+///
+/// Indexes of another version or of a different binary are rejected.
+pub fn transform_index(index: &[u8], dwarf_data: DwarfDebugData) -> Result<DwarfDebugInfo> {
+    let mut reader = IndexReader::new(index);
+    let (_, fingerprint) = read_header(&mut reader)?;
+    if dwarf_data.fingerprint() != fingerprint {
+        Err(BridgeError::Unsupported(
+            "index was written for a different binary".to_string(),
+        ))?
+    }
+
+    let sourcemap = DwarfSourceMap::read_index(&mut reader, dwarf_data.clone())?;
+    let subroutine = DwarfSubroutineMap::read_index(&mut reader, dwarf_data.clone())?;
+    if !reader.is_at_end() {
+        Err(anyhow!("trailing data at index offset {}", reader.position))?
+    }
+
+    Ok(DwarfDebugInfo {
+        sourcemap,
+        subroutine,
+        global_variables: DwarfGlobalVariables { dwarf_data },
+    })
+}
+
+/// Check the magic and version, and read the module index and fingerprint
+fn read_header(reader: &mut IndexReader) -> Result<(usize, u64)> {
+    if reader.bytes(INDEX_MAGIC.len()).ok() != Some(&INDEX_MAGIC[..]) {
+        Err(BridgeError::Unsupported(
+            "not a debug info index".to_string(),
+        ))?
+    }
+    let version = reader.u64()?;
+    if version != INDEX_VERSION {
+        Err(BridgeError::Unsupported(format!(
+            "index version {} is not supported, expected {}",
+            version, INDEX_VERSION
+        )))?
+    }
+    let module_index = reader.usize()?;
+    let fingerprint = reader.u64()?;
+    Ok((module_index, fingerprint))
+}
+
+/// Appends ULEB128 numbers and length prefixed strings
+pub(crate) struct IndexWriter {
+    data: Vec<u8>,
+}
+
+impl IndexWriter {
+    fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn u64(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.data.push(byte);
+                return;
+            }
+            self.data.push(byte | 0x80);
+        }
+    }
+
+    pub(crate) fn bool(&mut self, value: bool) {
+        self.data.push(value as u8);
+    }
+
+    pub(crate) fn string(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes(value.as_bytes());
+    }
+}
+
+/// Reads back what `IndexWriter` wrote, failing on truncated data
+pub(crate) struct IndexReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> IndexReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.position == self.data.len()
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .position
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| anyhow!("index is truncated at offset {}", self.position))?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.bytes(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow!("overlong number at index offset {}", self.position))
+    }
+
+    pub(crate) fn usize(&mut self) -> Result<usize> {
+        let value = self.u64()?;
+        usize::try_from(value).map_err(|_| {
+            anyhow!(
+                "{} at index offset {} is out of range",
+                value,
+                self.position
+            )
+        })
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool> {
+        Ok(self.bytes(1)?[0] != 0)
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String> {
+        let len = self.usize()?;
+        let bytes = self.bytes(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| anyhow!("invalid string at index offset {}: {}", self.position, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{load_custom_sections, transform_dwarf};
+    use super::*;
+
+    const MEMORY64: &[u8] = include_bytes!("../../tests/fixtures/memory64.wasm");
+
+    fn sections(module_index: usize) -> Result<DwarfDebugData> {
+        DwarfDebugData::from_sections(load_custom_sections(MEMORY64, module_index)?, None)
+    }
+
+    fn load(index: &[u8]) -> Result<DwarfDebugInfo> {
+        transform_index(index, sections(index_module(index)?)?)
+    }
+
+    fn rejected(index: &[u8]) -> BridgeError {
+        match load(index) {
+            Ok(_) => panic!("index was accepted"),
+            Err(err) => BridgeError::from_anyhow(err),
+        }
+    }
+
+    #[test]
+    fn numbers_roundtrip() {
+        let numbers = [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u32::MAX as u64, u64::MAX];
+        let mut writer = IndexWriter::new();
+        for number in numbers.iter() {
+            writer.u64(*number);
+        }
+        writer.string("main.c");
+        writer.bool(true);
+
+        let mut reader = IndexReader::new(&writer.data);
+        for number in numbers.iter() {
+            assert_eq!(reader.u64().unwrap(), *number);
+        }
+        assert_eq!(reader.string().unwrap(), "main.c");
+        assert!(reader.bool().unwrap());
+        assert!(reader.is_at_end());
+        assert!(reader.u64().is_err());
+    }
+
+    #[test]
+    fn stale_indexes() {
+        let info = transform_dwarf(sections(0).unwrap()).unwrap();
+        let index = serialize_index(&info, 0);
+        assert!(load(&index).is_ok());
+
+        assert_eq!(rejected(b"\0asm").kind(), "Unsupported");
+
+        // the version follows the magic
+        let mut newer = index.clone();
+        newer[INDEX_MAGIC.len()] += 1;
+        assert_eq!(rejected(&newer).kind(), "Unsupported");
+
+        // so do the module index and the fingerprint of the sections
+        let mut other_binary = index.clone();
+        other_binary[INDEX_MAGIC.len() + 2] ^= 1;
+        assert_eq!(rejected(&other_binary).kind(), "Unsupported");
+
+        let truncated = &index[..index.len() - 1];
+        assert_eq!(rejected(truncated).kind(), "Internal");
+    }
+}
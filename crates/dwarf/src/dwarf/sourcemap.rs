@@ -2,11 +2,14 @@ use anyhow::{anyhow, Result};
 use gimli::{DebugLine, DebugLineOffset, DebuggingInformationEntry, LineRow, Reader, Unit};
 
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap};
-use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
 
+use super::index::{IndexReader, IndexWriter};
 use super::utils::{
-    clone_string_attribute, convert_from_windows_stype_path, is_absolute_path, normalize_path,
+    clone_string_attribute, convert_from_windows_stype_path, normalize_path, replace_path_prefix,
+    resolve_path,
 };
 use super::{DwarfDebugData, DwarfReader, DwarfReaderOffset};
 
@@ -38,7 +41,7 @@ pub fn transform_debug_line(
 
     let program = debug_line
         .program(offset, unit.header.address_size(), None, None)
-        .expect("parsable debug_line");
+        .map_err(|e| anyhow!("failed to parse line program at {:?}: {}", offset, e))?;
 
     let header = program.header();
 
@@ -54,8 +57,9 @@ pub fn transform_debug_line(
         sequence_base_index = 0;
     }
 
+    // DWARF 5 stores these as form-encoded entries, mostly DW_FORM_line_strp
     for dir in header.include_directories() {
-        dirs.push(clone_string_attribute(dwarf, unit, dir.clone()).expect("parsable dir string"));
+        dirs.push(clone_string_attribute(dwarf, unit, dir.clone()).unwrap_or_default());
     }
 
     if header.version() <= 4 {
@@ -69,53 +73,63 @@ pub fn transform_debug_line(
             None => String::from("unknown"),
         };
 
-        let path = match is_absolute_path(&file_name) {
-            true => file_name,
-            false => format!("{}/{}", folder_name, file_name),
-        };
-        let path = convert_from_windows_stype_path(&path);
-        let path = normalize_path(&path);
-
-        files.push(PathBuf::from(&path));
-        file_sorted_rows.insert(0, BTreeMap::new());
+        files.push(PathBuf::from(resolve_path(&folder_name, "", &file_name)));
+        file_sorted_rows.insert(0, Vec::new());
     }
 
+    let comp_dir = match unit.comp_dir {
+        Some(ref comp_dir) => String::from_utf8(comp_dir.to_slice()?.to_vec()).unwrap_or_default(),
+        None => String::new(),
+    };
     for (file_index, file_entry) in header.file_names().iter().enumerate() {
-        let dir = &dirs[file_entry.directory_index() as usize];
-        let dir = convert_from_windows_stype_path(dir);
-
-        let dir_path = Path::new(&dir);
-        let path = clone_string_attribute(dwarf, unit, file_entry.path_name())?;
-        let mut path = dir_path.join(convert_from_windows_stype_path(&path));
-
-        if !is_absolute_path(path.to_str().unwrap_or_default()) {
-            if let Some(ref comp_dir) = unit.comp_dir {
-                let comp_dir = String::from_utf8(comp_dir.to_slice()?.to_vec()).unwrap_or_default();
-                let comp_dir = convert_from_windows_stype_path(&comp_dir);
-                path = Path::new(&comp_dir).join(path);
-            }
-        }
+        let dir = match dirs.get(file_entry.directory_index() as usize) {
+            Some(dir) => dir.as_str(),
+            None => "",
+        };
 
-        files.push(PathBuf::from(&normalize_path(
-            &path.to_string_lossy().into_owned(),
-        )));
-        file_sorted_rows.insert(file_index + sequence_base_index, BTreeMap::new());
+        let path = match clone_string_attribute(dwarf, unit, file_entry.path_name()) {
+            Ok(path) => path,
+            Err(_) => {
+                files.push(PathBuf::from(format!(
+                    "??? (unresolved file {})",
+                    file_index + sequence_base_index
+                )));
+                file_sorted_rows.insert(file_index + sequence_base_index, Vec::new());
+                continue;
+            }
+        };
+        files.push(PathBuf::from(resolve_path(&comp_dir, dir, &path)));
+        file_sorted_rows.insert(file_index + sequence_base_index, Vec::new());
     }
 
     let mut rows = program.rows();
     let mut sorted_rows = BTreeMap::new();
+    let mut prologue_ends = Vec::new();
     while let Some((_, row)) = rows.next_row()? {
-        sorted_rows.insert(row.address(), *row);
+        if row.prologue_end() && !row.end_sequence() {
+            prologue_ends.push(row.address());
+        }
+
+        // a sequence may start right where another one ends
+        if row.end_sequence() {
+            sorted_rows.entry(row.address()).or_insert(*row);
+        } else {
+            sorted_rows.insert(row.address(), *row);
+        }
+
+        if row.end_sequence() {
+            continue;
+        }
 
         match file_sorted_rows.get_mut(&(row.file_index() as usize)) {
             Some(x) => {
-                x.insert(
+                x.push((
                     match row.line() {
                         Some(x) => x.get(),
                         None => 0,
                     },
                     *row,
-                );
+                ));
             }
             None => {}
         }
@@ -123,30 +137,63 @@ pub fn transform_debug_line(
     let sorted_rows: Vec<_> = sorted_rows.into_iter().collect();
     let mapped_file_sorted_rows: Vec<(usize, Vec<(u64, LineRow)>)> = file_sorted_rows
         .into_iter()
-        .map(|x| (x.0, x.1.into_iter().collect()))
+        .map(|(file_index, mut rows)| {
+            rows.sort_by_key(|(line, row)| (*line, row.address()));
+            (file_index, rows)
+        })
         .collect();
     Ok(DwarfUnitSourceMap {
         address_sorted_rows: sorted_rows,
         file_sorted_rows: mapped_file_sorted_rows,
+        prologue_ends,
         paths: files,
     })
 }
 
+/// Resolve a file index of the unit's line program (e.g. DW_AT_call_file) to a path
+pub fn file_path_from_index(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader, DwarfReaderOffset>,
+    file_index: u64,
+) -> Option<String> {
+    let header = unit.line_program.as_ref()?.header();
+    let file_entry = header.file(file_index)?;
+
+    let path = clone_string_attribute(dwarf, unit, file_entry.path_name()).ok()?;
+    let dir = match file_entry.directory(header) {
+        Some(attr) => clone_string_attribute(dwarf, unit, attr).unwrap_or_default(),
+        None => String::new(),
+    };
+    let comp_dir = match unit.comp_dir {
+        Some(ref comp_dir) => {
+            String::from_utf8(comp_dir.to_slice().ok()?.to_vec()).unwrap_or_default()
+        }
+        None => String::new(),
+    };
+
+    Some(resolve_path(&comp_dir, &dir, &path))
+}
+
 pub struct DwarfUnitSourceMap {
     address_sorted_rows: Vec<(u64, LineRow)>,
     file_sorted_rows: Vec<(usize, Vec<(u64, LineRow)>)>,
+    /// Addresses of rows flagged `prologue_end`
+    prologue_ends: Vec<u64>,
     paths: Vec<std::path::PathBuf>,
 }
 
 fn transform_lineinfo(row: &LineRow, paths: &Vec<std::path::PathBuf>) -> LineInfo {
-    let filepath = paths[row.file_index() as usize].clone();
     LineInfo {
-        filepath: filepath.to_str().unwrap_or_default().to_string(),
-        line: if let Some(x) = row.line() { Some(x.get()) } else { None },
-        column: match row.column() {
-            gimli::ColumnType::Column(c) => ColumnType::Column(c.get()),
-            gimli::ColumnType::LeftEdge => ColumnType::LeftEdge,
-        },
+        filepath: transform_file_index(row.file_index() as usize, paths),
+        line: row.line().map(|x| x.get()),
+        column: transform_column(row),
+    }
+}
+
+fn transform_column(row: &LineRow) -> ColumnType {
+    match row.column() {
+        gimli::ColumnType::Column(c) => ColumnType::Column(c.get()),
+        gimli::ColumnType::LeftEdge => ColumnType::LeftEdge,
     }
 }
 fn transform_file_index(file_index: usize, paths: &Vec<std::path::PathBuf>) -> String {
@@ -159,15 +206,42 @@ fn transform_file_index(file_index: usize, paths: &Vec<std::path::PathBuf>) -> S
     }
 }
 
+/// A line table row of a source file, stored under its line
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct FileRow {
+    address: u64,
+    column: ColumnType,
+    is_stmt: bool,
+}
+
+/// Sorted addresses of the rows flagged `is_stmt`, the file rows being all but
+/// the ends of sequences
+fn statement_starts(file_rows: &[(String, Vec<(u64, FileRow)>)]) -> Vec<u64> {
+    let mut starts: Vec<u64> = file_rows
+        .iter()
+        .flat_map(|(_, rows)| rows.iter())
+        .filter(|(_, row)| row.is_stmt)
+        .map(|(_, row)| row.address)
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+    starts
+}
+
 pub struct DwarfSourceMap {
     /// Source files -> DebugLineOffsets mapping table
     file_sorted_entry_offsets: Vec<(String, DebugLineOffset)>,
-    /// Source files -> LineRows by source lines mapping table
-    file_sorted_rows: Vec<(String, Vec<(u64, LineRow)>)>,
-    /// Code address -> Source files mapping table
-    address_sorted_rows: Vec<(u64, LineInfo)>,
-
-    directory_map: RefCell<HashMap<String, String>>,
+    /// Source files -> rows by source lines mapping table
+    file_sorted_rows: Vec<(String, Vec<(u64, FileRow)>)>,
+    /// Code address -> Source files mapping table, `None` marks an end of sequence
+    address_sorted_rows: Vec<(u64, Option<LineInfo>)>,
+    /// Sorted addresses of rows flagged `prologue_end`
+    prologue_ends: Vec<u64>,
+    /// Sorted addresses of rows flagged `is_stmt`
+    statement_starts: Vec<u64>,
+
+    /// Path prefix substitutions as (recorded prefix, reported prefix) pairs
+    path_substitutions: RefCell<Vec<(String, String)>>,
 
     dwarf_data: DwarfDebugData,
 }
@@ -176,75 +250,450 @@ impl DwarfSourceMap {
     pub fn new(units: Vec<DwarfUnitSourceMap>, dwarf_data: DwarfDebugData) -> Self {
         let mut address_rows = BTreeMap::new();
         let mut file_rows = BTreeMap::new();
+        let mut prologue_ends = Vec::new();
         for unit in units {
             let path = unit.paths;
+            prologue_ends.extend(unit.prologue_ends);
 
             for (addr, row) in &unit.address_sorted_rows {
-                let line_info = transform_lineinfo(row, &path);
-                address_rows.insert(*addr, line_info);
+                if row.end_sequence() {
+                    address_rows.entry(*addr).or_insert(None);
+                } else {
+                    address_rows.insert(*addr, Some(transform_lineinfo(row, &path)));
+                }
             }
             for (file_index, vec) in unit.file_sorted_rows {
                 let file_name = transform_file_index(file_index, &path);
-                file_rows.insert(file_name, vec);
+                file_rows
+                    .entry(file_name)
+                    .or_insert_with(Vec::new)
+                    .extend(vec.iter().map(|(line, row)| {
+                        let row = FileRow {
+                            address: row.address(),
+                            column: transform_column(row),
+                            is_stmt: row.is_stmt(),
+                        };
+                        (*line, row)
+                    }));
             }
         }
+        // headers and templates show up in several units
+        for rows in file_rows.values_mut() {
+            rows.sort_by_key(|(line, row): &(u64, FileRow)| (*line, row.address));
+        }
+        prologue_ends.sort_unstable();
+        prologue_ends.dedup();
+        let file_sorted_rows: Vec<_> = file_rows.into_iter().collect();
         Self {
             file_sorted_entry_offsets: Vec::new(),
             address_sorted_rows: address_rows.into_iter().collect(),
-            file_sorted_rows: file_rows.into_iter().collect(),
-            directory_map: RefCell::new(HashMap::new()),
+            statement_starts: statement_starts(&file_sorted_rows),
+            file_sorted_rows,
+            prologue_ends,
+            path_substitutions: RefCell::new(Vec::new()),
             dwarf_data,
         }
     }
 
-    pub fn set_directory_map(&self, from: String, to: String) {
-        self.directory_map.borrow_mut().insert(from, to);
+    /// Encode the line tables for `read_index`
+    ///
+    /// Paths are stored once in a table and referred to by position.
+    /// Addresses are stored as deltas of the preceding one.
+    pub(crate) fn write_index(&self, writer: &mut IndexWriter) {
+        let mut paths: Vec<&str> = self
+            .file_sorted_rows
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .chain(
+                self.address_sorted_rows
+                    .iter()
+                    .filter_map(|(_, info)| Some(info.as_ref()?.filepath.as_str())),
+            )
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+        let path_index = |path: &str| paths.binary_search(&path).unwrap_or_default() as u64;
+        // line and column 0 stand for none, as neither can be 0 in a line table
+        let column = |column: ColumnType| match column {
+            ColumnType::LeftEdge => 0,
+            ColumnType::Column(c) => c,
+        };
+
+        writer.u64(paths.len() as u64);
+        for path in &paths {
+            writer.string(path);
+        }
+
+        writer.u64(self.address_sorted_rows.len() as u64);
+        let mut previous = 0;
+        for (address, info) in &self.address_sorted_rows {
+            writer.u64(address - previous);
+            previous = *address;
+            match info {
+                Some(info) => {
+                    writer.u64(path_index(&info.filepath) + 1);
+                    writer.u64(info.line.unwrap_or_default());
+                    writer.u64(column(info.column));
+                }
+                None => writer.u64(0),
+            }
+        }
+
+        writer.u64(self.file_sorted_rows.len() as u64);
+        for (path, rows) in &self.file_sorted_rows {
+            writer.u64(path_index(path));
+            writer.u64(rows.len() as u64);
+            for (line, row) in rows {
+                writer.u64(*line);
+                writer.u64(row.address);
+                writer.u64(column(row.column));
+                writer.bool(row.is_stmt);
+            }
+        }
+
+        writer.u64(self.prologue_ends.len() as u64);
+        let mut previous = 0;
+        for address in &self.prologue_ends {
+            writer.u64(address - previous);
+            previous = *address;
+        }
+    }
+
+    /// Decode line tables written by `write_index`
+    pub(crate) fn read_index(reader: &mut IndexReader, dwarf_data: DwarfDebugData) -> Result<Self> {
+        let mut paths = Vec::new();
+        for _ in 0..reader.usize()? {
+            paths.push(reader.string()?);
+        }
+        let path = |index: usize| match paths.get(index) {
+            Some(path) => Ok(path.clone()),
+            None => Err(anyhow!("path {} is not in the index", index)),
+        };
+        let column = |column: u64| match column {
+            0 => ColumnType::LeftEdge,
+            c => ColumnType::Column(c),
+        };
+
+        let mut address_sorted_rows = Vec::new();
+        let mut address = 0u64;
+        for _ in 0..reader.usize()? {
+            address = address.wrapping_add(reader.u64()?);
+            match reader.usize()? {
+                0 => address_sorted_rows.push((address, None)),
+                index => {
+                    let filepath = path(index - 1)?;
+                    let line = match reader.u64()? {
+                        0 => None,
+                        line => Some(line),
+                    };
+                    let column = column(reader.u64()?);
+                    address_sorted_rows.push((
+                        address,
+                        Some(LineInfo {
+                            filepath,
+                            line,
+                            column,
+                        }),
+                    ));
+                }
+            }
+        }
+
+        let mut file_sorted_rows = Vec::new();
+        for _ in 0..reader.usize()? {
+            let filepath = path(reader.usize()?)?;
+            let mut rows = Vec::new();
+            for _ in 0..reader.usize()? {
+                let line = reader.u64()?;
+                let row = FileRow {
+                    address: reader.u64()?,
+                    column: column(reader.u64()?),
+                    is_stmt: reader.bool()?,
+                };
+                rows.push((line, row));
+            }
+            file_sorted_rows.push((filepath, rows));
+        }
+
+        let mut prologue_ends = Vec::new();
+        let mut address = 0u64;
+        for _ in 0..reader.usize()? {
+            address = address.wrapping_add(reader.u64()?);
+            prologue_ends.push(address);
+        }
+
+        Ok(Self {
+            file_sorted_entry_offsets: Vec::new(),
+            address_sorted_rows,
+            statement_starts: statement_starts(&file_sorted_rows),
+            file_sorted_rows,
+            prologue_ends,
+            path_substitutions: RefCell::new(Vec::new()),
+            dwarf_data,
+        })
+    }
+
+    /// Report files recorded under the `from` directory as living under `to`,
+    /// like `-fdebug-prefix-map=from=to` would have at build time
+    ///
+    /// Lookups by a path under `to` find the files recorded under `from`.
+    /// Registering `from` again replaces its substitution.
+    pub fn add_path_substitution(&self, from: &str, to: &str) {
+        let from = normalize_path(&convert_from_windows_stype_path(&from.to_string()));
+        let to = normalize_path(&convert_from_windows_stype_path(&to.to_string()));
+        let mut substitutions = self.path_substitutions.borrow_mut();
+        substitutions.retain(|(recorded, _)| *recorded != from);
+        substitutions.push((from, to));
+    }
+
+    /// Apply the substitution with the longest matching prefix to a recorded path
+    pub(crate) fn substitute_path(&self, path: &str) -> String {
+        self.path_substitutions
+            .borrow()
+            .iter()
+            .filter_map(|(from, to)| Some((from.len(), replace_path_prefix(path, from, to)?)))
+            .max_by_key(|(len, _)| *len)
+            .map_or_else(|| path.to_string(), |(_, path)| path)
+    }
+
+    /// Undo the substitution with the longest matching prefix of a reported path
+    fn recorded_path(&self, path: &str) -> Option<String> {
+        self.path_substitutions
+            .borrow()
+            .iter()
+            .filter_map(|(from, to)| Some((to.len(), replace_path_prefix(path, to, from)?)))
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, path)| path)
     }
 
     fn update_file_sorted_rows(&mut self, _offset: DebugLineOffset) {}
 
+    /// List all source files referenced by the line programs
+    pub fn file_list(&self) -> Vec<String> {
+        let mut files: Vec<String> = self
+            .file_sorted_rows
+            .iter()
+            .filter(|(path, _)| !path.starts_with("???"))
+            .map(|(path, _)| self.substitute_path(path))
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+
     pub fn find_line_info(&self, offset: usize) -> Option<LineInfo> {
         let mut line_info = match self
             .address_sorted_rows
             .binary_search_by_key(&(offset as u64), |i| i.0)
         {
-            Ok(i) => self.address_sorted_rows[i].1.clone(),
+            Ok(i) => self.address_sorted_rows[i].1.clone()?,
             Err(i) => {
                 if i > 0 {
-                    self.address_sorted_rows[i - 1].1.clone()
+                    self.address_sorted_rows[i - 1].1.clone()?
                 } else {
                     return None;
                 }
             }
         };
-        for (from, to) in self.directory_map.borrow().iter() {
-            line_info.filepath = line_info.filepath.replace(from, to);
-        }
+        line_info.filepath = self.substitute_path(&line_info.filepath);
         Some(line_info)
     }
 
+    /// Find the first address past the prologue of the code in `range`
+    ///
+    /// This is the first row flagged `prologue_end`, or the second row of the
+    /// range when the producer doesn't emit the flag.
+    pub fn find_prologue_end(&self, range: &Range<u64>) -> Option<u64> {
+        let first = self.prologue_ends.partition_point(|x| *x < range.start);
+        if let Some(address) = self.prologue_ends.get(first) {
+            if range.contains(address) {
+                return Some(*address);
+            }
+        }
+
+        let next = self
+            .address_sorted_rows
+            .partition_point(|(address, _)| *address <= range.start);
+        match self.address_sorted_rows.get(next) {
+            Some((address, Some(_))) if range.contains(address) => Some(*address),
+            _ => None,
+        }
+    }
+
+    /// Whether a row flagged `is_stmt` starts at an address
+    ///
+    /// Addresses a row covers past its start are not statement starts.
+    pub fn is_statement_start(&self, offset: usize) -> bool {
+        self.statement_starts
+            .binary_search(&(offset as u64))
+            .is_ok()
+    }
+
     pub fn find_address(&self, file: &LineInfo) -> Option<usize> {
-        let escaped_filename = convert_from_windows_stype_path(&file.filepath);
-        let escaped_filename = normalize_path(&escaped_filename);
-        let line_vec = match self
-            .file_sorted_rows
-            .binary_search_by(|i| i.0.cmp(&escaped_filename))
-        {
-            Ok(i) => &self.file_sorted_rows[i].1,
-            Err(_) => {
-                return None;
+        self.find_rows(file).first().map(|row| row.address as usize)
+    }
+
+    /// Find all code addresses of the source location in ascending order
+    pub fn find_addresses(&self, file: &LineInfo) -> Vec<usize> {
+        let mut addresses: Vec<usize> = self
+            .find_rows(file)
+            .iter()
+            .map(|row| row.address as usize)
+            .collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+        addresses
+    }
+
+    /// Find every source location of a file that has code, sorted by line and
+    /// then column
+    ///
+    /// Only rows the producer marks as statements are listed. Rows without a
+    /// line are skipped, and rows at the left edge have no column.
+    pub fn find_breakable_locations(&self, filepath: &str) -> Vec<LineInfo> {
+        let mut locations: Vec<(u64, ColumnType)> = self
+            .file_rows(filepath)
+            .iter()
+            .filter(|(line, row)| *line != 0 && row.is_stmt)
+            .map(|(line, row)| (*line, row.column))
+            .collect();
+        locations.sort_by_key(|(line, column)| {
+            let column = match column {
+                ColumnType::LeftEdge => 0,
+                ColumnType::Column(c) => *c,
+            };
+            (*line, column)
+        });
+        locations.dedup();
+
+        locations
+            .into_iter()
+            .map(|(line, column)| LineInfo {
+                filepath: filepath.to_string(),
+                line: Some(line),
+                column,
+            })
+            .collect()
+    }
+
+    /// Find the first line from `line` on that has code, and the lowest
+    /// address of its statements
+    ///
+    /// Returns `None` when no line of the file from there on has code.
+    pub fn resolve_breakpoint(&self, filepath: &str, line: u64) -> Option<(u64, usize)> {
+        let rows = self.file_rows(filepath);
+        let first = rows.partition_point(|(row_line, _)| *row_line < line.max(1));
+        let (line, _) = rows[first..].iter().find(|(_, row)| row.is_stmt)?;
+        let address = rows[first..]
+            .iter()
+            .take_while(|(row_line, _)| row_line == line)
+            .filter(|(_, row)| row.is_stmt)
+            .map(|(_, row)| row.address)
+            .min()?;
+        Some((*line, address as usize))
+    }
+
+    /// Address ranges of the lines in the line tables, sorted by start
+    ///
+    /// Consecutive rows of the same line are merged into one range, and no
+    /// range spans the end of a sequence. Rows without a line are listed with
+    /// a line of `None`.
+    pub fn line_ranges(&self) -> Vec<(Range<u64>, LineInfo)> {
+        let mut ranges: Vec<(Range<u64>, LineInfo)> = Vec::new();
+        for pair in self.address_sorted_rows.windows(2) {
+            let (start, end) = (pair[0].0, pair[1].0);
+            let info = match &pair[0].1 {
+                Some(info) => info,
+                None => continue,
+            };
+            match ranges.last_mut() {
+                Some((range, last))
+                    if range.end == start
+                        && last.line == info.line
+                        && last.filepath == info.filepath =>
+                {
+                    range.end = end
+                }
+                _ => ranges.push((start..end, info.clone())),
             }
-        };
+        }
+        for (_, info) in &mut ranges {
+            info.filepath = self.substitute_path(&info.filepath);
+        }
+        ranges
+    }
 
-        match line_vec.binary_search_by_key(&file.line.unwrap_or_default(), |i| i.0) {
-            Ok(i) => Some(line_vec[i].1.address() as usize),
-            Err(i) => {
-                if i > 0 {
-                    Some(line_vec[i - 1].1.address() as usize)
-                } else {
-                    None
+    /// Address ranges of the line sequences, sorted by start
+    ///
+    /// A sequence starting where another ends continues it, as the rows of all
+    /// units are merged. Rows past the last end of a sequence cover nothing.
+    pub fn sequences(&self) -> Vec<Range<u64>> {
+        let mut sequences = Vec::new();
+        let mut start = None;
+        for (address, info) in &self.address_sorted_rows {
+            match (info, start) {
+                (Some(_), None) => start = Some(*address),
+                (None, Some(begin)) => {
+                    sequences.push(begin..*address);
+                    start = None;
                 }
+                _ => {}
             }
         }
+        sequences
+    }
+
+    /// Rows of a file sorted by line, empty for files without line info
+    ///
+    /// The path is normalized and may be either a reported path or the one
+    /// recorded in the line program.
+    fn file_rows(&self, filepath: &str) -> &[(u64, FileRow)] {
+        let escaped_filename = convert_from_windows_stype_path(&filepath.to_string());
+        let escaped_filename = normalize_path(&escaped_filename);
+        let recorded = self.recorded_path(&escaped_filename);
+        recorded
+            .iter()
+            .chain(std::iter::once(&escaped_filename))
+            .find_map(|path| {
+                let i = self
+                    .file_sorted_rows
+                    .binary_search_by(|i| i.0.cmp(path))
+                    .ok()?;
+                Some(self.file_sorted_rows[i].1.as_slice())
+            })
+            .unwrap_or(&[])
+    }
+
+    fn find_rows(&self, file: &LineInfo) -> Vec<&FileRow> {
+        let line_vec = self.file_rows(&file.filepath);
+
+        let line = file.line.unwrap_or_default();
+        let first = line_vec.partition_point(|i| i.0 < line);
+        let last = line_vec.partition_point(|i| i.0 <= line);
+
+        if first == last {
+            // no rows on this line, fall back to the nearest preceding line
+            return if first > 0 {
+                vec![&line_vec[first - 1].1]
+            } else {
+                Vec::new()
+            };
+        }
+
+        let rows_on_line = &line_vec[first..last];
+        let exact_column: Vec<_> = match file.column {
+            ColumnType::Column(column) => rows_on_line
+                .iter()
+                .filter(|(_, row)| row.column == ColumnType::Column(column))
+                .map(|(_, row)| row)
+                .collect(),
+            ColumnType::LeftEdge => Vec::new(),
+        };
+
+        if exact_column.is_empty() {
+            rows_on_line.iter().map(|(_, row)| row).collect()
+        } else {
+            exact_column
+        }
     }
 }
@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Result};
+use gimli::{DebugInfoOffset, DwoId, EndianRcSlice, Endianity, RunTimeEndian, SectionId};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+use super::{Dwarf, DwarfReader, DwarfUnit};
+
+/// Where a split unit is found in the companion data
+pub(crate) struct SplitUnitEntry {
+    /// Byte ranges of the unit in the sections of a package, empty for a
+    /// `.dwo` file
+    contributions: Contributions,
+    /// Offset of the unit in the `.debug_info.dwo` section, once narrowed to
+    /// the contributions
+    offset: DebugInfoOffset,
+}
+
+/// Split units of the companion data keyed by their `dwo_id`
+pub(crate) type SplitUnits = HashMap<DwoId, SplitUnitEntry>;
+
+/// Find every split unit in the companion data
+///
+/// For a `.dwp` package the units are listed by its `.debug_cu_index`, while
+/// the units of a `.dwo` file are parsed once to read their `dwo_id`.
+pub(crate) fn index_split_units(
+    split_sections: &HashMap<String, Rc<[u8]>>,
+    main_sections: &HashMap<String, Rc<[u8]>>,
+    endian: RunTimeEndian,
+) -> Result<SplitUnits> {
+    let mut units = HashMap::new();
+
+    if let Some(index) = split_sections.get(".debug_cu_index") {
+        for (dwo_id, contributions) in index_rows(index, endian)? {
+            let entry = SplitUnitEntry {
+                contributions,
+                offset: DebugInfoOffset(0),
+            };
+            units.insert(dwo_id, entry);
+        }
+        return Ok(units);
+    }
+
+    let dwarf = load_split_dwarf(split_sections, main_sections, &[], endian)?;
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let offset = match header.offset().as_debug_info_offset() {
+            Some(offset) => offset,
+            None => continue,
+        };
+        if let Some(dwo_id) = dwarf.unit(header)?.dwo_id {
+            let entry = SplitUnitEntry {
+                contributions: Vec::new(),
+                offset,
+            };
+            units.insert(dwo_id, entry);
+        }
+    }
+    Ok(units)
+}
+
+/// Load the split DWARF sections holding a unit
+///
+/// Sections that only exist in the main file, such as `.debug_addr`, are taken
+/// from `main_sections`. For a `.dwp` package the sections are narrowed to the
+/// unit's `contributions`.
+fn load_split_dwarf(
+    split_sections: &HashMap<String, Rc<[u8]>>,
+    main_sections: &HashMap<String, Rc<[u8]>>,
+    contributions: &[(SectionId, Range<usize>)],
+    endian: RunTimeEndian,
+) -> Result<Dwarf> {
+    let load_section = |id: gimli::SectionId| -> Result<DwarfReader> {
+        let data = match split_section_name(id) {
+            Some(name) => match split_sections.get(name) {
+                Some(section) => match contributions.iter().find(|(s, _)| *s == id) {
+                    Some((_, range)) => Rc::from(
+                        section
+                            .get(range.clone())
+                            .ok_or_else(|| anyhow!("contribution {:?} exceeds {}", range, name))?,
+                    ),
+                    None => section.clone(),
+                },
+                None => Rc::from(&[][..]),
+            },
+            None => match main_sections.get(id.name()) {
+                Some(section) => section.clone(),
+                None => Rc::from(&[][..]),
+            },
+        };
+
+        Ok(EndianRcSlice::new(data, endian))
+    };
+
+    let mut dwarf = Dwarf::load(&load_section)?;
+    dwarf.file_type = gimli::DwarfFileType::Dwo;
+    Ok(dwarf)
+}
+
+/// Parse the split unit of a skeleton unit and copy over the attributes that
+/// only the skeleton carries
+pub(crate) fn split_unit(
+    split_sections: &HashMap<String, Rc<[u8]>>,
+    main_sections: &HashMap<String, Rc<[u8]>>,
+    entry: &SplitUnitEntry,
+    skeleton: &DwarfUnit,
+    endian: RunTimeEndian,
+) -> Result<(Dwarf, DwarfUnit)> {
+    let dwarf = load_split_dwarf(split_sections, main_sections, &entry.contributions, endian)?;
+    let header = dwarf.debug_info.header_from_offset(entry.offset)?;
+    let mut unit = dwarf.unit(header)?;
+
+    unit.low_pc = skeleton.low_pc;
+    unit.addr_base = skeleton.addr_base;
+    if unit.header.version() < 5 {
+        unit.rnglists_base = skeleton.rnglists_base;
+    }
+    if unit.line_program.is_none() {
+        unit.line_program = skeleton.line_program.clone();
+    }
+    if unit.comp_dir.is_none() {
+        unit.comp_dir = skeleton.comp_dir.clone();
+    }
+    Ok((dwarf, unit))
+}
+
+/// Byte ranges of a unit within the sections of a package
+type Contributions = Vec<(SectionId, Range<usize>)>;
+
+fn split_section_name(id: SectionId) -> Option<&'static str> {
+    match id {
+        SectionId::DebugTypes => Some(".debug_types.dwo"),
+        _ => id.dwo_name(),
+    }
+}
+
+/// Read the section contributions of every unit in a `.debug_cu_index`
+///
+/// Both the GNU version 2 and the DWARF 5 package formats are accepted.
+fn index_rows(index: &[u8], endian: RunTimeEndian) -> Result<Vec<(DwoId, Contributions)>> {
+    let read_u32 = |offset: usize| -> Result<u32> {
+        index
+            .get(offset..offset + 4)
+            .map(|bytes| endian.read_u32(bytes))
+            .ok_or_else(|| anyhow!("unit index is truncated at {}", offset))
+    };
+    let read_u64 = |offset: usize| -> Result<u64> {
+        index
+            .get(offset..offset + 8)
+            .map(|bytes| endian.read_u64(bytes))
+            .ok_or_else(|| anyhow!("unit index is truncated at {}", offset))
+    };
+
+    let version = match read_u32(0)? {
+        2 => 2,
+        // DWARF 5 stores a 2-byte version followed by 2 bytes of padding
+        _ => match index.get(0..2).map(|bytes| endian.read_u16(bytes)) {
+            Some(5) => 5,
+            other => Err(anyhow!("unsupported unit index version: {:?}", other))?,
+        },
+    };
+    let section_count = read_u32(4)? as usize;
+    let unit_count = read_u32(8)? as usize;
+    let slot_count = read_u32(12)? as usize;
+
+    let hash_table = 16;
+    let index_table = hash_table + slot_count * 8;
+    let section_ids = index_table + slot_count * 4;
+    let offsets_table = section_ids + section_count * 4;
+    let sizes_table = offsets_table + unit_count * section_count * 4;
+
+    let mut columns = Vec::new();
+    for column in 0..section_count {
+        let section = match (version, read_u32(section_ids + column * 4)?) {
+            (_, 1) => SectionId::DebugInfo,
+            (2, 2) => SectionId::DebugTypes,
+            (_, 3) => SectionId::DebugAbbrev,
+            (_, 4) => SectionId::DebugLine,
+            (2, 5) => SectionId::DebugLoc,
+            (5, 5) => SectionId::DebugLocLists,
+            (_, 6) => SectionId::DebugStrOffsets,
+            (2, 8) | (5, 7) => SectionId::DebugMacro,
+            (5, 8) => SectionId::DebugRngLists,
+            _ => continue,
+        };
+        columns.push((column, section));
+    }
+
+    // every used slot of the hash table names a row of the offset and size
+    // tables
+    let mut rows = Vec::new();
+    for slot in 0..slot_count {
+        let row = match read_u32(index_table + slot * 4)? as usize {
+            0 => continue,
+            row if row <= unit_count => row - 1,
+            row => Err(anyhow!("unit index row {} is out of range", row))?,
+        };
+        let dwo_id = DwoId(read_u64(hash_table + slot * 8)?);
+
+        let mut contributions = Vec::new();
+        for (column, section) in &columns {
+            let cell = (row * section_count + column) * 4;
+            let offset = read_u32(offsets_table + cell)? as usize;
+            let size = read_u32(sizes_table + cell)? as usize;
+            contributions.push((*section, offset..offset + size));
+        }
+        rows.push((dwo_id, contributions));
+    }
+    Ok(rows)
+}
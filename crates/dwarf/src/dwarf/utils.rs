@@ -1,7 +1,8 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use gimli;
-use regex::{Captures, Regex};
+use std::io::Read;
 use wasm_bindgen::prelude::*;
+use wasmparser::{Parser, Payload};
 
 #[wasm_bindgen]
 extern "C" {
@@ -11,6 +12,7 @@ extern "C" {
     pub fn error(s: &str);
 }
 
+#[cfg(target_arch = "wasm32")]
 #[macro_export]
 macro_rules! console_log {
     // Note that this is using the `log` function imported above during
@@ -18,6 +20,16 @@ macro_rules! console_log {
     ($($t:tt)*) => (error(&format_args!($($t)*).to_string()))
 }
 
+/// Native builds such as unit tests can't call into JS, so they log to stderr
+#[cfg(not(target_arch = "wasm32"))]
+#[macro_export]
+macro_rules! console_log {
+    ($($t:tt)*) => ({
+        let _ = error;
+        eprintln!($($t)*)
+    })
+}
+
 pub(crate) fn clone_string_attribute<R: gimli::Reader>(
     dwarf: &gimli::Dwarf<R>,
     unit: &gimli::Unit<R, R::Offset>,
@@ -30,37 +42,278 @@ pub(crate) fn clone_string_attribute<R: gimli::Reader>(
         .to_string())
 }
 
-pub(crate) fn convert_from_windows_stype_path(path: &String) -> String {
-    let backslash_escaped = path.replace('\\', "/");
-    let regex = Regex::new("^([A-Za-z]):/");
-    regex
-        .unwrap()
-        .replace_all(&backslash_escaped, |captured: &Captures| {
-            format!("{}:/", captured[1].to_lowercase())
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compressed sections may not inflate past this, so that a small section
+/// can't claim gigabytes of memory
+const MAX_DECOMPRESSED_SECTION_SIZE: usize = 256 << 20;
+
+/// Inflate the contents of a `.zdebug_*` section, which holds either the GNU
+/// `ZLIB` header followed by a zlib stream or a bare zstd frame
+pub(crate) fn decompress_section(data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() >= 12 && data.starts_with(b"ZLIB") {
+        let mut size = [0; 8];
+        size.copy_from_slice(&data[4..12]);
+        let size = u64::from_be_bytes(size);
+        if size > MAX_DECOMPRESSED_SECTION_SIZE as u64 {
+            Err(anyhow!(
+                "zlib section declares {} bytes, more than the limit of {}",
+                size,
+                MAX_DECOMPRESSED_SECTION_SIZE
+            ))?
+        }
+        // the output buffer grows by doubling, so leave it room to overshoot
+        let size = size as usize;
+        let decompressed =
+            miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(&data[12..], 2 * size + 2)
+                .map_err(|e| anyhow!("failed to inflate zlib section: {:?}", e))?;
+        if decompressed.len() != size {
+            Err(anyhow!(
+                "zlib section inflated to {} bytes, expected {}",
+                decompressed.len(),
+                size
+            ))?
+        }
+        Ok(decompressed)
+    } else if data.starts_with(&ZSTD_MAGIC) {
+        let mut source = data;
+        let decoder = ruzstd::streaming_decoder::StreamingDecoder::new(&mut source)
+            .map_err(|e| anyhow!("failed to decode zstd section: {:?}", e))?;
+        let mut decompressed = Vec::new();
+        decoder
+            .take(MAX_DECOMPRESSED_SECTION_SIZE as u64 + 1)
+            .read_to_end(&mut decompressed)?;
+        if decompressed.len() > MAX_DECOMPRESSED_SECTION_SIZE {
+            Err(anyhow!(
+                "zstd section inflates past the limit of {} bytes",
+                MAX_DECOMPRESSED_SECTION_SIZE
+            ))?
+        }
+        Ok(decompressed)
+    } else {
+        Err(anyhow!("unknown compression format"))
+    }
+}
+
+/// Payloads of one module of a binary
+///
+/// The outermost module is 0, and modules nested in module sections are
+/// numbered in the order they appear. Payloads of other modules are skipped.
+pub(crate) fn module_payloads<'a>(
+    wasm_binary: &'a [u8],
+    module_index: usize,
+) -> impl Iterator<Item = wasmparser::Result<Payload<'a>>> + 'a {
+    let mut modules = Vec::new();
+    let mut next_index = 0;
+
+    Parser::new(0)
+        .parse_all(wasm_binary)
+        .filter(move |payload| match payload {
+            Ok(Payload::Version { .. }) => {
+                modules.push(next_index);
+                next_index += 1;
+                modules.last() == Some(&module_index)
+            }
+            Ok(Payload::End) => modules.pop() == Some(module_index),
+            Ok(_) => modules.last() == Some(&module_index),
+            Err(_) => true,
         })
-        .into_owned()
+}
+
+pub(crate) fn convert_from_windows_stype_path(path: &String) -> String {
+    let mut path = path.replace('\\', "/");
+    if has_drive(&path) {
+        path[..1].make_ascii_lowercase();
+    }
+    path
+}
+
+/// Whether a path starts with a Windows drive such as `c:/`
+fn has_drive(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'/'
 }
 
 pub(crate) fn is_absolute_path(path: &str) -> bool {
-    let regex = Regex::new("^([A-Za-z]):/").unwrap();
-    path.starts_with('/') | regex.is_match(path)
+    path.starts_with('/') || has_drive(path)
 }
 
+/// Collapse `.` and `..` components and repeated separators of a `/` separated path
+///
+/// `..` can't climb above the root of an absolute path, but is kept at the
+/// start of a relative one.
 pub(crate) fn normalize_path(path: &String) -> String {
-    let splited = path.split('/');
-    let mut stack = Vec::new();
+    let absolute = path.starts_with('/');
+    let mut stack: Vec<&str> = Vec::new();
 
-    for component in splited {
+    for component in path.split('/') {
         match component {
-            ".." => {
-                stack.pop();
-            }
-            "." => {
+            "" | "." => {
                 // nothing to do
             }
+            ".." => match stack.last() {
+                None | Some(&"..") if !absolute => stack.push(".."),
+                // the drive of a Windows path is its root
+                Some(last) if stack.len() == 1 && last.ends_with(':') => {}
+                _ => {
+                    stack.pop();
+                }
+            },
             other => stack.push(other),
         }
     }
 
-    stack.join("/")
+    let path = stack.join("/");
+    if absolute {
+        format!("/{}", path)
+    } else {
+        path
+    }
+}
+
+/// Resolve a line table entry against its include directory and the unit's
+/// `DW_AT_comp_dir`
+///
+/// A part that is absolute replaces the parts before it, and empty parts are
+/// skipped. The result is normalized with `/` separators.
+pub(crate) fn resolve_path(comp_dir: &str, dir: &str, file: &str) -> String {
+    let mut path = String::new();
+    for part in [comp_dir, dir, file].iter() {
+        let part = convert_from_windows_stype_path(&part.to_string());
+        if part.is_empty() {
+            continue;
+        }
+        path = if path.is_empty() || is_absolute_path(&part) {
+            part
+        } else {
+            format!("{}/{}", path, part)
+        };
+    }
+    normalize_path(&path)
+}
+
+/// Replace the leading `from` components of `path` with `to`
+///
+/// Only whole components match, so `/src` is a prefix of `/src/main.c` but
+/// not of `/srcs/main.c`. An empty `from` is a prefix of every relative path.
+pub(crate) fn replace_path_prefix(path: &str, from: &str, to: &str) -> Option<String> {
+    let rest = path.strip_prefix(from)?;
+    if from.is_empty() {
+        if is_absolute_path(path) {
+            return None;
+        }
+    } else if !from.ends_with('/') && !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    if rest.is_empty() {
+        return Some(to.to_string());
+    }
+    if to.is_empty() {
+        return Some(rest.trim_start_matches('/').to_string());
+    }
+    Some(format!(
+        "{}/{}",
+        to.trim_end_matches('/'),
+        rest.trim_start_matches('/')
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zlib_section(data: &[u8], size: u64) -> Vec<u8> {
+        let mut section = b"ZLIB".to_vec();
+        section.extend_from_slice(&size.to_be_bytes());
+        section.extend(miniz_oxide::deflate::compress_to_vec_zlib(data, 6));
+        section
+    }
+
+    #[test]
+    fn zlib_sections() {
+        let data = b".debug_line\0".repeat(8);
+        let section = zlib_section(&data, data.len() as u64);
+        assert_eq!(decompress_section(&section).unwrap(), data);
+
+        // the size in the header has to match what the stream inflates to
+        let short = zlib_section(&data, data.len() as u64 - 1);
+        assert!(decompress_section(&short).is_err());
+        let long = zlib_section(&data, data.len() as u64 + 1);
+        assert!(decompress_section(&long).is_err());
+
+        // and is checked before inflating anything
+        let huge = zlib_section(&data, 1 << 40);
+        let message = decompress_section(&huge).unwrap_err().to_string();
+        assert!(message.contains("limit"), "{}", message);
+    }
+
+    #[test]
+    fn zstd_sections() {
+        // `.debug_line\0` eight times, compressed by `zstd -19`
+        let frame = [
+            0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x68, 0xa5, 0x00, 0x00, 0x68, 0x2e, 0x64, 0x65, 0x62,
+            0x75, 0x67, 0x5f, 0x6c, 0x69, 0x6e, 0x65, 0x00, 0x2e, 0x01, 0x00, 0xf0, 0xa9, 0x7c,
+            0x01, 0xf0, 0xbf, 0x37, 0xc1,
+        ];
+        assert_eq!(
+            decompress_section(&frame).unwrap(),
+            b".debug_line\0".repeat(8)
+        );
+        assert!(decompress_section(&frame[..20]).is_err());
+        assert!(decompress_section(b"\x78\x9c").is_err());
+    }
+
+    fn normalize(path: &str) -> String {
+        normalize_path(&path.to_string())
+    }
+
+    #[test]
+    fn normalize_components() {
+        assert_eq!(normalize("/src/./lib/../main.c"), "/src/main.c");
+        assert_eq!(normalize("/src//main.c"), "/src/main.c");
+        assert_eq!(normalize("/../main.c"), "/main.c");
+        assert_eq!(normalize("../../src/../main.c"), "../../main.c");
+        assert_eq!(normalize("src/../../main.c"), "../main.c");
+        assert_eq!(normalize("c:/../src/main.c"), "c:/src/main.c");
+        assert_eq!(normalize("/"), "/");
+    }
+
+    #[test]
+    fn resolve_entries() {
+        assert_eq!(resolve_path("/build", "/src", "main.c"), "/src/main.c");
+        assert_eq!(resolve_path("/build", "../src", "main.c"), "/src/main.c");
+        assert_eq!(resolve_path("/build", "", "./main.c"), "/build/main.c");
+        assert_eq!(resolve_path("/build", "/src", "/usr/a.h"), "/usr/a.h");
+        assert_eq!(resolve_path("", "src", "main.c"), "src/main.c");
+        assert_eq!(
+            resolve_path("C:\\build", "include", "a.h"),
+            "c:/build/include/a.h"
+        );
+    }
+
+    #[test]
+    fn replace_prefixes() {
+        assert_eq!(
+            replace_path_prefix("/src/main.c", "/src", "/home/me/app"),
+            Some("/home/me/app/main.c".to_string())
+        );
+        assert_eq!(replace_path_prefix("/srcs/main.c", "/src", "/app"), None);
+        assert_eq!(
+            replace_path_prefix("/src/main.c", "/", "/app"),
+            Some("/app/src/main.c".to_string())
+        );
+        assert_eq!(
+            replace_path_prefix("/src", "/src", "/app"),
+            Some("/app".to_string())
+        );
+        assert_eq!(
+            replace_path_prefix("src/main.c", "", "/app"),
+            Some("/app/src/main.c".to_string())
+        );
+        assert_eq!(replace_path_prefix("/src/main.c", "", "/app"), None);
+        assert_eq!(
+            replace_path_prefix("/app/src/main.c", "/app", ""),
+            Some("src/main.c".to_string())
+        );
+    }
 }
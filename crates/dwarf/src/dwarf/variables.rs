@@ -1,47 +1,110 @@
 use anyhow::{anyhow, Result};
 use gimli::{
-    AttributeValue, DebuggingInformationEntry, Expression, Reader, Unit, UnitOffset,
-    UnitSectionOffset,
+    AttributeValue, DebuggingInformationEntry, Endianity, Expression, Reader, ReaderOffset,
+    RunTimeEndian, Unit, UnitOffset, UnitSectionOffset,
 };
+use num_bigint::BigInt;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
 
+use super::error::BridgeError;
+use super::format::{is_character, MAX_STRING_LENGTH, UNKNOWN_LENGTH_ELEMENTS};
+use super::subroutine::{read_wasm_location_op, IndexedVariable, WasmLoc};
 use super::utils::{clone_string_attribute, error};
-use super::wasm_bindings::WasmValueVector;
+use super::wasm_bindings::{ArtificialVariables, RawLocation, Value, WasmValueVector};
 use super::{
-    unit_type_name, DwarfDebugData, DwarfReader, DwarfReaderOffset, MemorySlice,
-    VariableEvaluationResult, VariableInfo,
+    dwarf_endian, entry_decl_file, entry_decl_line, entry_is_artificial, entry_linkage_name,
+    entry_name, subrange_count, unit_language, unit_type_byte_size, with_origins, wrap_address,
+    BitField, DwarfDebugData, DwarfReader, DwarfReaderOffset, MemorySlice, ParsedUnit,
+    SourceLanguage, StringLayout, UnitTypes, VariableInfo, Variant, VariantPart,
 };
 use crate::console_log;
 
+#[derive(Clone)]
 pub struct VariableName {
     pub name: String,
     pub display_name: String,
+    pub linkage_name: Option<String>,
     pub type_name: String,
     pub group_id: i32,
     pub child_group_id: Option<i32>,
+    pub layout: Option<MemberLayout>,
+    /// Offset of the variable's DIE in `.debug_info`, absent for members
+    pub die_offset: Option<usize>,
+    /// Offset of the header of the unit declaring the variable, absent for
+    /// members
+    pub unit_offset: Option<usize>,
+    pub decl_file: Option<String>,
+    pub decl_line: Option<u64>,
+    /// Whether the variable is a `DW_TAG_formal_parameter` of the function
+    pub is_parameter: bool,
+    /// Whether the compiler made the variable up, marking it `DW_AT_artificial`
+    pub is_artificial: bool,
+    /// Code ranges where the location list of the variable gives it a
+    /// location, absent when it has one throughout its scope
+    pub available_ranges: Option<Vec<Range<u64>>>,
+}
+
+/// Placement of a structure member inside its parent
+#[derive(Clone, Copy)]
+pub struct MemberLayout {
+    pub byte_offset: usize,
+    /// Bit extents for bit-field members, counted from the start of the parent
+    pub bit_offset: Option<usize>,
+    pub bit_size: Option<usize>,
 }
 
 pub struct SymbolVariable {
     pub name: Option<String>,
     pub display_name: Option<String>,
+    pub linkage_name: Option<String>,
     pub contents: Vec<VariableExpression>,
     pub ty_offset: TypeDescripter,
     pub group_id: i32,
     pub child_group_id: Option<i32>,
+    pub die_offset: Option<usize>,
+    pub decl_file: Option<String>,
+    pub decl_line: Option<u64>,
+    pub is_parameter: bool,
+    pub is_artificial: bool,
+    /// Placement of a bit-field member, whose value is cut from the bytes it spans
+    pub bit_field: Option<MemberLayout>,
+}
+
+/// How a variable to evaluate is identified
+pub enum VariableQuery<'a> {
+    /// Display name, e.g. `p.x` or `p->x`
+    Name(&'a str),
+    /// Offset of the DIE in `.debug_info`, as listed by `variable_name_list`
+    DieOffset(usize),
 }
 
 #[derive(Clone)]
 pub enum VariableExpression {
     Location(gimli::AttributeValue<DwarfReader>),
     ConstValue(Vec<u8>),
-    Pointer,
-    Unknown { debug_info: String },
+    /// Dereference of a pointer of the given byte size
+    Pointer(usize),
+    /// `DW_AT_data_member_location` expression placing a member from the
+    /// address of the aggregate holding it
+    MemberLocation(Expression<DwarfReader>),
+    Unknown {
+        debug_info: String,
+    },
 }
 
 #[derive(Clone)]
 pub enum VariableLocation {
     Address(u64),
     Offset(i64),
-    Pointer,
+    Pointer(usize),
+    /// Element count of an array read from memory before the array itself,
+    /// plus one when it is the upper bound
+    Count {
+        address: u64,
+        byte_size: usize,
+        upper_bound: bool,
+    },
 }
 
 #[derive(Clone)]
@@ -52,8 +115,58 @@ pub enum TypeDescripter {
 
 pub enum FrameBase {
     WasmFrameBase(u64),
-    WasmDataBase(u64),
     RBP(u64),
+    Unavailable,
+}
+
+/// Reads `size` bytes of linear memory at an address, `None` if they can't be read
+pub type MemoryReader<'a> = &'a dyn Fn(u64, usize) -> Option<Vec<u8>>;
+
+/// Reads a DWARF register by number, `None` if the caller doesn't know it
+pub type RegisterReader<'a> = &'a dyn Fn(u16) -> Option<u64>;
+
+/// Wasm locals, globals and operand stack of the inspected frame
+#[derive(Clone, Copy)]
+pub struct WasmFrame<'a> {
+    pub locals: &'a WasmValueVector,
+    pub globals: &'a WasmValueVector,
+    pub stacks: &'a WasmValueVector,
+    /// Memory offset `DW_OP_addr` operands are relative to, the
+    /// `__memory_base` of a relocatable module and 0 for one linked at fixed
+    /// addresses
+    pub data_base: u64,
+    /// Index of the `__tls_base` global, for `DW_OP_form_tls_address`
+    pub tls_base: Option<u32>,
+    /// Linear memory for `DW_OP_deref` and the pointers of member paths such
+    /// as `p->x`, if the caller can read it while evaluating
+    pub memory: Option<MemoryReader<'a>>,
+    /// Registers for `DW_OP_breg*` and `DW_OP_regval_type`, which wasm frames
+    /// don't have but debug info from other targets along the toolchain can
+    /// still use
+    pub registers: Option<RegisterReader<'a>>,
+}
+
+impl<'a> WasmFrame<'a> {
+    /// Value of a local, a global or an operand stack slot, counted from the
+    /// bottom of the frame's stack
+    ///
+    /// A slot the runtime didn't report, such as the stack of a runtime that
+    /// doesn't expose it, is unavailable.
+    pub fn value_at(&self, loc: &WasmLoc) -> Result<Value> {
+        let (values, index, kind) = match *loc {
+            WasmLoc::Local(idx) => (self.locals, idx, "local"),
+            WasmLoc::Global(idx) => (self.globals, idx, "global"),
+            WasmLoc::Stack(idx) => (self.stacks, idx, "stack slot"),
+        };
+
+        match values.data.get(index as usize) {
+            Some(x) => Ok(x.value),
+            None => Err(BridgeError::Unavailable(format!(
+                "{} {} was not supplied by the runtime",
+                kind, index
+            )))?,
+        }
+    }
 }
 
 /**
@@ -65,11 +178,50 @@ pub fn variables_in_unit_entry(
     entry_offset: Option<UnitOffset<DwarfReaderOffset>>,
     code_offset: u64,
     root_group_id: i32,
+) -> Result<Vec<SymbolVariable>> {
+    collect_variables(dwarf, unit, entry_offset, code_offset, root_group_id)
+}
+
+/// Variables of a subroutine from its indexed DIEs, listed as
+/// `variables_in_unit_entry` would list them
+pub fn indexed_variables(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader>,
+    entries: &[&IndexedVariable],
+    code_offset: u64,
+    root_group_id: i32,
+) -> Result<Vec<SymbolVariable>> {
+    let mut variables = vec![];
+    // child groups of the root are numbered from a block of ids reserved for it
+    let mut group_id = (root_group_id - 1000 + 1) * 10000;
+    for entry in entries {
+        let mut tree = unit.entries_tree(Some(entry.entry_offset))?;
+        push_variable(
+            tree.root()?,
+            dwarf,
+            unit,
+            entry.scope_offset,
+            code_offset,
+            &mut variables,
+            root_group_id,
+            &mut group_id,
+        )?;
+    }
+    Ok(variables)
+}
+
+fn collect_variables(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader>,
+    entry_offset: Option<UnitOffset<DwarfReaderOffset>>,
+    code_offset: u64,
+    root_group_id: i32,
 ) -> Result<Vec<SymbolVariable>> {
     let mut tree = unit.entries_tree(entry_offset)?;
     let root = tree.root()?;
     let mut variables = vec![];
-    let mut root_group_id = root_group_id;
+    // child groups of the root are numbered from a block of ids reserved for it
+    let mut group_id = (root_group_id - 1000 + 1) * 10000;
     variables_in_unit_entry_recursive(
         root,
         dwarf,
@@ -77,11 +229,12 @@ pub fn variables_in_unit_entry(
         code_offset,
         &mut variables,
         root_group_id,
-        &mut root_group_id,
+        &mut group_id,
     )?;
     Ok(variables)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn variables_in_unit_entry_recursive(
     node: gimli::EntriesTreeNode<DwarfReader>,
     dwarf: &gimli::Dwarf<DwarfReader>,
@@ -91,58 +244,49 @@ fn variables_in_unit_entry_recursive(
     root_group_id: i32,
     group_id: &mut i32,
 ) -> Result<()> {
+    let scope_offset = node.entry().offset();
     let mut children = node.children();
 
-    if *group_id < 10000 {
-        *group_id = (*group_id - 1000 + 1) * 10000;
-    } else {
-        *group_id += 1;
-    }
-
     while let Some(child) = children.next()? {
         match child.entry().tag() {
             gimli::DW_TAG_variable | gimli::DW_TAG_formal_parameter => {
-                let mut var = transform_variable(dwarf, unit, child.entry(), root_group_id)?;
-                structure_variable_recursive(child, dwarf, unit, &mut var, variables, group_id)?;
-                variables.push(var);
+                push_variable(
+                    child,
+                    dwarf,
+                    unit,
+                    scope_offset,
+                    code_offset,
+                    variables,
+                    root_group_id,
+                    group_id,
+                )?;
             }
-            gimli::DW_TAG_lexical_block => {
-                let low_pc_attr = child.entry().attr_value(gimli::DW_AT_low_pc)?;
-                let high_pc_attr = child.entry().attr_value(gimli::DW_AT_high_pc)?;
-
-                if let Some(AttributeValue::Addr(low_pc)) = low_pc_attr {
-                    let high_pc = match high_pc_attr {
-                        Some(AttributeValue::Udata(size)) => low_pc + size,
-                        Some(AttributeValue::Addr(high_pc)) => high_pc,
-                        Some(x) => unreachable!("high_pc can't be {:?}", x),
-                        None => continue,
-                    };
-
-                    let code_range = low_pc..high_pc;
-
-                    if code_range.contains(&code_offset) {
-                        variables_in_unit_entry_recursive(
-                            child,
-                            dwarf,
-                            unit,
-                            code_offset,
-                            variables,
-                            root_group_id,
-                            group_id,
-                        )?;
-                    }
+            // locals of a block are only in scope while the pc is inside it
+            gimli::DW_TAG_lexical_block | gimli::DW_TAG_inlined_subroutine => {
+                if entry_contains_pc(dwarf, unit, child.entry(), code_offset)? {
+                    variables_in_unit_entry_recursive(
+                        child,
+                        dwarf,
+                        unit,
+                        code_offset,
+                        variables,
+                        root_group_id,
+                        group_id,
+                    )?;
                 }
             }
             gimli::DW_TAG_namespace => {
                 let mut var = transform_namespace(dwarf, unit, child.entry(), root_group_id)?;
-                var.child_group_id = Some(*group_id);
+                let namespace_group_id = *group_id;
+                var.child_group_id = Some(namespace_group_id);
+                *group_id += 1;
                 variables_in_unit_entry_recursive(
                     child,
                     dwarf,
                     unit,
                     code_offset,
                     variables,
-                    *group_id,
+                    namespace_group_id,
                     group_id,
                 )?;
                 variables.push(var);
@@ -153,6 +297,92 @@ fn variables_in_unit_entry_recursive(
     Ok(())
 }
 
+/// Push a variable or parameter in scope at the pc, followed by its members
+#[allow(clippy::too_many_arguments)]
+fn push_variable(
+    node: gimli::EntriesTreeNode<DwarfReader>,
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader>,
+    scope_offset: UnitOffset<DwarfReaderOffset>,
+    code_offset: u64,
+    variables: &mut Vec<SymbolVariable>,
+    root_group_id: i32,
+    group_id: &mut i32,
+) -> Result<()> {
+    if !in_start_scope(dwarf, unit, scope_offset, node.entry(), code_offset)? {
+        return Ok(());
+    }
+    let mut var = transform_variable(dwarf, unit, node.entry(), root_group_id)?;
+    structure_variable_recursive(
+        node,
+        dwarf,
+        unit,
+        &mut var,
+        variables,
+        group_id,
+        &mut Vec::new(),
+    )?;
+    variables.push(var);
+    Ok(())
+}
+
+/// Whether the pc falls in the `DW_AT_low_pc`/`DW_AT_high_pc` or `DW_AT_ranges` of an entry
+fn entry_contains_pc(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader>,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+    code_offset: u64,
+) -> Result<bool> {
+    let mut ranges = dwarf.die_ranges(unit, entry)?;
+    while let Some(range) = ranges.next()? {
+        if (range.begin..range.end).contains(&code_offset) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the pc is past the `DW_AT_start_scope` of a variable, if it has one
+///
+/// A constant is an offset from the start of the enclosing scope, a range list
+/// lists the addresses where the variable is in scope.
+fn in_start_scope(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader>,
+    scope_offset: UnitOffset<DwarfReaderOffset>,
+    entry: &DebuggingInformationEntry<DwarfReader>,
+    code_offset: u64,
+) -> Result<bool> {
+    let start_scope = match entry.attr_value(gimli::DW_AT_start_scope)? {
+        Some(attr) => attr,
+        None => return Ok(true),
+    };
+
+    if let Some(mut ranges) = dwarf.attr_ranges(unit, start_scope.clone())? {
+        while let Some(range) = ranges.next()? {
+            if (range.begin..range.end).contains(&code_offset) {
+                return Ok(true);
+            }
+        }
+        return Ok(false);
+    }
+
+    let offset = match start_scope.udata_value() {
+        Some(offset) => offset,
+        None => return Ok(true),
+    };
+    let mut scope_start = None;
+    let scope = unit.entry(scope_offset)?;
+    let mut ranges = dwarf.die_ranges(unit, &scope)?;
+    while let Some(range) = ranges.next()? {
+        scope_start = Some(scope_start.map_or(range.begin, |start: u64| start.min(range.begin)));
+    }
+    match scope_start {
+        Some(start) => Ok(code_offset >= start + offset),
+        None => Ok(true),
+    }
+}
+
 fn structure_variable_recursive(
     node: gimli::EntriesTreeNode<DwarfReader>,
     dwarf: &gimli::Dwarf<DwarfReader>,
@@ -160,55 +390,47 @@ fn structure_variable_recursive(
     parent_variable: &mut SymbolVariable,
     variables: &mut Vec<SymbolVariable>,
     group_id: &mut i32,
+    expanding_types: &mut Vec<UnitOffset<DwarfReaderOffset>>,
 ) -> Result<()> {
     match node.entry().tag() {
         gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
-            let mut children = node.children();
+            // self-referential types (linked lists, trees) are expanded only once per path
+            let type_offset = node.entry().offset();
+            if expanding_types.contains(&type_offset) {
+                return Ok(());
+            }
+            expanding_types.push(type_offset);
+
             let current_group_id = *group_id;
             parent_variable.child_group_id = Some(current_group_id);
             *group_id += 1;
 
-            while let Some(child) = children.next()? {
-                match child.entry().tag() {
-                    gimli::DW_TAG_member => {
-                        let mut var =
-                            transform_variable(dwarf, unit, child.entry(), current_group_id)?;
-
-                        let mut contents = parent_variable.contents.clone();
-                        contents.append(&mut var.contents);
-
-                        let mut var = SymbolVariable {
-                            display_name: Some(format!(
-                                "{}.{}",
-                                parent_variable
-                                    .name
-                                    .as_ref()
-                                    .unwrap_or(&"<unnamed>".to_string()),
-                                var.name.as_ref().unwrap_or(&"<unnamed>".to_string())
-                            )),
-                            name: Some(var.name.unwrap_or("<unnamed>".to_string())),
-                            contents,
-                            ty_offset: var.ty_offset,
-                            group_id: var.group_id,
-                            child_group_id: var.child_group_id,
-                        };
-
-                        if let TypeDescripter::TypeOffset(offset) = var.ty_offset {
-                            let mut tree = unit.entries_tree(Some(UnitOffset(offset)))?;
-                            let root = tree.root()?;
-                            structure_variable_recursive(
-                                root, dwarf, unit, &mut var, variables, group_id,
-                            )?;
-                        }
-
-                        variables.push(var);
-                    }
-                    _ => continue,
-                }
-            }
+            let prefix = parent_variable
+                .display_name
+                .clone()
+                .unwrap_or_else(|| "<unnamed>".to_string());
+            let contents = parent_variable.contents.clone();
+            structure_members_recursive(
+                node,
+                dwarf,
+                unit,
+                &prefix,
+                &contents,
+                current_group_id,
+                variables,
+                group_id,
+                expanding_types,
+            )?;
+            expanding_types.pop();
         }
         gimli::DW_TAG_pointer_type | gimli::DW_TAG_reference_type => {
-            parent_variable.contents.push(VariableExpression::Pointer);
+            // members are reached through the pointer, the pointer itself stays an address
+            let is_pointer = node.entry().tag() == gimli::DW_TAG_pointer_type;
+            let pointer_size = unit_type_byte_size(unit, node.entry().offset().0)?
+                .unwrap_or(unit.header.address_size() as u64);
+            parent_variable
+                .contents
+                .push(VariableExpression::Pointer(pointer_size as usize));
 
             if let Some(AttributeValue::UnitRef(ref offset)) =
                 node.entry().attr_value(gimli::DW_AT_type)?
@@ -223,9 +445,13 @@ fn structure_variable_recursive(
                         parent_variable,
                         variables,
                         group_id,
+                        expanding_types,
                     )?;
                 }
             }
+            if is_pointer {
+                parent_variable.contents.pop();
+            }
         }
         gimli::DW_TAG_const_type => {
             if let Some(AttributeValue::UnitRef(ref offset)) =
@@ -241,6 +467,7 @@ fn structure_variable_recursive(
                         parent_variable,
                         variables,
                         group_id,
+                        expanding_types,
                     )?;
                 }
             }
@@ -259,6 +486,7 @@ fn structure_variable_recursive(
                         parent_variable,
                         variables,
                         group_id,
+                        expanding_types,
                     )?;
                 }
             }
@@ -268,6 +496,112 @@ fn structure_variable_recursive(
     Ok(())
 }
 
+/// Push the members of a structure as `<prefix>.<member>`, with the members of
+/// C11 anonymous structures and unions listed as if they were its own
+#[allow(clippy::too_many_arguments)]
+fn structure_members_recursive(
+    node: gimli::EntriesTreeNode<DwarfReader>,
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader>,
+    prefix: &str,
+    parent_contents: &[VariableExpression],
+    member_group_id: i32,
+    variables: &mut Vec<SymbolVariable>,
+    group_id: &mut i32,
+    expanding_types: &mut Vec<UnitOffset<DwarfReaderOffset>>,
+) -> Result<()> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() != gimli::DW_TAG_member {
+            continue;
+        }
+        let mut var = transform_variable(dwarf, unit, child.entry(), member_group_id)?;
+
+        let mut contents = parent_contents.to_vec();
+        contents.append(&mut var.contents);
+
+        if let Some(anonymous) = anonymous_aggregate(unit, child.entry())? {
+            // a malformed aggregate holding itself is left out
+            if expanding_types.contains(&anonymous) {
+                continue;
+            }
+            expanding_types.push(anonymous);
+            let mut tree = unit.entries_tree(Some(anonymous))?;
+            structure_members_recursive(
+                tree.root()?,
+                dwarf,
+                unit,
+                prefix,
+                &contents,
+                member_group_id,
+                variables,
+                group_id,
+                expanding_types,
+            )?;
+            expanding_types.pop();
+            continue;
+        }
+
+        let mut var = SymbolVariable {
+            display_name: Some(format!(
+                "{}.{}",
+                prefix,
+                var.name.as_ref().unwrap_or(&"<unnamed>".to_string())
+            )),
+            name: Some(var.name.unwrap_or("<unnamed>".to_string())),
+            linkage_name: None,
+            contents,
+            ty_offset: var.ty_offset,
+            group_id: var.group_id,
+            child_group_id: var.child_group_id,
+            die_offset: None,
+            decl_file: var.decl_file,
+            decl_line: var.decl_line,
+            is_parameter: false,
+            is_artificial: false,
+            bit_field: var.bit_field,
+        };
+
+        if let TypeDescripter::TypeOffset(offset) = var.ty_offset {
+            let mut tree = unit.entries_tree(Some(UnitOffset(offset)))?;
+            let root = tree.root()?;
+            structure_variable_recursive(
+                root,
+                dwarf,
+                unit,
+                &mut var,
+                variables,
+                group_id,
+                expanding_types,
+            )?;
+        }
+
+        variables.push(var);
+    }
+    Ok(())
+}
+
+/// Type of an unnamed member holding a C11 anonymous structure or union,
+/// whose members belong to the parent
+fn anonymous_aggregate<R: gimli::Reader>(
+    unit: &Unit<R>,
+    member: &DebuggingInformationEntry<R>,
+) -> Result<Option<UnitOffset<R::Offset>>> {
+    if member.attr_value(gimli::DW_AT_name)?.is_some() {
+        return Ok(None);
+    }
+    let member_type = match member.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(offset)) => offset,
+        _ => return Ok(None),
+    };
+    match unit.entry(member_type)?.tag() {
+        gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
+            Ok(Some(member_type))
+        }
+        _ => Ok(None),
+    }
+}
+
 fn transform_variable(
     dwarf: &gimli::Dwarf<DwarfReader>,
     unit: &Unit<DwarfReader, DwarfReaderOffset>,
@@ -281,39 +615,80 @@ fn transform_variable(
         content = Some(VariableExpression::Location(location));
         has_explicit_location = true;
     } else if let Some(location) = entry.attr_value(gimli::DW_AT_data_member_location)? {
-        content = Some(VariableExpression::Location(location));
+        content = Some(
+            match (
+                constant_member_offset(unit.encoding(), &location)?,
+                location,
+            ) {
+                (Some(offset), _) => VariableExpression::Location(AttributeValue::Udata(offset)),
+                (None, AttributeValue::Exprloc(expr)) => VariableExpression::MemberLocation(expr),
+                (None, location) => VariableExpression::Location(location),
+            },
+        );
         has_explicit_location = true;
     }
     if let Some(constant) = entry.attr_value(gimli::DW_AT_const_value)? {
         if !has_explicit_location {
-            // TODO: support big endian
+            let endian = dwarf_endian(dwarf);
             let bytes = match constant {
                 AttributeValue::Block(block) => block.to_slice()?.to_vec(),
                 AttributeValue::Data1(b) => vec![b],
-                AttributeValue::Data2(b) => b.to_le_bytes().to_vec(),
-                AttributeValue::Data4(b) => b.to_le_bytes().to_vec(),
-                AttributeValue::Data8(b) => b.to_le_bytes().to_vec(),
-                AttributeValue::Sdata(b) => b.to_le_bytes().to_vec(),
-                AttributeValue::Udata(b) => b.to_le_bytes().to_vec(),
+                AttributeValue::Data2(b) => target_bytes(b.to_le_bytes().to_vec(), endian),
+                AttributeValue::Data4(b) => target_bytes(b.to_le_bytes().to_vec(), endian),
+                AttributeValue::Data8(b) => target_bytes(b.to_le_bytes().to_vec(), endian),
+                AttributeValue::Sdata(b) => target_bytes(b.to_le_bytes().to_vec(), endian),
+                AttributeValue::Udata(b) => target_bytes(b.to_le_bytes().to_vec(), endian),
                 AttributeValue::String(b) => b.to_slice()?.to_vec(),
-                _ => unimplemented!(),
+                // DWARF 5 producers store string constants as `DW_FORM_strx*`
+                attr @ AttributeValue::DebugStrRef(_)
+                | attr @ AttributeValue::DebugStrOffsetsIndex(_)
+                | attr @ AttributeValue::DebugLineStrRef(_) => {
+                    dwarf.attr_string(unit, attr)?.to_slice()?.to_vec()
+                }
+                form => Err(BridgeError::Unsupported(format!(
+                    "constant value of form {:?}",
+                    form
+                )))?,
             };
             content = Some(VariableExpression::ConstValue(bytes));
         }
     }
-    let name = match entry.attr_value(gimli::DW_AT_name)? {
-        Some(name_attr) => Some(clone_string_attribute(dwarf, unit, name_attr)?),
-        None => None,
-    };
+    // bit-fields are read from the first byte they occupy, which
+    // DW_AT_data_bit_offset gives without a DW_AT_data_member_location
+    let mut bit_field = None;
+    if entry.tag() == gimli::DW_TAG_member && entry.attr_value(gimli::DW_AT_bit_size)?.is_some() {
+        let layout = member_layout(dwarf, unit, entry)?;
+        if content.is_none() {
+            content = Some(VariableExpression::Location(AttributeValue::Udata(
+                layout.byte_offset as u64,
+            )));
+        }
+        bit_field = Some(layout);
+    }
+    let name = entry_name(dwarf, unit, entry)?;
+    let linkage_name = entry_linkage_name(dwarf, unit, entry)?;
+    let decl_file = entry_decl_file(dwarf, unit, entry)?;
+    let decl_line = entry_decl_line(dwarf, unit, entry)?;
 
-    let ty = match entry.attr_value(gimli::DW_AT_type)? {
-        Some(AttributeValue::UnitRef(ref offset)) => TypeDescripter::TypeOffset(offset.0),
-        _ => TypeDescripter::Description(String::from("<unnamed>")),
+    // type offsets are only meaningful in the unit the variable is read from
+    let unit_offset = unit.header.offset();
+    let ty_offset = with_origins(dwarf, unit, entry, |_, origin_unit, origin| {
+        match origin.attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(offset)) if origin_unit.header.offset() == unit_offset => {
+                Ok(Some(offset.0))
+            }
+            _ => Ok(None),
+        }
+    })?;
+    let ty = match ty_offset {
+        Some(offset) => TypeDescripter::TypeOffset(offset),
+        None => TypeDescripter::Description(String::from("<unnamed>")),
     };
 
     Ok(SymbolVariable {
         name: name.clone(),
         display_name: name,
+        linkage_name,
         contents: match content {
             Some(x) => vec![x],
             None => vec![],
@@ -321,6 +696,12 @@ fn transform_variable(
         ty_offset: ty,
         group_id,
         child_group_id: None,
+        die_offset: Some(die_section_offset(unit, entry)),
+        decl_file,
+        decl_line,
+        is_parameter: entry.tag() == gimli::DW_TAG_formal_parameter,
+        is_artificial: entry_is_artificial(dwarf, unit, entry)?,
+        bit_field,
     })
 }
 
@@ -338,77 +719,260 @@ fn transform_namespace(
     Ok(SymbolVariable {
         name: name.clone(),
         display_name: name,
+        linkage_name: None,
         contents: vec![],
         ty_offset: TypeDescripter::Description(String::from("namespace")),
         group_id,
         child_group_id: None,
+        die_offset: None,
+        decl_file: None,
+        decl_line: None,
+        is_parameter: false,
+        is_artificial: false,
+        bit_field: None,
     })
 }
 
-pub fn evaluate_variable_from_string(
-    name: &String,
-    variables: &Vec<SymbolVariable>,
-    dwarf: &gimli::Dwarf<DwarfReader>,
+/// Offset of a unit's header in its section
+pub(crate) fn unit_section_offset(unit: &Unit<DwarfReader, DwarfReaderOffset>) -> usize {
+    match unit.header.offset() {
+        UnitSectionOffset::DebugInfoOffset(offset) => offset.0,
+        UnitSectionOffset::DebugTypesOffset(offset) => offset.0,
+    }
+}
+
+fn die_section_offset(
     unit: &Unit<DwarfReader, DwarfReaderOffset>,
-    frame_base: FrameBase,
-) -> Result<Option<VariableInfo>> {
-    let name = name.replace("->", ".");
-    let this_name = format!("this.{}", name);
+    entry: &DebuggingInformationEntry<DwarfReader>,
+) -> usize {
+    match entry.offset().to_unit_section_offset(unit) {
+        UnitSectionOffset::DebugInfoOffset(offset) => offset.0,
+        UnitSectionOffset::DebugTypesOffset(offset) => offset.0,
+    }
+}
 
-    let var = match variables
-        .iter()
-        .filter(|v| {
-            if let Some(ref vname) = v.display_name {
-                *vname == name || *vname == this_name
-            } else {
-                false
+/// Find the variable a query names among the variables in scope
+fn find_variable<'a>(
+    query: &VariableQuery,
+    variables: &'a [SymbolVariable],
+) -> Result<&'a SymbolVariable> {
+    let var = match query {
+        VariableQuery::Name(name) => {
+            let name = name.replace("->", ".");
+            let this_name = format!("this.{}", name);
+
+            match variables
+                .iter()
+                .filter(|v| {
+                    if let Some(ref vname) = v.display_name {
+                        *vname == name || *vname == this_name
+                    } else {
+                        false
+                    }
+                })
+                .next()
+            {
+                Some(v) => v,
+                None => {
+                    return Err(BridgeError::NotFound(format!(
+                        "'{}' is not valid variable name",
+                        name
+                    )))?;
+                }
+            }
+        }
+        VariableQuery::DieOffset(die_offset) => {
+            match variables.iter().find(|v| v.die_offset == Some(*die_offset)) {
+                Some(v) => v,
+                None => {
+                    return Err(BridgeError::NotFound(format!(
+                        "no variable at DIE offset {:#x} is in scope",
+                        die_offset
+                    )))?;
+                }
             }
-        })
-        .next()
-    {
-        Some(v) => v,
-        None => {
-            return Err(anyhow!("'{}' is not valid variable name", name));
         }
     };
+    Ok(var)
+}
+
+/// `DW_AT_location` expression of a variable that applies at the pc, for
+/// callers evaluating it themselves
+///
+/// Returns `None` for members and for variables without a location, such as
+/// constants or those optimized out at the pc.
+pub fn raw_variable_location(
+    query: &VariableQuery,
+    variables: &[SymbolVariable],
+    unit_data: &ParsedUnit,
+    code_offset: u64,
+) -> Result<Option<RawLocation>> {
+    let (dwarf, unit, _) = unit_data;
+    let var = find_variable(query, variables)?;
+    let location = match var.contents.first() {
+        Some(VariableExpression::Location(location)) if var.die_offset.is_some() => location,
+        _ => return Ok(None),
+    };
+    match location_expression(dwarf, unit, location, code_offset)? {
+        Some(expr) => Ok(Some(RawLocation {
+            expression: expr.0.to_slice()?.to_vec(),
+            is_location_list: !matches!(location, AttributeValue::Exprloc(_)),
+        })),
+        None => Ok(None),
+    }
+}
+
+pub fn evaluate_variable(
+    query: &VariableQuery,
+    variables: &Vec<SymbolVariable>,
+    unit_data: &ParsedUnit,
+    code_offset: u64,
+    frame_base: FrameBase,
+    frame: &WasmFrame,
+) -> Result<Option<VariableInfo>> {
+    let (dwarf, unit, types) = unit_data;
+    let var = find_variable(query, variables)?;
+    let optimized_out = || {
+        BridgeError::OptimizedOut(format!(
+            "'{}' is not available at {:#x}",
+            var.display_name.as_deref().unwrap_or("<unnamed>"),
+            code_offset
+        ))
+    };
+    // a variable with neither DW_AT_location nor DW_AT_const_value was optimized out
+    if var.contents.is_empty() && matches!(var.ty_offset, TypeDescripter::TypeOffset(_)) {
+        Err(optimized_out())?
+    }
+
     let mut calculated_address = Vec::new();
-    let mut constant_data = None;
+    let mut constant_data: Option<Vec<u8>> = None;
+    let mut unavailable_bits: Vec<Range<usize>> = Vec::new();
+    let mut from_pieces = false;
 
     for content in &var.contents {
         match content {
             VariableExpression::Location(location) => match location {
-                AttributeValue::Exprloc(expr) => {
-                    let piece =
-                        evaluate_variable_location(unit.encoding(), &frame_base, expr.clone())?;
+                // members of a value held outside memory are cut from its bytes
+                AttributeValue::Sdata(_) | AttributeValue::Udata(_)
+                    if constant_data.is_some() && calculated_address.is_empty() =>
+                {
+                    let offset = location.udata_value().unwrap_or(0) as usize;
+                    if let Some(ref mut data) = constant_data {
+                        data.drain(..offset.min(data.len()));
+                    }
+                    let skipped = offset * 8;
+                    unavailable_bits = unavailable_bits
+                        .into_iter()
+                        .filter(|bits| bits.end > skipped)
+                        .map(|bits| bits.start.saturating_sub(skipped)..bits.end - skipped)
+                        .collect();
+                }
+                AttributeValue::Sdata(b) => {
+                    calculated_address.push(VariableLocation::Offset(*b));
+                }
+                AttributeValue::Udata(b) => {
+                    calculated_address.push(VariableLocation::Offset(*b as i64));
+                }
+                _ => {
+                    let expr = match location_expression(dwarf, unit, location, code_offset)? {
+                        Some(expr) => expr,
+                        None => Err(optimized_out())?,
+                    };
+                    let piece = evaluate_variable_location(
+                        unit.encoding(),
+                        Some((dwarf, unit)),
+                        &frame_base,
+                        frame,
+                        expr,
+                    )?;
+                    if piece.len() > 1 || piece.iter().any(|p| p.size_in_bits.is_some()) {
+                        let (bytes, missing) = assemble_pieces(&piece)?;
+                        constant_data = Some(bytes);
+                        unavailable_bits = missing;
+                        from_pieces = true;
+                        continue;
+                    }
                     let piece = match piece.get(0) {
                         Some(p) => p,
-                        None => {
-                            println!("failed to get piece of variable");
-                            return Ok(None);
-                        }
+                        None => Err(optimized_out())?,
                     };
 
                     match piece.location {
+                        // an empty location expression
+                        gimli::Location::Empty => Err(optimized_out())?,
                         gimli::Location::Address { address } => {
                             calculated_address.push(VariableLocation::Address(address));
                         }
-                        _ => unimplemented!(),
+                        gimli::Location::Value { value } => {
+                            constant_data = Some(value_to_bytes(value, dwarf_endian(dwarf)));
+                        }
+                        ref x => Err(BridgeError::Unsupported(format!(
+                            "unsupported variable location: {:?}",
+                            x
+                        )))?,
                     };
                 }
-                AttributeValue::LocationListsRef(_listsref) => unimplemented!("listsref"),
-                AttributeValue::Sdata(b) => {
-                    calculated_address.push(VariableLocation::Offset(*b));
-                }
-                AttributeValue::Udata(b) => {
-                    calculated_address.push(VariableLocation::Offset(*b as i64));
-                }
-                _ => panic!(),
             },
             VariableExpression::ConstValue(ref _bytes) => {
                 constant_data = Some(_bytes.clone());
             }
-            VariableExpression::Pointer => {
-                calculated_address.push(VariableLocation::Pointer);
+            VariableExpression::Pointer(pointer_size) => {
+                let base = match (frame.memory, &constant_data) {
+                    (Some(_), None) => {
+                        object_address(&calculated_address, unit.header.address_size())
+                    }
+                    _ => None,
+                };
+                // with memory at hand the pointer is followed right away, which
+                // also lets member locations start from the pointee
+                let base = match base {
+                    Some(base) => base,
+                    None => {
+                        calculated_address.push(VariableLocation::Pointer(*pointer_size));
+                        continue;
+                    }
+                };
+                let address = read_address(frame, base, *pointer_size as u8)?;
+                if address == 0 {
+                    Err(BridgeError::Unavailable(format!(
+                        "null dereference while locating '{}'",
+                        var.display_name.as_deref().unwrap_or("<unnamed>")
+                    )))?
+                }
+                calculated_address = vec![VariableLocation::Address(address)];
+            }
+            VariableExpression::MemberLocation(expr) => {
+                let base = match constant_data {
+                    None => object_address(&calculated_address, unit.header.address_size()),
+                    Some(_) => None,
+                };
+                let base = match base {
+                    Some(base) => base,
+                    None => {
+                        console_log!(
+                            "'{}' is placed from an address that isn't known yet, assuming offset 0",
+                            var.display_name.as_deref().unwrap_or("<unnamed>")
+                        );
+                        continue;
+                    }
+                };
+                // the expression starts with the aggregate's address on the stack
+                let piece = evaluate_location_part(
+                    unit.encoding(),
+                    Some((dwarf, unit)),
+                    &FrameBase::RBP(base),
+                    frame,
+                    expr.clone(),
+                )?;
+                match piece.first().map(|p| &p.location) {
+                    Some(gimli::Location::Address { address }) => {
+                        calculated_address = vec![VariableLocation::Address(*address)];
+                    }
+                    x => Err(BridgeError::Unsupported(format!(
+                        "unsupported member location: {:?}",
+                        x
+                    )))?,
+                }
             }
             VariableExpression::Unknown { ref debug_info } => {
                 unimplemented!("Unknown variable content found {}", debug_info)
@@ -421,132 +985,1578 @@ pub fn evaluate_variable_from_string(
             let mut tree = unit.entries_tree(Some(UnitOffset(*offset)))?;
             let root = tree.root()?;
 
-            return match create_variable_info(root, calculated_address, constant_data, dwarf, unit)
-            {
-                Ok(x) => Ok(Some(x)),
+            return match create_variable_info(
+                root,
+                calculated_address,
+                constant_data,
+                dwarf,
+                unit,
+                types,
+                0,
+            ) {
+                Ok(mut x) => {
+                    if let Some(MemberLayout {
+                        byte_offset,
+                        bit_offset: Some(bit_offset),
+                        bit_size: Some(bit_size),
+                    }) = var.bit_field
+                    {
+                        let bit_offset = bit_offset - byte_offset * 8;
+                        x.bit_field = Some(BitField {
+                            bit_offset,
+                            bit_size,
+                            value_size: x.byte_size,
+                        });
+                        x.byte_size = (bit_offset + bit_size).div_ceil(8);
+                    }
+                    if x.tag == gimli::DW_TAG_array_type && x.dimensions.first() == Some(&None) {
+                        if let Some(bound) = dynamic_bound(unit, UnitOffset(*offset))? {
+                            let count = resolve_element_count(
+                                &bound,
+                                unit_data,
+                                code_offset,
+                                &frame_base,
+                                frame,
+                                &x.address_expr,
+                            );
+                            match count {
+                                Ok(Some(ElementCount::Known(count))) => {
+                                    x.set_element_count(bound.add_upper_bound(count) as usize)
+                                }
+                                Ok(Some(ElementCount::Memory { address, byte_size })) => {
+                                    x.address_expr.insert(
+                                        0,
+                                        VariableLocation::Count {
+                                            address,
+                                            byte_size,
+                                            upper_bound: bound.upper_bound,
+                                        },
+                                    );
+                                }
+                                result => {
+                                    if let Err(e) = result {
+                                        console_log!("length of '{}' is unknown: {}", x.name, e);
+                                    }
+                                    // show the first few elements, which the bound would cover
+                                    // for all but the shortest arrays
+                                    let stride = x.strides().first().copied().unwrap_or(0);
+                                    x.byte_size = UNKNOWN_LENGTH_ELEMENTS * stride;
+                                }
+                            }
+                        }
+                    }
+                    let value_bits = x.byte_size * 8;
+                    // pieces may stop short of the end of the value
+                    let supplied_bits = x.memory_slice.memory_slice.len() * 8;
+                    if from_pieces && supplied_bits < value_bits {
+                        unavailable_bits.push(supplied_bits..value_bits);
+                    }
+                    x.unavailable_bits = unavailable_bits
+                        .into_iter()
+                        .filter(|bits| bits.start < value_bits)
+                        .map(|bits| bits.start..bits.end.min(value_bits))
+                        .collect();
+                    Ok(Some(x))
+                }
                 Err(e) => {
                     console_log!("{}", e);
                     Ok(None)
                 }
             };
         }
-        TypeDescripter::Description(desc) => Ok(Some(VariableInfo {
-            name: desc.clone(),
-            address_expr: Vec::new(),
-            byte_size: 0,
-            tag: gimli::DW_TAG_class_type,
-            memory_slice: MemorySlice::new(),
-            state: VariableEvaluationResult::Ready,
-            encoding: gimli::DW_ATE_ASCII,
-        })),
+        TypeDescripter::Description(desc) => Ok(Some(unit_variable_info(
+            gimli::DW_TAG_class_type,
+            desc.clone(),
+            0,
+            gimli::DW_ATE_ASCII,
+            dwarf,
+            unit,
+        ))),
     }
 }
 
-fn evaluate_variable_location<R: gimli::Reader>(
-    encoding: gimli::Encoding,
-    base: &FrameBase,
-    expr: Expression<R>,
-) -> Result<Vec<gimli::Piece<R>>> {
-    let mut evaluation = expr.evaluation(encoding);
-    if let FrameBase::RBP(base) = base {
-        evaluation.set_initial_value(*base);
-    }
-    let mut result = evaluation.evaluate()?;
-    use gimli::EvaluationResult;
-    loop {
-        if let EvaluationResult::Complete = result {
-            return Ok(evaluation.result());
-        }
-        match result {
-            EvaluationResult::RequiresFrameBase => {
-                if let FrameBase::WasmFrameBase(base) = base {
-                    result = evaluation.resume_with_frame_base(*base)?;
-                } else {
-                    return Err(anyhow!("unexpected occurrence of DW_AT_frame_base"));
-                }
-            }
-            EvaluationResult::RequiresRelocatedAddress(addr) => {
-                if let FrameBase::WasmDataBase(base) = base {
-                    result = evaluation.resume_with_relocated_address(addr + *base)?;
-                } else {
-                    return Err(anyhow!("unexpected occurrence of relocated_address"));
-                }
-            }
-            ref x => Err(anyhow!("{:?}", x))?,
+/// `DW_AT_count` or `DW_AT_upper_bound` of the outermost dimension of an array
+/// computed at run time, as for C variable length arrays
+struct DynamicBound {
+    /// A reference to the variable or member holding the bound, or an
+    /// expression computing it
+    value: AttributeValue<DwarfReader>,
+    upper_bound: bool,
+}
+
+impl DynamicBound {
+    fn add_upper_bound(&self, count: u64) -> u64 {
+        match self.upper_bound {
+            true => count.wrapping_add(1),
+            false => count,
         }
     }
 }
 
-fn create_variable_info<R: gimli::Reader>(
-    node: gimli::EntriesTreeNode<R>,
-    address: Vec<VariableLocation>,
-    const_data: Option<Vec<u8>>,
-    dwarf: &gimli::Dwarf<R>,
+/// Where the element count of an array with a `DynamicBound` comes from
+enum ElementCount {
+    Known(u64),
+    Memory { address: u64, byte_size: usize },
+}
+
+/// The dynamic bound of the array type at `type_offset`, looking through
+/// typedefs and qualifiers
+fn dynamic_bound(
+    unit: &Unit<DwarfReader>,
+    type_offset: UnitOffset<DwarfReaderOffset>,
+) -> Result<Option<DynamicBound>> {
+    let mut offset = type_offset;
+    for _ in 0..MAX_VALUE_NESTING {
+        let entry = unit.entry(offset)?;
+        match entry.tag() {
+            gimli::DW_TAG_array_type => break,
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type => match entry.attr_value(gimli::DW_AT_type)? {
+                Some(AttributeValue::UnitRef(next)) => offset = next,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        }
+    }
+
+    let mut tree = unit.entries_tree(Some(offset))?;
+    let mut children = tree.root()?.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        if entry.tag() != gimli::DW_TAG_subrange_type {
+            continue;
+        }
+        for &(name, upper_bound) in &[
+            (gimli::DW_AT_count, false),
+            (gimli::DW_AT_upper_bound, true),
+        ] {
+            match entry.attr_value(name)? {
+                Some(value @ AttributeValue::UnitRef(_))
+                | Some(value @ AttributeValue::Exprloc(_)) => {
+                    return Ok(Some(DynamicBound { value, upper_bound }))
+                }
+                Some(_) => return Ok(None),
+                None => {}
+            }
+        }
+        return Ok(None);
+    }
+    Ok(None)
+}
+
+/// Evaluate a `DynamicBound`, or find the memory it has to be read from
+///
+/// A member holding the bound is looked up in the structure the array is a
+/// member of, which `array_address` ends in the array's offset into.
+fn resolve_element_count(
+    bound: &DynamicBound,
+    unit_data: &ParsedUnit,
+    code_offset: u64,
+    frame_base: &FrameBase,
+    frame: &WasmFrame,
+    array_address: &[VariableLocation],
+) -> Result<Option<ElementCount>> {
+    let (dwarf, unit, types) = unit_data;
+    let encoding = unit.encoding();
+    let address_size = unit.header.address_size();
+    let offset = match bound.value {
+        // an expression computes the bound itself rather than its location
+        AttributeValue::Exprloc(ref expr) => {
+            let pieces = evaluate_location_part(
+                encoding,
+                Some((dwarf, unit)),
+                frame_base,
+                frame,
+                expr.clone(),
+            )?;
+            return Ok(match pieces.first().map(|p| &p.location) {
+                Some(gimli::Location::Address { address }) => Some(ElementCount::Known(*address)),
+                Some(gimli::Location::Value { value }) => {
+                    Some(ElementCount::Known(value.to_u64(!0)?))
+                }
+                _ => None,
+            });
+        }
+        AttributeValue::UnitRef(offset) => offset,
+        _ => return Ok(None),
+    };
+
+    let entry = unit.entry(offset)?;
+    let byte_size = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(ty)) => types.byte_size(unit, ty.0)?,
+        _ => None,
+    }
+    .unwrap_or(address_size as u64) as usize;
+
+    match entry.tag() {
+        gimli::DW_TAG_variable | gimli::DW_TAG_formal_parameter => {
+            if let Some(value) = entry.attr_value(gimli::DW_AT_const_value)? {
+                return Ok(value.udata_value().map(ElementCount::Known));
+            }
+            let location = match entry.attr_value(gimli::DW_AT_location)? {
+                Some(location) => location,
+                None => return Ok(None),
+            };
+            let expr = match location_expression(dwarf, unit, &location, code_offset)? {
+                Some(expr) => expr,
+                None => return Ok(None),
+            };
+            let pieces =
+                evaluate_variable_location(encoding, Some((dwarf, unit)), frame_base, frame, expr)?;
+            Ok(match pieces.first().map(|p| &p.location) {
+                Some(gimli::Location::Address { address }) => Some(ElementCount::Memory {
+                    address: *address,
+                    byte_size,
+                }),
+                Some(gimli::Location::Value { value }) => {
+                    Some(ElementCount::Known(value.to_u64(!0)?))
+                }
+                _ => None,
+            })
+        }
+        gimli::DW_TAG_member => {
+            let parent = match array_address.split_last() {
+                Some((VariableLocation::Offset(_), parent)) => object_address(parent, address_size),
+                _ => None,
+            };
+            let parent = match parent {
+                Some(parent) => parent,
+                None => return Ok(None),
+            };
+            let address = parent.wrapping_add(member_location(unit, &entry)?);
+            Ok(Some(ElementCount::Memory {
+                address: wrap_address(address, address_size),
+                byte_size,
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Select the location expression of a variable that applies at the pc
+///
+/// A location list yields the expression of the entry covering the pc, or
+/// `None` when the variable has no location there.
+fn location_expression(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader, DwarfReaderOffset>,
+    location: &AttributeValue<DwarfReader>,
+    code_offset: u64,
+) -> Result<Option<Expression<DwarfReader>>> {
+    if let AttributeValue::Exprloc(expr) = location {
+        return Ok(Some(expr.clone()));
+    }
+
+    let mut entries = match dwarf.attr_locations(unit, location.clone())? {
+        Some(entries) => entries,
+        None => Err(BridgeError::Unsupported(format!(
+            "unsupported variable location form: {:?}",
+            location
+        )))?,
+    };
+    while let Some(entry) = entries.next()? {
+        if (entry.range.begin..entry.range.end).contains(&code_offset) {
+            return Ok(Some(entry.data));
+        }
+    }
+    Ok(None)
+}
+
+/// Code ranges where a variable has a location, merged where they touch
+///
+/// Returns `None` for a variable located by one expression or given by a
+/// constant, which is available wherever it is in scope.
+pub fn available_ranges(
+    dwarf: &gimli::Dwarf<DwarfReader>,
+    unit: &Unit<DwarfReader, DwarfReaderOffset>,
+    var: &SymbolVariable,
+) -> Result<Option<Vec<Range<u64>>>> {
+    let location = match var.contents.first() {
+        Some(VariableExpression::Location(AttributeValue::Exprloc(_))) => return Ok(None),
+        Some(VariableExpression::Location(location)) => location,
+        Some(_) => return Ok(None),
+        None => return Ok(Some(Vec::new())),
+    };
+    let mut entries = match dwarf.attr_locations(unit, location.clone())? {
+        Some(entries) => entries,
+        None => return Ok(None),
+    };
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    while let Some(entry) = entries.next()? {
+        if entry.range.begin < entry.range.end {
+            ranges.push(entry.range.begin..entry.range.end);
+        }
+    }
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<u64>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    Ok(Some(merged))
+}
+
+/// Evaluate DW_AT_frame_base of a subprogram to the frame base address
+pub fn evaluate_frame_base<R: gimli::Reader>(
+    encoding: gimli::Encoding,
+    expr: Expression<R>,
+    frame: &WasmFrame,
+) -> Result<u64> {
+    let pieces = evaluate_variable_location(encoding, None, &FrameBase::Unavailable, frame, expr)?;
+
+    match pieces.first().map(|piece| &piece.location) {
+        Some(gimli::Location::Address { address }) => Ok(*address),
+        Some(gimli::Location::Value { value }) => Ok(value.to_u64(!0)?),
+        Some(x) => Err(anyhow!("unexpected frame base location: {:?}", x)),
+        None => Err(anyhow!("empty frame base expression")),
+    }
+}
+
+/// Evaluate a location expression, resolving the base types of typed
+/// operations and the indexed addresses of split units in `unit`
+fn evaluate_variable_location<R: gimli::Reader>(
+    encoding: gimli::Encoding,
+    unit: Option<(&gimli::Dwarf<R>, &Unit<R>)>,
+    base: &FrameBase,
+    frame: &WasmFrame,
+    expr: Expression<R>,
+) -> Result<Vec<gimli::Piece<R>>> {
+    // each piece of a composite location may start with its own
+    // DW_OP_WASM_location, so the pieces are evaluated one at a time
+    let mut pieces = Vec::new();
+    let mut segment = expr.0.clone();
+    let mut rest = expr.0.clone();
+    while !rest.is_empty() {
+        let operation_start = rest.clone();
+        if let gimli::Operation::Piece {
+            size_in_bits,
+            bit_offset,
+        } = gimli::Operation::parse(&mut rest, encoding)
+            .map_err(|e| unsupported_operation(e.into()))?
+        {
+            let mut bytes = segment.clone();
+            bytes.truncate(operation_start.offset_from(&segment))?;
+            // a piece without a location was optimized out
+            let location = match bytes.is_empty() {
+                true => gimli::Location::Empty,
+                false => {
+                    let mut parts =
+                        evaluate_location_part(encoding, unit, base, frame, Expression(bytes))?;
+                    match parts.len() {
+                        1 => parts.remove(0).location,
+                        _ => Err(anyhow!("unexpected pieces inside a piece"))?,
+                    }
+                }
+            };
+            pieces.push(gimli::Piece {
+                size_in_bits: Some(size_in_bits),
+                bit_offset,
+                location,
+            });
+            segment = rest.clone();
+        }
+    }
+
+    if pieces.is_empty() {
+        evaluate_location_part(encoding, unit, base, frame, expr)
+    } else {
+        Ok(pieces)
+    }
+}
+
+/// Evaluate a location expression without `DW_OP_piece`
+fn evaluate_location_part<R: gimli::Reader>(
+    encoding: gimli::Encoding,
+    unit: Option<(&gimli::Dwarf<R>, &Unit<R>)>,
+    base: &FrameBase,
+    frame: &WasmFrame,
+    expr: Expression<R>,
+) -> Result<Vec<gimli::Piece<R>>> {
+    // gimli can't evaluate DW_OP_WASM_location, so resolve a leading one here
+    // and continue with its value pushed on the stack
+    let mut bytes = expr.0.clone();
+    let mut wasm_value = None;
+    if !bytes.is_empty() && expr.0.clone().read_u8()? == gimli::DW_OP_WASM_location.0 {
+        let mut rest = expr.0.clone();
+        let loc = read_wasm_location_op(&mut rest)?;
+        let value = frame.value_at(&loc)?;
+
+        // without DW_OP_stack_value the local holds the variable's address
+        let mut tail = rest.clone();
+        let is_value_itself =
+            !tail.is_empty() && tail.read_u8()? == gimli::DW_OP_stack_value.0 && tail.is_empty();
+        if is_value_itself {
+            return Ok(vec![gimli::Piece {
+                size_in_bits: None,
+                bit_offset: None,
+                location: gimli::Location::Value {
+                    value: value.to_gimli_value(),
+                },
+            }]);
+        }
+
+        wasm_value = Some(value);
+        bytes = rest;
+    }
+
+    let expr = Expression(bytes);
+    let mut evaluation = expr.evaluation(encoding);
+    if let Some(value) = wasm_value {
+        evaluation.set_initial_value(value.to_bits());
+    } else if let FrameBase::RBP(base) = base {
+        evaluation.set_initial_value(*base);
+    }
+    run_evaluation(&mut evaluation, encoding, unit, base, frame).map_err(unsupported_operation)?;
+    Ok(evaluation.result())
+}
+
+/// Supply what an evaluation asks for until it completes
+fn run_evaluation<R: gimli::Reader>(
+    evaluation: &mut gimli::Evaluation<R>,
+    encoding: gimli::Encoding,
+    unit: Option<(&gimli::Dwarf<R>, &Unit<R>)>,
+    base: &FrameBase,
+    frame: &WasmFrame,
+) -> Result<()> {
+    let mut result = evaluation.evaluate()?;
+    use gimli::EvaluationResult;
+    loop {
+        if let EvaluationResult::Complete = result {
+            return Ok(());
+        }
+        match result {
+            EvaluationResult::RequiresFrameBase => {
+                if let FrameBase::WasmFrameBase(base) = base {
+                    result = evaluation.resume_with_frame_base(*base)?;
+                } else {
+                    return Err(anyhow!("unexpected occurrence of DW_AT_frame_base"));
+                }
+            }
+            EvaluationResult::RequiresCallFrameCfa => {
+                Err(BridgeError::Unsupported(
+                    "DW_OP_call_frame_cfa is not supported for wasm frames".to_string(),
+                ))?;
+            }
+            EvaluationResult::RequiresRelocatedAddress(addr) => {
+                let address = data_address(encoding, addr, frame.data_base)?;
+                result = evaluation.resume_with_relocated_address(address)?;
+            }
+            EvaluationResult::RequiresIndexedAddress { index, relocate } => {
+                let address = match unit {
+                    Some((dwarf, unit)) => dwarf.address(unit, index)?,
+                    None => Err(BridgeError::Unavailable(
+                        "indexed address outside of a unit".to_string(),
+                    ))?,
+                };
+                let address = match relocate {
+                    true => data_address(encoding, address, frame.data_base)?,
+                    false => address,
+                };
+                result = evaluation.resume_with_indexed_address(address)?;
+            }
+            EvaluationResult::RequiresTls(offset) => {
+                let index = match frame.tls_base {
+                    Some(index) => index,
+                    None => Err(BridgeError::Unsupported(
+                        "thread-local variable in a module without a __tls_base global".to_string(),
+                    ))?,
+                };
+                let base = frame.value_at(&WasmLoc::Global(index as u64))?.to_bits();
+                result = evaluation.resume_with_tls(base.wrapping_add(offset))?;
+            }
+            EvaluationResult::RequiresMemory {
+                address,
+                size,
+                space: None,
+                base_type,
+            } => {
+                let raw = read_address(frame, address, size)?;
+                let value = match base_type.0.into_u64() {
+                    0 => gimli::Value::Generic(raw),
+                    _ => {
+                        let value_type = base_value_type(unit, base_type)?;
+                        let bytes = raw.to_le_bytes();
+                        let bytes = &bytes[..(size as usize).min(bytes.len())];
+                        gimli::Value::parse(
+                            value_type,
+                            gimli::EndianSlice::new(bytes, gimli::LittleEndian),
+                        )?
+                    }
+                };
+                result = evaluation.resume_with_memory(value)?;
+            }
+            EvaluationResult::RequiresBaseType(offset) => {
+                let value_type = base_value_type(unit, offset)?;
+                result = evaluation.resume_with_base_type(value_type)?;
+            }
+            EvaluationResult::RequiresRegister {
+                register,
+                base_type,
+            } => {
+                let value = frame.registers.and_then(|read| read(register.0));
+                let value = match value {
+                    Some(value) => value,
+                    None => Err(BridgeError::Unavailable(format!(
+                        "location needs register {}, which wasn't supplied",
+                        register.0
+                    )))?,
+                };
+                let value = match base_type.0.into_u64() {
+                    0 => gimli::Value::Generic(value),
+                    _ => gimli::Value::from_u64(base_value_type(unit, base_type)?, value)?,
+                };
+                result = evaluation.resume_with_register(value)?;
+            }
+            // wasm frames have nothing else to supply, such as entry values
+            ref x => Err(BridgeError::Unavailable(format!(
+                "location needs {:?}, which wasm frames don't have",
+                x
+            )))?,
+        }
+    }
+}
+
+/// Report operations gimli can't evaluate as leaving the variable unavailable,
+/// rather than as a failure
+fn unsupported_operation(e: anyhow::Error) -> anyhow::Error {
+    match e.downcast_ref::<gimli::Error>() {
+        Some(gimli::Error::InvalidExpression(op)) => {
+            BridgeError::Unavailable(format!("{} can't be evaluated", op)).into()
+        }
+        Some(
+            gimli::Error::UnsupportedEvaluation
+            | gimli::Error::UnsupportedTypeOperation
+            | gimli::Error::TypeMismatch
+            | gimli::Error::IntegralTypeRequired,
+        ) => BridgeError::Unavailable(format!("location can't be evaluated: {}", e)).into(),
+        _ => e,
+    }
+}
+
+/// Value type of the base type DIE a typed operation such as `DW_OP_convert`
+/// refers to
+fn base_value_type<R: gimli::Reader>(
+    unit: Option<(&gimli::Dwarf<R>, &Unit<R>)>,
+    offset: UnitOffset<R::Offset>,
+) -> Result<gimli::ValueType> {
+    let unit = match unit {
+        Some((_, unit)) => unit,
+        None => Err(BridgeError::Unavailable(
+            "typed operation outside of a unit".to_string(),
+        ))?,
+    };
+    let entry = unit.entry(offset)?;
+    let encoding = match entry.attr_value(gimli::DW_AT_encoding)? {
+        Some(AttributeValue::Encoding(encoding)) => encoding,
+        _ => Err(anyhow!(
+            "base type at {:#x} has no encoding",
+            offset.0.into_u64()
+        ))?,
+    };
+    let byte_size = entry
+        .attr_value(gimli::DW_AT_byte_size)?
+        .and_then(|size| size.udata_value())
+        .unwrap_or(0);
+    gimli::ValueType::from_encoding(encoding, byte_size).ok_or_else(|| {
+        BridgeError::Unavailable(format!(
+            "values of {} bytes encoded as {} can't be evaluated",
+            byte_size, encoding
+        ))
+        .into()
+    })
+}
+
+/// Read the little-endian value a `DW_OP_deref` or `DW_OP_deref_size` loads
+fn read_address(frame: &WasmFrame, address: u64, size: u8) -> Result<u64> {
+    let read = match frame.memory {
+        Some(read) => read,
+        None => Err(BridgeError::Unavailable(format!(
+            "locating the variable needs to read memory at {:#x}",
+            address
+        )))?,
+    };
+    let bytes = match read(address, size as usize) {
+        Some(bytes) if bytes.len() >= size as usize => bytes,
+        _ => Err(BridgeError::Unavailable(format!(
+            "memory at {:#x} can't be read",
+            address
+        )))?,
+    };
+    Ok(bytes[..size.min(8) as usize]
+        .iter()
+        .rev()
+        .fold(0, |value, byte| value << 8 | *byte as u64))
+}
+
+/// Linear memory address of a `DW_OP_addr` operand
+fn data_address(encoding: gimli::Encoding, address: u64, data_base: u64) -> Result<u64> {
+    // the linker points variables it discarded at the last address
+    let tombstone = match encoding.address_size {
+        8 => u64::MAX,
+        _ => u32::MAX as u64,
+    };
+    if address == tombstone {
+        Err(BridgeError::OptimizedOut(
+            "variable was discarded by the linker".to_string(),
+        ))?
+    }
+
+    Ok(address.wrapping_add(data_base))
+}
+
+/// Concatenate the pieces of a composite location into the little-endian
+/// bytes of the value
+///
+/// Returns the bit ranges no piece supplied, such as optimized out pieces.
+/// Pieces in memory aren't read and count as missing as well.
+fn assemble_pieces<R: gimli::Reader>(
+    pieces: &[gimli::Piece<R>],
+) -> Result<(Vec<u8>, Vec<Range<usize>>)> {
+    let mut bytes = Vec::new();
+    let mut missing: Vec<Range<usize>> = Vec::new();
+    let mut position = 0;
+
+    for piece in pieces {
+        let size = match piece.size_in_bits {
+            Some(size) => size as usize,
+            None => Err(anyhow!("piece of a composite location without a size"))?,
+        };
+        let source = match piece.location {
+            gimli::Location::Value { value } => Some(value_to_bytes(value, RunTimeEndian::Little)),
+            gimli::Location::Bytes { ref value } => Some(value.to_slice()?.into_owned()),
+            _ => None,
+        };
+        bytes.resize((position + size).div_ceil(8), 0);
+
+        match source {
+            Some(source) => {
+                let start = piece.bit_offset.unwrap_or(0) as usize;
+                for bit in 0..size {
+                    let source_bit = start + bit;
+                    let set = source
+                        .get(source_bit / 8)
+                        .is_some_and(|byte| byte >> (source_bit % 8) & 1 != 0);
+                    if set {
+                        let target_bit = position + bit;
+                        bytes[target_bit / 8] |= 1 << (target_bit % 8);
+                    }
+                }
+            }
+            None => match missing.last_mut() {
+                Some(last) if last.end == position => last.end += size,
+                _ => missing.push(position..position + size),
+            },
+        }
+        position += size;
+    }
+    Ok((bytes, missing))
+}
+
+fn value_to_bytes(value: gimli::Value, endian: RunTimeEndian) -> Vec<u8> {
+    let bytes = match value {
+        gimli::Value::Generic(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::I8(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::U8(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::I16(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::U16(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::I32(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::U32(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::I64(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::U64(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::F32(v) => v.to_le_bytes().to_vec(),
+        gimli::Value::F64(v) => v.to_le_bytes().to_vec(),
+    };
+    target_bytes(bytes, endian)
+}
+
+/// Reorder little-endian bytes of a value into the target's byte order
+fn target_bytes(mut bytes: Vec<u8>, endian: RunTimeEndian) -> Vec<u8> {
+    if endian.is_big_endian() {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// Bound on types nested through pointees, array elements and enum variants,
+/// which recursive types such as linked lists would otherwise never end
+const MAX_VALUE_NESTING: usize = 6;
+
+fn create_variable_info<R: gimli::Reader>(
+    node: gimli::EntriesTreeNode<R>,
+    address: Vec<VariableLocation>,
+    const_data: Option<Vec<u8>>,
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    types: &UnitTypes<R::Offset>,
+    depth: usize,
+) -> Result<VariableInfo> {
+    if depth > MAX_VALUE_NESTING {
+        Err(anyhow!(
+            "type at {:?} is nested too deeply",
+            node.entry().offset()
+        ))?
+    }
+    let data = const_data.unwrap_or_default();
+
+    match node.entry().tag() {
+        gimli::DW_TAG_base_type => {
+            let entry = node.entry();
+            let name = match entry.attr_value(gimli::DW_AT_name)? {
+                Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+                None => "<no type name>".to_string(),
+            };
+            let byte_size = types
+                .byte_size(unit, entry.offset().0)?
+                .unwrap_or(unit.header.address_size() as u64);
+            let encoding = entry
+                .attr_value(gimli::DW_AT_encoding)?
+                .and_then(|attr| match attr {
+                    gimli::AttributeValue::Encoding(encoding) => Some(encoding),
+                    _ => None,
+                })
+                .unwrap_or(gimli::constants::DW_ATE_unsigned);
+
+            Ok(VariableInfo {
+                address_expr: address,
+                memory_slice: MemorySlice::from_u8_vec(data),
+                ..unit_variable_info(
+                    gimli::DW_TAG_base_type,
+                    name,
+                    byte_size as usize,
+                    encoding,
+                    dwarf,
+                    unit,
+                )
+            })
+        }
+        gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
+            let entry = node.entry();
+            let tag = entry.tag();
+            let type_name = match entry.attr_value(gimli::DW_AT_name)? {
+                Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+                None => "<no type name>".to_string(),
+            };
+            let byte_size = types.byte_size(unit, entry.offset().0)?.unwrap_or(0);
+            let offset = entry.offset();
+
+            let mut members = Vec::new();
+            let mut variant_part = None;
+            let mut children = node.children();
+            while let Some(child) = children.next()? {
+                match child.entry().tag() {
+                    gimli::DW_TAG_member => {
+                        push_member(dwarf, unit, types, child.entry(), 0, &mut members, depth)?;
+                    }
+                    gimli::DW_TAG_variant_part => {
+                        variant_part = Some(read_variant_part(child, dwarf, unit, types, depth)?);
+                    }
+                    _ => {}
+                }
+            }
+
+            let (string, pointee) =
+                match rust_string_layout(dwarf, unit, types, offset, &type_name, depth)? {
+                    Some((layout, element)) => {
+                        let characters = StringLayout::Characters { length: 0 };
+                        let buffer = character_buffer(element, 0, Some(characters));
+                        (Some(layout), Some(Box::new(buffer)))
+                    }
+                    None => (None, None),
+                };
+
+            Ok(VariableInfo {
+                address_expr: address,
+                memory_slice: MemorySlice::from_u8_vec(data),
+                members,
+                pointee,
+                string,
+                variant_part,
+                ..unit_variable_info(
+                    tag,
+                    type_name,
+                    byte_size as usize,
+                    gimli::DW_ATE_signed,
+                    dwarf,
+                    unit,
+                )
+            })
+        }
+        gimli::DW_TAG_enumeration_type => {
+            let entry = node.entry();
+            let name = match entry.attr_value(gimli::DW_AT_name)? {
+                Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+                None => "<no type name>".to_string(),
+            };
+
+            // the underlying type decides signedness; C enums without one are ints
+            let underlying = match entry.attr_value(gimli::DW_AT_type)? {
+                Some(AttributeValue::UnitRef(ref offset)) => {
+                    let mut tree = unit.entries_tree(Some(UnitOffset(offset.0)))?;
+                    let root = tree.root()?;
+                    create_variable_info(root, Vec::new(), None, dwarf, unit, types, depth + 1).ok()
+                }
+                _ => None,
+            };
+            let encoding = underlying
+                .as_ref()
+                .map(|info| info.encoding)
+                .unwrap_or(gimli::DW_ATE_signed);
+            let byte_size = types.byte_size(unit, entry.offset().0)?.unwrap_or(4);
+            let signed = matches!(encoding, gimli::DW_ATE_signed | gimli::DW_ATE_signed_char);
+
+            let mut enumerators = Vec::new();
+            let mut children = node.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_enumerator {
+                    continue;
+                }
+                let name = match child.attr_value(gimli::DW_AT_name)? {
+                    Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+                    None => continue,
+                };
+                let value = match child.attr_value(gimli::DW_AT_const_value)? {
+                    Some(attr) => enumerator_value(attr, signed),
+                    None => None,
+                };
+                if let Some(value) = value {
+                    enumerators.push((name, value));
+                }
+            }
+
+            Ok(VariableInfo {
+                address_expr: address,
+                memory_slice: MemorySlice::from_u8_vec(data),
+                enumerators,
+                ..unit_variable_info(
+                    gimli::DW_TAG_enumeration_type,
+                    name,
+                    byte_size as usize,
+                    encoding,
+                    dwarf,
+                    unit,
+                )
+            })
+        }
+        gimli::DW_TAG_array_type => {
+            let entry = node.entry();
+            let name = types.type_name(dwarf, unit, Some(entry.offset().0))?;
+            let element = match entry.attr_value(gimli::DW_AT_type)? {
+                Some(AttributeValue::UnitRef(ref offset)) => {
+                    let mut tree = unit.entries_tree(Some(UnitOffset(offset.0)))?;
+                    let root = tree.root()?;
+                    create_variable_info(root, Vec::new(), None, dwarf, unit, types, depth + 1)?
+                }
+                _ => Err(anyhow!("array without element type"))?,
+            };
+            let byte_size = types.byte_size(unit, entry.offset().0)?.unwrap_or(0);
+            // a stride on the array itself spaces the elements of the innermost dimension
+            let element_stride = byte_stride(entry)?;
+
+            let mut dimensions = Vec::new();
+            let mut byte_strides = Vec::new();
+            let mut children = node.children();
+            while let Some(child) = children.next()? {
+                if child.entry().tag() == gimli::DW_TAG_subrange_type {
+                    dimensions.push(subrange_count(child.entry())?);
+                    byte_strides.push(byte_stride(child.entry())?);
+                }
+            }
+            if let Some(last) = byte_strides.last_mut() {
+                *last = last.or(element_stride);
+            }
+
+            let encoding = element.encoding;
+            let mut info = VariableInfo {
+                address_expr: address,
+                memory_slice: MemorySlice::from_u8_vec(data),
+                element: Some(Box::new(element)),
+                dimensions,
+                byte_strides,
+                ..unit_variable_info(
+                    gimli::DW_TAG_array_type,
+                    name,
+                    byte_size as usize,
+                    encoding,
+                    dwarf,
+                    unit,
+                )
+            };
+            // the size computed from the element type misses the padding strides add
+            if info.byte_strides.iter().any(Option::is_some) {
+                if let (Some(Some(count)), Some(stride)) =
+                    (info.dimensions.first(), info.strides().first())
+                {
+                    info.byte_size = info.byte_size.max(*count as usize * stride);
+                }
+            }
+            Ok(info)
+        }
+        // references are shown like pointers, as the address followed by the referent
+        gimli::DW_TAG_pointer_type
+        | gimli::DW_TAG_reference_type
+        | gimli::DW_TAG_rvalue_reference_type => {
+            let entry = node.entry();
+            let name = types.type_name(dwarf, unit, Some(entry.offset().0))?;
+            let byte_size = types
+                .byte_size(unit, entry.offset().0)?
+                .unwrap_or(unit.header.address_size() as u64);
+
+            // void pointers and unsupported pointee types only show the address
+            let pointee = match entry.attr_value(gimli::DW_AT_type)? {
+                Some(AttributeValue::UnitRef(ref offset)) => {
+                    let mut tree = unit.entries_tree(Some(UnitOffset(offset.0)))?;
+                    let root = tree.root()?;
+                    create_variable_info(root, Vec::new(), None, dwarf, unit, types, depth + 1)
+                        .ok()
+                        .map(|mut pointee| {
+                            // only one level is dereferenced, besides the text of a string
+                            if pointee.string.is_none() {
+                                pointee.pointee = None;
+                            }
+                            Box::new(pointee)
+                        })
+                }
+                _ => None,
+            };
+            // a C string is read up to its terminator, or as far as it is shown
+            let pointee = pointee.map(|pointee| {
+                if is_character(&pointee) {
+                    Box::new(character_buffer(
+                        *pointee,
+                        MAX_STRING_LENGTH as u64 + 1,
+                        Some(StringLayout::Terminated),
+                    ))
+                } else {
+                    pointee
+                }
+            });
+
+            Ok(VariableInfo {
+                address_expr: address,
+                memory_slice: MemorySlice::from_u8_vec(data),
+                pointee,
+                ..unit_variable_info(
+                    gimli::DW_TAG_pointer_type,
+                    name,
+                    byte_size as usize,
+                    gimli::DW_ATE_address,
+                    dwarf,
+                    unit,
+                )
+            })
+        }
+        _ => match node.entry().attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(ref offset)) => {
+                let mut tree = unit.entries_tree(Some(UnitOffset(offset.0)))?;
+                let root = tree.root()?;
+
+                create_variable_info(root, address, Some(data), dwarf, unit, types, depth)
+            }
+            _ => Err(anyhow!("unsupported DIE type")),
+        },
+    }
+}
+
+/// Read the discriminant and variants of a Rust enum
+fn read_variant_part<R: gimli::Reader>(
+    node: gimli::EntriesTreeNode<R>,
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    types: &UnitTypes<R::Offset>,
+    depth: usize,
+) -> Result<VariantPart> {
+    let type_info = |type_attr: Option<AttributeValue<R>>| -> Result<Option<VariableInfo>> {
+        match type_attr {
+            Some(AttributeValue::UnitRef(offset)) => {
+                let mut tree = unit.entries_tree(Some(offset))?;
+                let root = tree.root()?;
+                Ok(
+                    create_variable_info(root, Vec::new(), None, dwarf, unit, types, depth + 1)
+                        .ok(),
+                )
+            }
+            _ => Ok(None),
+        }
+    };
+
+    let discriminant = match node.entry().attr_value(gimli::DW_AT_discr)? {
+        Some(AttributeValue::UnitRef(offset)) => {
+            let member = unit.entry(offset)?;
+            let location = member_location(unit, &member)?;
+            type_info(member.attr_value(gimli::DW_AT_type)?)?
+                .map(|info| (location as usize, Box::new(info)))
+        }
+        _ => None,
+    };
+    let signed = match discriminant {
+        Some((_, ref info)) => {
+            matches!(
+                info.encoding,
+                gimli::DW_ATE_signed | gimli::DW_ATE_signed_char
+            )
+        }
+        None => false,
+    };
+
+    let mut variants = Vec::new();
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() != gimli::DW_TAG_variant {
+            continue;
+        }
+        let discriminants = variant_discriminants(child.entry(), signed)?;
+
+        let mut members = child.children();
+        while let Some(member) = members.next()? {
+            let member = member.entry();
+            if member.tag() != gimli::DW_TAG_member {
+                continue;
+            }
+            let name = match member.attr_value(gimli::DW_AT_name)? {
+                Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+                None => "<unnamed>".to_string(),
+            };
+            let location = member_location(unit, member)? as usize;
+
+            // Rust wraps the fields of each variant in a structure named after it
+            let mut fields = Vec::new();
+            let fields_type = match member.attr_value(gimli::DW_AT_type)? {
+                Some(AttributeValue::UnitRef(offset)) => Some(unit.entry(offset)?),
+                _ => None,
+            };
+            match fields_type {
+                Some(ref structure) if structure.tag() == gimli::DW_TAG_structure_type => {
+                    let mut tree = unit.entries_tree(Some(structure.offset()))?;
+                    let root = tree.root()?;
+                    let mut structure_members = root.children();
+                    while let Some(field) = structure_members.next()? {
+                        let field = field.entry();
+                        if field.tag() != gimli::DW_TAG_member {
+                            continue;
+                        }
+                        let field_name = match field.attr_value(gimli::DW_AT_name)? {
+                            Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+                            None => "<unnamed>".to_string(),
+                        };
+                        fields.push((
+                            field_name,
+                            location + member_location(unit, field)? as usize,
+                            type_info(field.attr_value(gimli::DW_AT_type)?)?,
+                        ));
+                    }
+                }
+                _ => fields.push((
+                    name.clone(),
+                    location,
+                    type_info(member.attr_value(gimli::DW_AT_type)?)?,
+                )),
+            }
+
+            variants.push(Variant {
+                name,
+                discriminants,
+                fields,
+            });
+            break;
+        }
+    }
+
+    Ok(VariantPart {
+        discriminant,
+        variants,
+    })
+}
+
+/// Offset of a member in its parent, with offset 0 as a last resort for
+/// members an expression places from their object's address
+fn member_location<R: gimli::Reader>(
+    unit: &Unit<R>,
+    member: &DebuggingInformationEntry<R>,
+) -> Result<u64> {
+    let attr = match member.attr_value(gimli::DW_AT_data_member_location)? {
+        Some(attr) => attr,
+        None => return Ok(0),
+    };
+    if let Some(offset) = constant_member_offset(unit.encoding(), &attr)? {
+        return Ok(offset);
+    }
+    console_log!(
+        "member at {:#x} needs the address of its object, assuming offset 0",
+        member.offset().0.into_u64()
+    );
+    Ok(0)
+}
+
+/// Offset a `DW_AT_data_member_location` gives as a constant or as an
+/// expression that only adds to the object's address, like the
+/// `DW_OP_plus_uconst` of DWARF 2 producers
+fn constant_member_offset<R: gimli::Reader>(
+    encoding: gimli::Encoding,
+    attr: &AttributeValue<R>,
+) -> Result<Option<u64>> {
+    if let Some(offset) = attr.udata_value() {
+        return Ok(Some(offset));
+    }
+    let expr = match attr {
+        AttributeValue::Exprloc(expr) => expr.clone(),
+        _ => return Ok(None),
+    };
+    let mut evaluation = expr.evaluation(encoding);
+    evaluation.set_initial_value(0);
+    // reading memory or anything else about the object makes it dynamic
+    if let gimli::EvaluationResult::Complete = evaluation.evaluate()? {
+        if let [gimli::Piece {
+            location: gimli::Location::Address { address },
+            ..
+        }] = evaluation.result()[..]
+        {
+            return Ok(Some(address));
+        }
+    }
+    Ok(None)
+}
+
+/// Address the locations resolved so far end at, unless a pointer still has
+/// to be read
+fn object_address(locations: &[VariableLocation], address_size: u8) -> Option<u64> {
+    let mut address = 0;
+    for location in locations {
+        match location {
+            VariableLocation::Address(a) => address = *a,
+            VariableLocation::Offset(offset) => {
+                address = wrap_address(address.wrapping_add(*offset as u64), address_size)
+            }
+            VariableLocation::Pointer(_) | VariableLocation::Count { .. } => return None,
+        }
+    }
+    Some(address)
+}
+
+/// Discriminant values of a `DW_TAG_variant` as inclusive ranges, `None` for
+/// the default variant
+///
+/// Values keep the bits of the raw constant, with signed ones sign extended
+/// to 64 bits.
+fn variant_discriminants<R: gimli::Reader>(
+    variant: &DebuggingInformationEntry<R>,
+    signed: bool,
+) -> Result<Option<Vec<(u64, u64)>>> {
+    if let Some(value) = variant.attr_value(gimli::DW_AT_discr_value)? {
+        let value = match value {
+            AttributeValue::Sdata(value) => value as u64,
+            value => match value.udata_value() {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+        };
+        return Ok(Some(vec![(value, value)]));
+    }
+
+    let mut list = match variant.attr_value(gimli::DW_AT_discr_list)? {
+        Some(AttributeValue::Block(list)) => list,
+        _ => return Ok(None),
+    };
+    let read_value = |list: &mut R| -> Result<u64> {
+        Ok(match signed {
+            true => list.read_sleb128()? as u64,
+            false => list.read_uleb128()?,
+        })
+    };
+    let mut ranges = Vec::new();
+    while !list.is_empty() {
+        match gimli::DwDsc(list.read_u8()?) {
+            gimli::DW_DSC_label => {
+                let value = read_value(&mut list)?;
+                ranges.push((value, value));
+            }
+            gimli::DW_DSC_range => {
+                let low = read_value(&mut list)?;
+                let high = read_value(&mut list)?;
+                ranges.push((low, high));
+            }
+            other => Err(anyhow!("unknown discriminant descriptor: {}", other))?,
+        }
+    }
+    Ok(Some(ranges))
+}
+
+fn byte_stride<R: gimli::Reader>(entry: &DebuggingInformationEntry<R>) -> Result<Option<u64>> {
+    Ok(entry
+        .attr_value(gimli::DW_AT_byte_stride)?
+        .and_then(|attr| attr.udata_value()))
+}
+
+/// An array of `count` characters, as read through a string pointer
+fn character_buffer(
+    element: VariableInfo,
+    count: u64,
+    string: Option<StringLayout>,
+) -> VariableInfo {
+    let name = format!("{}[]", element.name);
+    let byte_size = count as usize * element.byte_size;
+    let encoding = element.encoding;
+    VariableInfo {
+        dimensions: vec![Some(count)],
+        endian: element.endian,
+        address_size: element.address_size,
+        language: element.language,
+        element: Some(Box::new(element)),
+        string,
+        ..VariableInfo::new(gimli::DW_TAG_array_type, name, byte_size, encoding)
+    }
+}
+
+/// A value of a type declared in `unit`, read with the unit's byte order and
+/// address size
+fn unit_variable_info<R: gimli::Reader>(
+    tag: gimli::DwTag,
+    name: String,
+    byte_size: usize,
+    encoding: gimli::DwAte,
+    dwarf: &gimli::Dwarf<R>,
     unit: &Unit<R>,
-) -> Result<VariableInfo> {
-    let data = const_data.unwrap_or_default();
+) -> VariableInfo {
+    VariableInfo {
+        endian: dwarf_endian(dwarf),
+        address_size: unit.header.address_size(),
+        language: unit_language(unit),
+        ..VariableInfo::new(tag, name, byte_size, encoding)
+    }
+}
 
-    match node.entry().tag() {
-        gimli::DW_TAG_base_type => {
-            let entry = node.entry();
-            let name = match entry.attr_value(gimli::DW_AT_name)? {
-                Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
-                None => "<no type name>".to_string(),
-            };
-            let byte_size = entry
-                .attr_value(gimli::DW_AT_byte_size)?
-                .and_then(|attr| attr.udata_value())
-                .unwrap_or(unit.header.address_size() as u64);
-            let encoding = entry
-                .attr_value(gimli::DW_AT_encoding)?
-                .and_then(|attr| match attr {
-                    gimli::AttributeValue::Encoding(encoding) => Some(encoding),
-                    _ => None,
-                })
-                .unwrap_or(gimli::constants::DW_ATE_unsigned);
+/// Nesting of the structures holding the pointer and length of a `String`
+const MAX_STRING_NESTING: usize = 8;
 
-            Ok(VariableInfo {
-                address_expr: address,
-                byte_size: byte_size as usize,
-                name,
-                encoding,
-                tag: gimli::DW_TAG_base_type,
-                memory_slice: MemorySlice::from_u8_vec(data),
-                state: VariableEvaluationResult::Ready,
-            })
+/// Pointer and length of a Rust `&str` or `String`, with the character type
+///
+/// A `String` keeps them in the structures it wraps, so members are
+/// searched depth first.
+fn rust_string_layout<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    types: &UnitTypes<R::Offset>,
+    structure: UnitOffset<R::Offset>,
+    type_name: &str,
+    depth: usize,
+) -> Result<Option<(StringLayout, VariableInfo)>> {
+    if unit_language(unit) != SourceLanguage::Rust
+        || !matches!(type_name, "&str" | "&mut str" | "String")
+    {
+        return Ok(None);
+    }
+
+    let pointer = find_nested_member(
+        dwarf,
+        unit,
+        structure,
+        &|_, tag| tag == gimli::DW_TAG_pointer_type,
+        0,
+    )?;
+    let length = find_nested_member(
+        dwarf,
+        unit,
+        structure,
+        &|name, tag| tag == gimli::DW_TAG_base_type && (name == "length" || name == "len"),
+        0,
+    )?;
+    let ((pointer_offset, pointer_type), (length_offset, _)) = match (pointer, length) {
+        (Some(pointer), Some(length)) => (pointer, length),
+        _ => return Ok(None),
+    };
+
+    let element = match unit.entry(pointer_type)?.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(offset)) => {
+            let mut tree = unit.entries_tree(Some(offset))?;
+            create_variable_info(
+                tree.root()?,
+                Vec::new(),
+                None,
+                dwarf,
+                unit,
+                types,
+                depth + 1,
+            )?
         }
-        gimli::DW_TAG_class_type | gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type => {
-            let entry = node.entry();
-            let tag = entry.tag();
-            let type_name = match entry.attr_value(gimli::DW_AT_name)? {
-                Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
-                None => "<no type name>".to_string(),
+        _ => return Ok(None),
+    };
+    let layout = StringLayout::Slice {
+        pointer_offset: pointer_offset as usize,
+        length_offset: length_offset as usize,
+        word_size: unit.header.address_size() as usize,
+    };
+    Ok(Some((layout, element)))
+}
+
+/// Byte offset and type of the first member selected by `is_wanted`, which
+/// gets the member name and the tag of its type
+fn find_nested_member<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    structure: UnitOffset<R::Offset>,
+    is_wanted: &dyn Fn(&str, gimli::DwTag) -> bool,
+    depth: usize,
+) -> Result<Option<(u64, UnitOffset<R::Offset>)>> {
+    if depth > MAX_STRING_NESTING {
+        return Ok(None);
+    }
+
+    let mut tree = unit.entries_tree(Some(structure))?;
+    let mut children = tree.root()?.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        if entry.tag() != gimli::DW_TAG_member {
+            continue;
+        }
+        let member_type = match entry.attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(offset)) => offset,
+            _ => continue,
+        };
+        let name = match entry.attr_value(gimli::DW_AT_name)? {
+            Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+            None => String::new(),
+        };
+        let offset = member_location(unit, entry)?;
+
+        let tag = unit.entry(member_type)?.tag();
+        if is_wanted(&name, tag) {
+            return Ok(Some((offset, member_type)));
+        }
+        if tag == gimli::DW_TAG_structure_type {
+            if let Some((inner, found)) =
+                find_nested_member(dwarf, unit, member_type, is_wanted, depth + 1)?
+            {
+                return Ok(Some((offset + inner, found)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Fixed-size data forms carry no signedness of their own
+fn enumerator_value<R: gimli::Reader>(attr: AttributeValue<R>, signed: bool) -> Option<BigInt> {
+    let value = match attr {
+        AttributeValue::Sdata(v) => BigInt::from(v),
+        AttributeValue::Udata(v) => BigInt::from(v),
+        AttributeValue::Data1(v) if signed => BigInt::from(v as i8),
+        AttributeValue::Data2(v) if signed => BigInt::from(v as i16),
+        AttributeValue::Data4(v) if signed => BigInt::from(v as i32),
+        AttributeValue::Data8(v) if signed => BigInt::from(v as i64),
+        AttributeValue::Data1(v) => BigInt::from(v),
+        AttributeValue::Data2(v) => BigInt::from(v),
+        AttributeValue::Data4(v) => BigInt::from(v),
+        AttributeValue::Data8(v) => BigInt::from(v),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Push a member placed `byte_offset` into the structure being listed, or the
+/// members of the anonymous structure or union it holds
+fn push_member<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    types: &UnitTypes<R::Offset>,
+    entry: &DebuggingInformationEntry<R>,
+    byte_offset: usize,
+    members: &mut Vec<VariableName>,
+    depth: usize,
+) -> Result<()> {
+    let mut member = transform_member(dwarf, unit, types, entry)?;
+    if let Some(layout) = member.layout.as_mut() {
+        layout.byte_offset += byte_offset;
+        layout.bit_offset = layout.bit_offset.map(|x| x + byte_offset * 8);
+    }
+
+    let anonymous = match anonymous_aggregate(unit, entry)? {
+        Some(anonymous) if depth < MAX_VALUE_NESTING => anonymous,
+        _ => {
+            members.push(member);
+            return Ok(());
+        }
+    };
+    let offset = member
+        .layout
+        .map_or(byte_offset, |layout| layout.byte_offset);
+    let mut tree = unit.entries_tree(Some(anonymous))?;
+    let mut children = tree.root()?.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() == gimli::DW_TAG_member {
+            push_member(
+                dwarf,
+                unit,
+                types,
+                child.entry(),
+                offset,
+                members,
+                depth + 1,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn transform_member<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    types: &UnitTypes<R::Offset>,
+    entry: &DebuggingInformationEntry<R>,
+) -> Result<VariableName> {
+    let name = match entry.attr_value(gimli::DW_AT_name)? {
+        Some(attr) => clone_string_attribute(dwarf, unit, attr)?,
+        None => "<unnamed>".to_string(),
+    };
+    let type_name = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(ref offset)) => types
+            .type_name(dwarf, unit, Some(offset.0))
+            .unwrap_or_else(|_| "<unnamed>".to_string()),
+        _ => "<unnamed>".to_string(),
+    };
+
+    Ok(VariableName {
+        name: name.clone(),
+        display_name: name,
+        linkage_name: None,
+        type_name,
+        group_id: 0,
+        child_group_id: None,
+        layout: Some(member_layout(dwarf, unit, entry)?),
+        die_offset: None,
+        unit_offset: None,
+        decl_file: None,
+        decl_line: None,
+        is_parameter: false,
+        is_artificial: false,
+        available_ranges: None,
+    })
+}
+
+/// Byte offset of a member, and for bit-fields its bits counted in the
+/// target's order: from the least significant bit of the first byte on
+/// little-endian targets and from the most significant one on big-endian
+fn member_layout<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &Unit<R>,
+    entry: &DebuggingInformationEntry<R>,
+) -> Result<MemberLayout> {
+    let udata = |at| -> Result<Option<u64>> {
+        Ok(entry.attr_value(at)?.and_then(|attr| attr.udata_value()))
+    };
+    let bit_size = udata(gimli::DW_AT_bit_size)?;
+    let member_location = member_location(unit, entry)?;
+
+    let (byte_offset, bit_offset) = match (udata(gimli::DW_AT_data_bit_offset)?, bit_size) {
+        (Some(data_bit_offset), _) => (data_bit_offset / 8, Some(data_bit_offset)),
+        (None, Some(bit_size)) => {
+            let byte_offset = member_location;
+            // DWARF 2/3 counts DW_AT_bit_offset from the most significant bit
+            // of the storage unit
+            let bit_offset = match (
+                udata(gimli::DW_AT_bit_offset)?,
+                udata(gimli::DW_AT_byte_size)?,
+            ) {
+                (Some(bit_offset), _) if dwarf_endian(dwarf).is_big_endian() => Some(bit_offset),
+                (Some(bit_offset), Some(storage_size)) => {
+                    (storage_size * 8).checked_sub(bit_offset + bit_size)
+                }
+                _ => Some(0),
             };
-            let byte_size = entry
-                .attr_value(gimli::DW_AT_byte_size)?
-                .and_then(|attr| attr.udata_value())
-                .unwrap_or(0);
+            (byte_offset, bit_offset.map(|x| byte_offset * 8 + x))
+        }
+        (None, None) => (member_location, None),
+    };
 
-            Ok(VariableInfo {
-                address_expr: address,
-                byte_size: byte_size as usize,
-                name: type_name,
-                encoding: gimli::DW_ATE_signed,
-                tag,
-                memory_slice: MemorySlice::from_u8_vec(data),
-                state: VariableEvaluationResult::Ready,
-            })
+    Ok(MemberLayout {
+        byte_offset: byte_offset as usize,
+        bit_offset: bit_offset.map(|x| x as usize),
+        bit_size: bit_size.map(|x| x as usize),
+    })
+}
+
+/// Drop the artificial variables `policy` leaves out of a list, along with the
+/// members listed under them
+pub fn hide_artificial(variables: &mut Vec<VariableName>, policy: ArtificialVariables) {
+    let is_hidden = |var: &VariableName| {
+        var.is_artificial
+            && match policy {
+                ArtificialVariables::Show => false,
+                ArtificialVariables::OnlyThis => var.name != "this",
+                ArtificialVariables::Hide => true,
+            }
+    };
+    let mut pending: Vec<i32> = variables
+        .iter()
+        .filter(|var| is_hidden(var))
+        .filter_map(|var| var.child_group_id)
+        .collect();
+    let mut hidden_groups = Vec::new();
+    while let Some(group_id) = pending.pop() {
+        for var in variables.iter() {
+            if var.group_id == group_id {
+                pending.extend(var.child_group_id);
+            }
         }
-        _ => match node.entry().attr_value(gimli::DW_AT_type)? {
-            Some(AttributeValue::UnitRef(ref offset)) => {
-                let mut tree = unit.entries_tree(Some(UnitOffset(offset.0)))?;
-                let root = tree.root()?;
+        hidden_groups.push(group_id);
+    }
+    variables.retain(|var| !is_hidden(var) && !hidden_groups.contains(&var.group_id));
+}
+
+/// Keep the first variable of each name in a group, dropping the others with
+/// their members, and move the contents of repeated namespaces into the first
+fn deduplicate_variables(variables: Vec<VariableName>, root_id: i32) -> Vec<VariableName> {
+    let mut groups: HashMap<i32, Vec<usize>> = HashMap::new();
+    for (index, variable) in variables.iter().enumerate() {
+        groups.entry(variable.group_id).or_default().push(index);
+    }
 
-                create_variable_info(root, address, Some(data), dwarf, unit)
+    let is_namespace = |variable: &VariableName| variable.type_name == "namespace";
+    let mut kept = HashMap::new();
+    let mut first_of_name: HashMap<(i32, &str), usize> = HashMap::new();
+    // groups to walk, with the group their variables end up in
+    let mut pending = VecDeque::from(vec![(root_id, root_id)]);
+    while let Some((group_id, target_id)) = pending.pop_front() {
+        for &index in groups.get(&group_id).into_iter().flatten() {
+            let variable = &variables[index];
+            let name = variable
+                .linkage_name
+                .as_deref()
+                .unwrap_or(&variable.display_name);
+            match first_of_name.get(&(target_id, name)) {
+                Some(&first) => {
+                    let first = &variables[first];
+                    if let (true, true, Some(child), Some(first_child)) = (
+                        is_namespace(variable),
+                        is_namespace(first),
+                        variable.child_group_id,
+                        first.child_group_id,
+                    ) {
+                        pending.push_back((child, first_child));
+                    }
+                }
+                None => {
+                    first_of_name.insert((target_id, name), index);
+                    kept.insert(index, target_id);
+                    if let Some(child) = variable.child_group_id {
+                        pending.push_back((child, child));
+                    }
+                }
             }
-            _ => Err(anyhow!("unsupported DIE type")),
-        },
+        }
     }
+
+    variables
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, mut variable)| {
+            variable.group_id = *kept.get(&index)?;
+            Some(variable)
+        })
+        .collect()
 }
 
 pub struct DwarfGlobalVariables {
@@ -559,23 +2569,33 @@ impl DwarfGlobalVariables {
         unit_offset: UnitSectionOffset,
         root_id: i32,
     ) -> Result<Vec<VariableName>> {
-        let (dwarf, unit) = match self.dwarf_data.unit_offset(unit_offset)? {
+        let unit_data = match self.dwarf_data.unit_offset(unit_offset)? {
             Some(x) => x,
             None => {
                 return Ok(Vec::new());
             }
         };
+        let (dwarf, unit, types) = &*unit_data;
 
-        let mut variables = variables_in_unit_entry(&dwarf, &unit, None, 0, root_id)?;
+        let mut variables = variables_in_unit_entry(dwarf, unit, None, 0, root_id)?;
         let list = variables
             .iter_mut()
             .map(|var| {
                 let mut v = VariableName {
                     name: "<<not parsed yet>>".to_string(),
                     display_name: "<<not parsed yet>>".to_string(),
+                    linkage_name: var.linkage_name.take(),
                     type_name: "<<not parsed yet>>".to_string(),
                     group_id: var.group_id,
                     child_group_id: var.child_group_id,
+                    layout: None,
+                    die_offset: var.die_offset,
+                    unit_offset: var.die_offset.map(|_| unit_section_offset(unit)),
+                    decl_file: var.decl_file.take(),
+                    decl_line: var.decl_line,
+                    is_parameter: var.is_parameter,
+                    is_artificial: var.is_artificial,
+                    available_ranges: None,
                 };
                 if let Some(ref mut name) = var.name {
                     v.name = std::mem::take(name);
@@ -585,7 +2605,7 @@ impl DwarfGlobalVariables {
                 }
                 match &var.ty_offset {
                     TypeDescripter::TypeOffset(offset) => {
-                        if let Ok(ty_name) = unit_type_name(&dwarf, &unit, Some(*offset)) {
+                        if let Ok(ty_name) = types.type_name(dwarf, unit, Some(*offset)) {
                             v.type_name = ty_name;
                         }
                     }
@@ -601,30 +2621,522 @@ impl DwarfGlobalVariables {
         Ok(list)
     }
 
+    /// Globals of every unit, listed without a pc
+    ///
+    /// A global declared in several units is listed once, and namespaces of
+    /// the same name are merged.
+    pub fn all_variable_names(&self, root_id: i32) -> Result<Vec<VariableName>> {
+        let dwarf = self.dwarf_data.parse_dwarf()?;
+        let mut variables = Vec::new();
+        let mut next_group_id = (root_id - 1000 + 1) * 10000;
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            // child groups of each unit are numbered from the same block of ids
+            let mut renumbered = HashMap::new();
+            let mut renumber = |group_id: i32| {
+                if group_id == root_id {
+                    return group_id;
+                }
+                *renumbered.entry(group_id).or_insert_with(|| {
+                    next_group_id += 1;
+                    next_group_id
+                })
+            };
+            for mut variable in self.variable_name_list(header.offset(), root_id)? {
+                variable.group_id = renumber(variable.group_id);
+                variable.child_group_id = variable.child_group_id.map(&mut renumber);
+                variables.push(variable);
+            }
+        }
+        Ok(deduplicate_variables(variables, root_id))
+    }
+
     fn display_variable(
         &self,
         unit_offset: UnitSectionOffset,
         frame_base: FrameBase,
-        name: &String,
+        frame: &WasmFrame,
+        query: &VariableQuery,
     ) -> Result<Option<VariableInfo>> {
-        let (dwarf, unit) = match self.dwarf_data.unit_offset(unit_offset)? {
+        let unit_data = match self.dwarf_data.unit_offset(unit_offset)? {
+            Some(x) => x,
+            None => {
+                return Ok(None);
+            }
+        };
+        let (dwarf, unit, _) = &*unit_data;
+        let variables = variables_in_unit_entry(dwarf, unit, None, 0, 0)?;
+
+        evaluate_variable(query, &variables, &unit_data, 0, frame_base, frame)
+    }
+
+    /// Unit whose DIEs span an offset into `.debug_info`
+    pub fn unit_containing(&self, die_offset: usize) -> Result<Option<UnitSectionOffset>> {
+        let dwarf = self.dwarf_data.parse_dwarf()?;
+        let offset = gimli::DebugInfoOffset(die_offset);
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            if offset.to_unit_offset(&header).is_some() {
+                return Ok(Some(header.offset()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// `DW_AT_location` expression of a global variable
+    pub fn raw_variable_location(
+        &self,
+        query: &VariableQuery,
+        unit_offset: UnitSectionOffset,
+    ) -> Result<Option<RawLocation>> {
+        let unit_data = match self.dwarf_data.unit_offset(unit_offset)? {
             Some(x) => x,
             None => {
                 return Ok(None);
             }
         };
-        let variables = variables_in_unit_entry(&dwarf, &unit, None, 0, 0)?;
+        let (dwarf, unit, _) = &*unit_data;
+        let variables = variables_in_unit_entry(dwarf, unit, None, 0, 0)?;
 
-        evaluate_variable_from_string(name, &variables, &dwarf, &unit, frame_base)
+        raw_variable_location(query, &variables, &unit_data, 0)
     }
 
+    /// Evaluate a global variable, with only the globals and memory of `frame`
     pub fn get_variable_info(
         &self,
-        opts: &String,
+        query: &VariableQuery,
         unit_offset: UnitSectionOffset,
-        data_base: usize,
-        _globals: &WasmValueVector,
+        frame: &WasmFrame,
     ) -> Result<Option<VariableInfo>> {
-        self.display_variable(unit_offset, FrameBase::WasmDataBase(data_base as u64), opts)
+        let empty = WasmValueVector::new();
+        let frame = WasmFrame {
+            locals: &empty,
+            stacks: &empty,
+            ..*frame
+        };
+
+        self.display_variable(unit_offset, FrameBase::Unavailable, &frame, query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dwarf::wasm_bindings::WasmValue;
+    use gimli::{EndianSlice, LittleEndian};
+
+    const ENCODING: gimli::Encoding = gimli::Encoding {
+        format: gimli::Format::Dwarf32,
+        version: 4,
+        address_size: 4,
+    };
+
+    fn global_address(expr: &[u8], data_base: u64) -> Result<u64> {
+        let empty = WasmValueVector::new();
+        let frame = WasmFrame {
+            locals: &empty,
+            globals: &empty,
+            stacks: &empty,
+            data_base,
+            tls_base: None,
+            memory: None,
+            registers: None,
+        };
+        let expr = Expression(EndianSlice::new(expr, LittleEndian));
+        let pieces =
+            evaluate_variable_location(ENCODING, None, &FrameBase::Unavailable, &frame, expr)?;
+        match pieces[0].location {
+            gimli::Location::Address { address } => Ok(address),
+            ref x => Err(anyhow!("unexpected location: {:?}", x)),
+        }
+    }
+
+    fn local_location(
+        expr: &[u8],
+        local: i32,
+    ) -> Result<gimli::Location<EndianSlice<'_, LittleEndian>>> {
+        let empty = WasmValueVector::new();
+        let mut locals = WasmValueVector::new();
+        locals.push(WasmValue::from_i32(local));
+        let frame = WasmFrame {
+            locals: &locals,
+            globals: &empty,
+            stacks: &empty,
+            data_base: 0,
+            tls_base: None,
+            memory: None,
+            registers: None,
+        };
+        let expr = Expression(EndianSlice::new(expr, LittleEndian));
+        let mut pieces =
+            evaluate_variable_location(ENCODING, None, &FrameBase::Unavailable, &frame, expr)?;
+        Ok(pieces.remove(0).location)
+    }
+
+    fn addr(address: u32) -> Vec<u8> {
+        let mut expr = vec![gimli::DW_OP_addr.0];
+        expr.extend_from_slice(&address.to_le_bytes());
+        expr
+    }
+
+    #[test]
+    fn local_value() {
+        let expr = [0xed, 0x00, 0x00, gimli::DW_OP_stack_value.0];
+        assert!(matches!(
+            local_location(&expr, 7).unwrap(),
+            gimli::Location::Value { .. }
+        ));
+    }
+
+    #[test]
+    fn local_address() {
+        assert!(matches!(
+            local_location(&[0xed, 0x00, 0x00], 0x1000).unwrap(),
+            gimli::Location::Address { address: 0x1000 }
+        ));
+    }
+
+    #[test]
+    fn static_int() {
+        // static int g = 5; in a module linked with its data at 0x400
+        assert_eq!(global_address(&addr(0x436), 0).unwrap(), 0x436);
+    }
+
+    #[test]
+    fn relocated_address() {
+        // a side module loaded with its `__memory_base` at 0x400
+        assert_eq!(global_address(&addr(0x10), 0x400).unwrap(), 0x410);
+    }
+
+    #[test]
+    fn member_offset() {
+        let mut expr = addr(0x100004);
+        expr.extend_from_slice(&[gimli::DW_OP_plus_uconst.0, 0x04]);
+        assert_eq!(global_address(&expr, 0).unwrap(), 0x100008);
+    }
+
+    #[test]
+    fn thread_local_address() {
+        let empty = WasmValueVector::new();
+        let mut globals = WasmValueVector::new();
+        globals.push(WasmValue::from_i32(0x1000));
+        globals.push(WasmValue::from_i32(0x2000));
+        let frame = WasmFrame {
+            locals: &empty,
+            globals: &globals,
+            stacks: &empty,
+            data_base: 0,
+            tls_base: Some(1),
+            memory: None,
+            registers: None,
+        };
+        let address = |expr: &[u8], frame: &WasmFrame| {
+            let expr = Expression(EndianSlice::new(expr, LittleEndian));
+            let pieces =
+                evaluate_variable_location(ENCODING, None, &FrameBase::Unavailable, frame, expr)?;
+            match pieces[0].location {
+                gimli::Location::Address { address } => Ok(address),
+                ref x => Err(anyhow!("unexpected location: {:?}", x)),
+            }
+        };
+
+        // llc's DW_OP_const4u 4, DW_OP_GNU_push_tls_address
+        let push_tls = [
+            0x0c,
+            0x04,
+            0x00,
+            0x00,
+            0x00,
+            gimli::DW_OP_GNU_push_tls_address.0,
+        ];
+        assert_eq!(address(&push_tls, &frame).unwrap(), 0x2004);
+        // or the base global pushed with DW_OP_WASM_location 0x03
+        let global_base = [0xed, 0x03, 0x01, 0x00, 0x00, 0x00, 0x23, 0x04];
+        assert_eq!(address(&global_base, &frame).unwrap(), 0x2004);
+
+        let unnamed = WasmFrame {
+            tls_base: None,
+            ..frame
+        };
+        let err = address(&push_tls, &unnamed).unwrap_err();
+        assert_eq!(BridgeError::from_anyhow(err).kind(), "Unsupported");
+    }
+
+    #[test]
+    fn register_relative() {
+        let empty = WasmValueVector::new();
+        let frame = WasmFrame {
+            locals: &empty,
+            globals: &empty,
+            stacks: &empty,
+            data_base: 0,
+            tls_base: None,
+            memory: None,
+            registers: None,
+        };
+        let address = |expr: &[u8], frame: &WasmFrame| {
+            let expr = Expression(EndianSlice::new(expr, LittleEndian));
+            let pieces =
+                evaluate_variable_location(ENCODING, None, &FrameBase::Unavailable, frame, expr)?;
+            match pieces[0].location {
+                gimli::Location::Address { address } => Ok(address),
+                ref x => Err(anyhow!("unexpected location: {:?}", x)),
+            }
+        };
+        // DW_OP_breg5 -8, and DW_OP_bregx 70 +16
+        let breg = [gimli::DW_OP_breg5.0, 0x78];
+        let bregx = [gimli::DW_OP_bregx.0, 70, 0x10];
+
+        let err = address(&breg, &frame).unwrap_err();
+        assert_eq!(BridgeError::from_anyhow(err).kind(), "Unavailable");
+
+        let read = |register: u16| match register {
+            5 => Some(0x1000),
+            70 => Some(0x2000),
+            _ => None,
+        };
+        let frame = WasmFrame {
+            registers: Some(&read),
+            ..frame
+        };
+        assert_eq!(address(&breg, &frame).unwrap(), 0xff8);
+        assert_eq!(address(&bregx, &frame).unwrap(), 0x2010);
+        // registers the caller doesn't know stay unavailable
+        let err = address(&[gimli::DW_OP_breg6.0, 0], &frame).unwrap_err();
+        assert_eq!(BridgeError::from_anyhow(err).kind(), "Unavailable");
+    }
+
+    #[test]
+    fn artificial_variables() {
+        let variable = |name: &str, group_id, child_group_id| VariableName {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            linkage_name: None,
+            type_name: "int".to_string(),
+            group_id,
+            child_group_id,
+            layout: None,
+            die_offset: None,
+            unit_offset: None,
+            decl_file: None,
+            decl_line: None,
+            is_parameter: false,
+            is_artificial: group_id == 1000 && name != "x",
+            available_ranges: None,
+        };
+        let variables = vec![
+            variable("this", 1000, Some(1)),
+            variable("_vptr", 1000, Some(2)),
+            variable("x", 1000, None),
+            variable("this.y", 1, None),
+            variable("_vptr.entry", 2, Some(3)),
+            variable("_vptr.entry.z", 3, None),
+        ];
+        let listed = |policy| {
+            let mut variables = variables.clone();
+            hide_artificial(&mut variables, policy);
+            variables.into_iter().map(|v| v.name).collect::<Vec<_>>()
+        };
+        assert_eq!(listed(ArtificialVariables::Show).len(), variables.len());
+        assert_eq!(
+            listed(ArtificialVariables::OnlyThis),
+            ["this", "x", "this.y"]
+        );
+        assert_eq!(listed(ArtificialVariables::Hide), ["x"]);
+    }
+
+    #[test]
+    fn typed_operations() {
+        use crate::dwarf::load_custom_sections;
+        use gimli::{DebugInfoOffset, EndianRcSlice};
+
+        const TYPED: &[u8] = include_bytes!("../../tests/fixtures/typed.wasm");
+        let sections = load_custom_sections(TYPED, 0).unwrap();
+        let dwarf_data = DwarfDebugData::from_sections(sections, None).unwrap();
+        let offset = UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0));
+        let unit_data = dwarf_data.unit_offset(offset).unwrap().unwrap();
+        let unit = &unit_data.1;
+
+        let mut memory_bytes = WasmValueVector::new();
+        memory_bytes.push(WasmValue::from_i32(0x1000));
+        let read = |address: u64, size: usize| match address {
+            0x1000 => Some((-2i32).to_le_bytes()[..size].to_vec()),
+            _ => None,
+        };
+        let frame = WasmFrame {
+            locals: &memory_bytes,
+            globals: &memory_bytes,
+            stacks: &memory_bytes,
+            data_base: 0,
+            tls_base: None,
+            memory: Some(&read),
+            registers: None,
+        };
+        let location = |expr: &[u8]| {
+            let expr = Expression(EndianRcSlice::new(expr.into(), RunTimeEndian::Little));
+            evaluate_variable_location(
+                unit.encoding(),
+                Some((&unit_data.0, unit)),
+                &FrameBase::Unavailable,
+                &frame,
+                expr,
+            )
+            .map(|pieces| match pieces[0].location {
+                gimli::Location::Value { value } => Some(value),
+                _ => None,
+            })
+        };
+
+        // DW_ATE_signed_32 is at 0x27 and DW_ATE_unsigned_64 at 0x2f
+        let const_type = [0xa4, 0x27, 0x04, 0xfe, 0xff, 0xff, 0xff, 0x9f];
+        assert_eq!(location(&const_type).unwrap(), Some(gimli::Value::I32(-2)));
+        let convert = [0xa4, 0x27, 0x04, 0xfe, 0xff, 0xff, 0xff, 0xa8, 0x2f, 0x9f];
+        assert_eq!(
+            location(&convert).unwrap(),
+            Some(gimli::Value::U64(-2i64 as u64))
+        );
+        let deref_type = [0xed, 0x00, 0x00, 0xa6, 0x04, 0x27, 0x9f];
+        assert_eq!(location(&deref_type).unwrap(), Some(gimli::Value::I32(-2)));
+
+        // wasm has no registers to read, and unknown operations aren't guessed
+        for expr in [&[0xa5, 0x00, 0x27, 0x9f][..], &[0xfe, 0x9f][..]] {
+            let err = location(expr).unwrap_err();
+            assert_eq!(BridgeError::from_anyhow(err).kind(), "Unavailable");
+        }
+    }
+
+    #[test]
+    fn member_location_expressions() {
+        let exprloc = |expr: &'static [u8]| {
+            AttributeValue::Exprloc(Expression(EndianSlice::new(expr, LittleEndian)))
+        };
+        let plus_uconst = exprloc(&[gimli::DW_OP_plus_uconst.0, 0x08]);
+        assert_eq!(
+            constant_member_offset(ENCODING, &plus_uconst).unwrap(),
+            Some(8)
+        );
+        let constant = AttributeValue::<EndianSlice<LittleEndian>>::Udata(4);
+        assert_eq!(
+            constant_member_offset(ENCODING, &constant).unwrap(),
+            Some(4)
+        );
+
+        // a virtual base is found through the vtable the object points to,
+        // whose entry 12 bytes before the address point holds the offset
+        let virtual_base: &[u8] = &[
+            gimli::DW_OP_dup.0,
+            gimli::DW_OP_deref.0,
+            gimli::DW_OP_constu.0,
+            12,
+            gimli::DW_OP_minus.0,
+            gimli::DW_OP_deref.0,
+            gimli::DW_OP_plus.0,
+        ];
+        assert_eq!(
+            constant_member_offset(ENCODING, &exprloc(virtual_base)).unwrap(),
+            None
+        );
+
+        let read = |address: u64, size: usize| match (address, size) {
+            (0x1000, 4) => Some(0x2010u32.to_le_bytes().to_vec()),
+            (0x2004, 4) => Some(16u32.to_le_bytes().to_vec()),
+            _ => None,
+        };
+        let empty = WasmValueVector::new();
+        let frame = WasmFrame {
+            locals: &empty,
+            globals: &empty,
+            stacks: &empty,
+            data_base: 0,
+            tls_base: None,
+            memory: Some(&read),
+            registers: None,
+        };
+        let expr = Expression(EndianSlice::new(virtual_base, LittleEndian));
+        let pieces = evaluate_location_part(ENCODING, None, &FrameBase::RBP(0x1000), &frame, expr);
+        assert!(matches!(
+            pieces.unwrap()[0].location,
+            gimli::Location::Address { address: 0x1010 }
+        ));
+        assert!(matches!(
+            object_address(
+                &[
+                    VariableLocation::Address(0x1000),
+                    VariableLocation::Offset(8)
+                ],
+                4
+            ),
+            Some(0x1008)
+        ));
+        assert!(object_address(&[VariableLocation::Pointer(4)], 4).is_none());
+    }
+
+    #[test]
+    fn discarded_global() {
+        let error = global_address(&addr(u32::MAX), 0x400).unwrap_err();
+        assert!(matches!(
+            BridgeError::from_anyhow(error),
+            BridgeError::OptimizedOut(_)
+        ));
+    }
+
+    fn split_value(expr: &[u8], locals: &[i32]) -> Result<(Vec<u8>, Vec<Range<usize>>)> {
+        let empty = WasmValueVector::new();
+        let mut local_values = WasmValueVector::new();
+        for local in locals {
+            local_values.push(WasmValue::from_i32(*local));
+        }
+        let frame = WasmFrame {
+            locals: &local_values,
+            globals: &empty,
+            stacks: &empty,
+            data_base: 0,
+            tls_base: None,
+            memory: None,
+            registers: None,
+        };
+        let expr = Expression(EndianSlice::new(expr, LittleEndian));
+        let pieces =
+            evaluate_variable_location(ENCODING, None, &FrameBase::Unavailable, &frame, expr)?;
+        assemble_pieces(&pieces)
+    }
+
+    // `let p = Pt { x: a, y: b };` of `#[repr(C)] struct Pt { x: i32, y: i32 }`
+    // at opt-level=2 keeps `x` in local 0 and `y` in local 1
+    const LOCAL_X: [u8; 4] = [0xed, 0x00, 0x00, 0x9f];
+    const LOCAL_Y: [u8; 4] = [0xed, 0x00, 0x01, 0x9f];
+    const PIECE_4: [u8; 2] = [0x93, 0x04];
+
+    #[test]
+    fn split_struct() {
+        let expr = [&LOCAL_X[..], &PIECE_4, &LOCAL_Y, &PIECE_4].concat();
+        let (bytes, missing) = split_value(&expr, &[7, -2]).unwrap();
+        assert_eq!(bytes, [7, 0, 0, 0, 0xfe, 0xff, 0xff, 0xff]);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn optimized_out_piece() {
+        // `x` is gone while `y` still lives in local 1
+        let expr = [&PIECE_4[..], &LOCAL_Y, &PIECE_4].concat();
+        let (bytes, missing) = split_value(&expr, &[7, 9]).unwrap();
+        assert_eq!(bytes, [0, 0, 0, 0, 9, 0, 0, 0]);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0], 0..32);
+    }
+
+    #[test]
+    fn bit_pieces() {
+        // the low nibble of local 0 followed by bits 8..12 of local 1
+        let expr = [
+            &LOCAL_X[..],
+            &[gimli::DW_OP_bit_piece.0, 0x04, 0x00],
+            &LOCAL_Y,
+            &[gimli::DW_OP_bit_piece.0, 0x04, 0x08],
+        ]
+        .concat();
+        let (bytes, missing) = split_value(&expr, &[0x35, 0x0a00]).unwrap();
+        assert_eq!(bytes, [0xa5]);
+        assert!(missing.is_empty());
     }
 }
@@ -1,5 +1,7 @@
-use super::sourcemap::{ColumnType, LineInfo};
+use super::sourcemap::{ColumnType, DwarfSourceMap, LineInfo};
+use super::subroutine::{InlinedSubroutine, Subroutine, VariableScope};
 use super::variables::VariableName;
+use super::{format_variable_list, Scalar, VariableInfo};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::*;
 
@@ -11,6 +13,26 @@ pub enum Value {
     F64(f64),
 }
 
+impl Value {
+    pub(crate) fn to_bits(self) -> u64 {
+        match self {
+            Value::I32(v) => v as u32 as u64,
+            Value::I64(v) => v as u64,
+            Value::F32(v) => v.to_bits() as u64,
+            Value::F64(v) => v.to_bits(),
+        }
+    }
+
+    pub(crate) fn to_gimli_value(self) -> gimli::Value {
+        match self {
+            Value::I32(v) => gimli::Value::I32(v),
+            Value::I64(v) => gimli::Value::I64(v),
+            Value::F32(v) => gimli::Value::F32(v),
+            Value::F64(v) => gimli::Value::F64(v),
+        }
+    }
+}
+
 #[wasm_bindgen]
 pub struct WasmValue {
     pub(crate) value: Value,
@@ -96,13 +118,65 @@ impl WasmLineInfo {
             filepath: info.filepath.clone(),
             line: info.line.map(|x| x as u64),
             column: match info.column {
+                Some(0) | None => ColumnType::LeftEdge,
                 Some(x) => ColumnType::Column(x as u64),
-                None => ColumnType::LeftEdge,
             },
         }
     }
 }
 
+/// Where a breakpoint requested on a line without code stops instead
+#[wasm_bindgen]
+pub struct ResolvedBreakpoint {
+    pub(crate) filepath: String,
+    pub line: usize,
+    /// Instruction offset of the line's first statement
+    pub address: usize,
+}
+
+#[wasm_bindgen]
+impl ResolvedBreakpoint {
+    pub fn file(&self) -> String {
+        self.filepath.clone()
+    }
+}
+
+/// Location expression of a variable, left for the caller to evaluate
+#[wasm_bindgen]
+pub struct RawLocation {
+    pub(crate) expression: Vec<u8>,
+    /// Whether the expression is the entry of a location list covering the pc
+    pub is_location_list: bool,
+}
+
+#[wasm_bindgen]
+impl RawLocation {
+    /// Bytes of the DWARF expression, operands included
+    pub fn expression(&self) -> Vec<u8> {
+        self.expression.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct LineInfoVector {
+    data: Vec<Option<LineInfo>>,
+}
+
+#[wasm_bindgen]
+impl LineInfoVector {
+    pub(crate) fn from_vec(data: Vec<Option<LineInfo>>) -> Self {
+        Self { data }
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn at(&self, index: usize) -> Option<WasmLineInfo> {
+        self.data[index].as_ref().map(WasmLineInfo::from_line_info)
+    }
+}
+
 #[wasm_bindgen]
 pub struct VariableVector {
     data: Vec<VariableName>,
@@ -114,6 +188,32 @@ impl VariableVector {
         Self { data }
     }
 
+    /// Move the available ranges from code offsets to instruction offsets
+    pub(crate) fn relocate(mut self, instruction_base: usize) -> Self {
+        let base = instruction_base as u64;
+        for ranges in self
+            .data
+            .iter_mut()
+            .filter_map(|v| v.available_ranges.as_mut())
+        {
+            for range in ranges.iter_mut() {
+                *range = range.start + base..range.end + base;
+            }
+        }
+        self
+    }
+
+    /// Apply the path substitutions of the source map to the declaring files
+    pub(crate) fn substitute_paths(mut self, sourcemap: &DwarfSourceMap) -> Self {
+        for variable in &mut self.data {
+            variable.decl_file = variable
+                .decl_file
+                .as_deref()
+                .map(|path| sourcemap.substitute_path(path));
+        }
+        self
+    }
+
     pub fn size(&self) -> usize {
         self.data.len()
     }
@@ -126,6 +226,11 @@ impl VariableVector {
         self.data[index].display_name.clone()
     }
 
+    /// Raw mangled name of the variable, if any
+    pub fn at_linkage_name(&self, index: usize) -> Option<String> {
+        self.data[index].linkage_name.clone()
+    }
+
     pub fn at_type_name(&self, index: usize) -> String {
         self.data[index].type_name.clone()
     }
@@ -137,4 +242,586 @@ impl VariableVector {
     pub fn at_chile_group_id(&self, index: usize) -> Option<i32> {
         self.data[index].child_group_id
     }
+
+    pub fn at_byte_offset(&self, index: usize) -> Option<usize> {
+        self.data[index].layout.map(|x| x.byte_offset)
+    }
+
+    pub fn at_bit_offset(&self, index: usize) -> Option<usize> {
+        self.data[index].layout.and_then(|x| x.bit_offset)
+    }
+
+    pub fn at_bit_size(&self, index: usize) -> Option<usize> {
+        self.data[index].layout.and_then(|x| x.bit_size)
+    }
+
+    /// DIE offset to pass to `get_variable_info_by_offset`, absent for members
+    pub fn at_die_offset(&self, index: usize) -> Option<usize> {
+        self.data[index].die_offset
+    }
+
+    /// Offset of the unit declaring the variable, which together with the DIE
+    /// offset identifies a variable across calls, e.g. to cache globals by
+    pub fn at_unit_offset(&self, index: usize) -> Option<usize> {
+        self.data[index].unit_offset
+    }
+
+    /// File the variable was declared in, resolved against the unit's line program
+    pub fn at_decl_file(&self, index: usize) -> Option<String> {
+        self.data[index].decl_file.clone()
+    }
+
+    pub fn at_decl_line(&self, index: usize) -> Option<usize> {
+        self.data[index].decl_line.map(|x| x as usize)
+    }
+
+    /// Whether the variable is an argument of the function rather than a local,
+    /// with arguments listed in call order
+    pub fn at_is_parameter(&self, index: usize) -> bool {
+        self.data[index].is_parameter
+    }
+
+    /// Whether the compiler made the variable up, such as `this` or a vtable
+    /// pointer
+    pub fn at_is_artificial(&self, index: usize) -> bool {
+        self.data[index].is_artificial
+    }
+
+    /// Whether the variable has a location wherever it is in scope, rather than
+    /// only over the ranges listed by `at_available_range_start` and
+    /// `at_available_range_end`, e.g. to gray out the others
+    pub fn at_is_available_throughout(&self, index: usize) -> bool {
+        self.data[index].available_ranges.is_none()
+    }
+
+    /// Number of instruction ranges the location list of the variable covers,
+    /// 0 for a variable optimized out altogether
+    pub fn at_available_range_count(&self, index: usize) -> usize {
+        self.data[index]
+            .available_ranges
+            .as_ref()
+            .map_or(0, Vec::len)
+    }
+
+    pub fn at_available_range_start(&self, index: usize, range: usize) -> usize {
+        self.available_range(index, range).start as usize
+    }
+
+    pub fn at_available_range_end(&self, index: usize, range: usize) -> usize {
+        self.available_range(index, range).end as usize
+    }
+
+    fn available_range(&self, index: usize, range: usize) -> &std::ops::Range<u64> {
+        &self.data[index].available_ranges.as_deref().unwrap_or(&[])[range]
+    }
+
+    /// JSON array of `{name, typeName, kind, declFile, declLine,
+    /// availableRanges}` objects, one per variable, so callers don't depend on
+    /// the getters above
+    pub fn to_json(&self) -> String {
+        format_variable_list(&self.data)
+    }
+}
+
+/// Which of the variables the compiler made up, marked `DW_AT_artificial`,
+/// variable lists include
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArtificialVariables {
+    Show,
+    /// Only `this`, the artificial parameter of methods
+    OnlyThis,
+    Hide,
+}
+
+/// Outcome of looking up a variable
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariableStatus {
+    Found,
+    /// The variable is in scope but has no location at the pc
+    OptimizedOut,
+    NotFound,
+    /// Locating the variable needs memory the caller can't read
+    Unavailable,
+}
+
+#[wasm_bindgen]
+pub struct VariableLookup {
+    status: VariableStatus,
+    info: Option<VariableInfo>,
+}
+
+#[wasm_bindgen]
+impl VariableLookup {
+    pub(crate) fn new(status: VariableStatus, info: Option<VariableInfo>) -> Self {
+        Self { status, info }
+    }
+
+    pub fn status(&self) -> VariableStatus {
+        self.status
+    }
+
+    /// The variable to evaluate, set when its status is `Found`
+    pub fn info(&self) -> Option<VariableInfo> {
+        self.info.clone()
+    }
+}
+
+/// What a `ScalarValue` holds, telling which of its getters returns it
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarKind {
+    Signed,
+    Unsigned,
+    Float,
+    Boolean,
+    /// Bytes of a value no other kind fits, such as a 128-bit integer
+    Bytes,
+}
+
+/// Value of an evaluated scalar variable, e.g. for comparing it in a
+/// breakpoint condition without parsing its formatted text
+#[wasm_bindgen]
+pub struct ScalarValue {
+    value: Scalar,
+}
+
+#[wasm_bindgen]
+impl ScalarValue {
+    pub(crate) fn new(value: Scalar) -> Self {
+        Self { value }
+    }
+
+    pub fn kind(&self) -> ScalarKind {
+        match self.value {
+            Scalar::Signed(_) => ScalarKind::Signed,
+            Scalar::Unsigned(_) => ScalarKind::Unsigned,
+            Scalar::Float(_) => ScalarKind::Float,
+            Scalar::Boolean(_) => ScalarKind::Boolean,
+            Scalar::Bytes(_) => ScalarKind::Bytes,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.value {
+            Scalar::Signed(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Unsigned integers, characters and pointers
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.value {
+            Scalar::Unsigned(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.value {
+            Scalar::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            Scalar::Boolean(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Bytes of the value in the target's byte order
+    pub fn as_bytes(&self) -> Option<Vec<u8>> {
+        match self.value {
+            Scalar::Bytes(ref bytes) => Some(bytes.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct VariableInfoVector {
+    data: Vec<VariableInfo>,
+}
+
+#[wasm_bindgen]
+impl VariableInfoVector {
+    pub(crate) fn from_vec(data: Vec<VariableInfo>) -> Self {
+        Self { data }
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn at(&self, index: usize) -> VariableInfo {
+        self.data[index].clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct StringVector {
+    data: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl StringVector {
+    pub(crate) fn from_vec(data: Vec<String>) -> Self {
+        Self { data }
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn at(&self, index: usize) -> String {
+        self.data[index].clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct InlinedFrameVector {
+    data: Vec<InlinedFrame>,
+}
+
+struct InlinedFrame {
+    name: Option<String>,
+    call_file: Option<String>,
+    call_line: Option<u64>,
+}
+
+#[wasm_bindgen]
+impl InlinedFrameVector {
+    pub(crate) fn from_inlined_subroutines(data: Vec<InlinedSubroutine>) -> Self {
+        Self {
+            data: data
+                .into_iter()
+                .map(|x| InlinedFrame {
+                    name: x.name,
+                    call_file: x.call_file,
+                    call_line: x.call_line,
+                })
+                .collect(),
+        }
+    }
+
+    /// Apply the path substitutions of the source map to the call sites
+    pub(crate) fn substitute_paths(mut self, sourcemap: &DwarfSourceMap) -> Self {
+        for frame in &mut self.data {
+            frame.call_file = frame
+                .call_file
+                .as_deref()
+                .map(|path| sourcemap.substitute_path(path));
+        }
+        self
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn at_name(&self, index: usize) -> Option<String> {
+        self.data[index].name.clone()
+    }
+
+    pub fn at_call_file(&self, index: usize) -> Option<String> {
+        self.data[index].call_file.clone()
+    }
+
+    pub fn at_call_line(&self, index: usize) -> Option<usize> {
+        self.data[index].call_line.map(|x| x as usize)
+    }
+}
+
+/// Variables in scope at a pc, grouped by the scope declaring them
+#[wasm_bindgen]
+pub struct ScopeVector {
+    data: Vec<VariableScope>,
+}
+
+#[wasm_bindgen]
+impl ScopeVector {
+    pub(crate) fn from_vec(data: Vec<VariableScope>) -> Self {
+        Self { data }
+    }
+
+    /// Move the available ranges from code offsets to instruction offsets
+    pub(crate) fn relocate(mut self, instruction_base: usize) -> Self {
+        for scope in &mut self.data {
+            let variables = std::mem::take(&mut scope.variables);
+            scope.variables = VariableVector::from_vec(variables)
+                .relocate(instruction_base)
+                .data;
+        }
+        self
+    }
+
+    /// Apply the path substitutions of the source map to the declaring files
+    pub(crate) fn substitute_paths(mut self, sourcemap: &DwarfSourceMap) -> Self {
+        for scope in &mut self.data {
+            let variables = std::mem::take(&mut scope.variables);
+            scope.variables = VariableVector::from_vec(variables)
+                .substitute_paths(sourcemap)
+                .data;
+        }
+        self
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// `Block`, `Local`, `Closure` or `Global`
+    pub fn at_label(&self, index: usize) -> String {
+        self.data[index].label.to_string()
+    }
+
+    /// Group the scope's variables are listed under, which their members
+    /// aren't
+    pub fn at_group_id(&self, index: usize) -> i32 {
+        self.data[index].group_id
+    }
+
+    pub fn at_variables(&self, index: usize) -> VariableVector {
+        VariableVector::from_vec(self.data[index].variables.clone())
+    }
+}
+
+#[wasm_bindgen]
+pub struct FunctionVector {
+    data: Vec<FunctionEntry>,
+}
+
+struct FunctionEntry {
+    name: Option<String>,
+    linkage_name: Option<String>,
+    low_pc: usize,
+    high_pc: usize,
+    decl_file: Option<String>,
+    decl_line: Option<u64>,
+}
+
+#[wasm_bindgen]
+impl FunctionVector {
+    /// One entry per address range, so non-contiguous functions appear several times
+    pub(crate) fn from_subroutines<'a>(
+        data: impl IntoIterator<Item = &'a Subroutine>,
+        code_base: usize,
+    ) -> Self {
+        let mut entries = Vec::new();
+        for subroutine in data {
+            for range in &subroutine.ranges {
+                entries.push(FunctionEntry {
+                    name: subroutine.name.clone(),
+                    linkage_name: subroutine.linkage_name.clone(),
+                    low_pc: range.start as usize + code_base,
+                    high_pc: range.end as usize + code_base,
+                    decl_file: subroutine.decl_file.clone(),
+                    decl_line: subroutine.decl_line,
+                });
+            }
+        }
+        entries.sort_by_key(|x| x.low_pc);
+        Self { data: entries }
+    }
+
+    /// One entry per subroutine, covering the range its entry point starts
+    pub(crate) fn from_entry_points<'a>(
+        data: impl IntoIterator<Item = &'a Subroutine>,
+        code_base: usize,
+    ) -> Self {
+        let entries = data
+            .into_iter()
+            .filter_map(|subroutine| {
+                let range = subroutine.ranges.first()?;
+                Some(FunctionEntry {
+                    name: subroutine.name.clone(),
+                    linkage_name: subroutine.linkage_name.clone(),
+                    low_pc: range.start as usize + code_base,
+                    high_pc: range.end as usize + code_base,
+                    decl_file: subroutine.decl_file.clone(),
+                    decl_line: subroutine.decl_line,
+                })
+            })
+            .collect();
+        Self { data: entries }
+    }
+
+    /// Apply the path substitutions of the source map to the declaring files
+    pub(crate) fn substitute_paths(mut self, sourcemap: &DwarfSourceMap) -> Self {
+        for entry in &mut self.data {
+            entry.decl_file = entry
+                .decl_file
+                .as_deref()
+                .map(|path| sourcemap.substitute_path(path));
+        }
+        self
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn at_name(&self, index: usize) -> Option<String> {
+        self.data[index].name.clone()
+    }
+
+    /// Raw mangled name of the function, if any
+    pub fn at_linkage_name(&self, index: usize) -> Option<String> {
+        self.data[index].linkage_name.clone()
+    }
+
+    pub fn at_low_pc(&self, index: usize) -> usize {
+        self.data[index].low_pc
+    }
+
+    pub fn at_high_pc(&self, index: usize) -> usize {
+        self.data[index].high_pc
+    }
+
+    pub fn at_decl_file(&self, index: usize) -> Option<String> {
+        self.data[index].decl_file.clone()
+    }
+
+    pub fn at_decl_line(&self, index: usize) -> Option<usize> {
+        self.data[index].decl_line.map(|x| x as usize)
+    }
+}
+
+#[wasm_bindgen]
+pub struct AddressRangeVector {
+    data: Vec<std::ops::Range<usize>>,
+}
+
+#[wasm_bindgen]
+impl AddressRangeVector {
+    pub(crate) fn from_ranges(ranges: &[std::ops::Range<u64>], code_base: usize) -> Self {
+        Self {
+            data: ranges
+                .iter()
+                .map(|range| range.start as usize + code_base..range.end as usize + code_base)
+                .collect(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn at_start(&self, index: usize) -> usize {
+        self.data[index].start
+    }
+
+    /// Exclusive end of the range
+    pub fn at_end(&self, index: usize) -> usize {
+        self.data[index].end
+    }
+}
+
+/// Summary of the line tables, telling why addresses in the gaps between
+/// sequences resolve to no line
+#[wasm_bindgen]
+pub struct LineTableCoverage {
+    pub sequences: usize,
+    /// Bytes of code any sequence covers
+    pub covered_bytes: usize,
+    gaps: Vec<std::ops::Range<usize>>,
+}
+
+#[wasm_bindgen]
+impl LineTableCoverage {
+    pub(crate) fn from_sequences(sequences: &[std::ops::Range<u64>], code_base: usize) -> Self {
+        let gaps = sequences
+            .windows(2)
+            .filter(|pair| pair[0].end < pair[1].start)
+            .map(|pair| pair[0].end as usize + code_base..pair[1].start as usize + code_base)
+            .collect();
+        Self {
+            sequences: sequences.len(),
+            covered_bytes: sequences
+                .iter()
+                .map(|range| (range.end - range.start) as usize)
+                .sum(),
+            gaps,
+        }
+    }
+
+    /// Number of gaps between sequences, not counting the code before the
+    /// first or after the last
+    pub fn gap_count(&self) -> usize {
+        self.gaps.len()
+    }
+
+    pub fn at_gap_start(&self, index: usize) -> usize {
+        self.gaps[index].start
+    }
+
+    /// Exclusive end of the gap
+    pub fn at_gap_end(&self, index: usize) -> usize {
+        self.gaps[index].end
+    }
+
+    /// JSON of `{sequences, coveredBytes, gaps}`, with gaps as `[start, end]`
+    /// pairs
+    pub fn to_json(&self) -> String {
+        let gaps: Vec<String> = self
+            .gaps
+            .iter()
+            .map(|gap| format!("[{},{}]", gap.start, gap.end))
+            .collect();
+        format!(
+            "{{\"sequences\":{},\"coveredBytes\":{},\"gaps\":[{}]}}",
+            self.sequences,
+            self.covered_bytes,
+            gaps.join(",")
+        )
+    }
+}
+
+/// Address ranges of source lines, sorted by start
+#[wasm_bindgen]
+pub struct LineRangeVector {
+    data: Vec<(std::ops::Range<usize>, LineInfo)>,
+}
+
+#[wasm_bindgen]
+impl LineRangeVector {
+    pub(crate) fn from_ranges(
+        ranges: Vec<(std::ops::Range<u64>, LineInfo)>,
+        code_base: usize,
+    ) -> Self {
+        Self {
+            data: ranges
+                .into_iter()
+                .map(|(range, info)| {
+                    let range = range.start as usize + code_base..range.end as usize + code_base;
+                    (range, info)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn at_start(&self, index: usize) -> usize {
+        self.data[index].0.start
+    }
+
+    /// Exclusive end of the range
+    pub fn at_end(&self, index: usize) -> usize {
+        self.data[index].0.end
+    }
+
+    pub fn at_file(&self, index: usize) -> String {
+        self.data[index].1.filepath.clone()
+    }
+
+    /// Line of the range, `None` for code the compiler attributes to no line
+    pub fn at_line(&self, index: usize) -> Option<usize> {
+        self.data[index].1.line.map(|x| x as usize)
+    }
 }
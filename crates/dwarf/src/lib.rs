@@ -1,88 +1,626 @@
 use anyhow::Result;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::*;
-use wasmparser::{Parser, Payload};
+use wasmparser::{
+    DataKind, DataSectionReader, ImportSectionEntryType, Name, NameSectionReader, Operator, Payload,
+};
 
 mod dwarf;
 
-use crate::dwarf::utils::error;
-use crate::dwarf::wasm_bindings::{VariableVector, WasmLineInfo, WasmValueVector};
-use crate::dwarf::{transform_dwarf, DwarfDebugInfo, VariableInfo};
+use crate::dwarf::demangle::demangle;
+use crate::dwarf::error::{to_js_error, BridgeError};
+use crate::dwarf::index::{index_module, serialize_index, transform_index};
+use crate::dwarf::subroutine::{VariableScope, WasmLoc};
+use crate::dwarf::utils::{error, module_payloads};
+use crate::dwarf::variables::{hide_artificial, VariableName, VariableQuery, WasmFrame};
+use crate::dwarf::wasm_bindings::{
+    AddressRangeVector, ArtificialVariables, FunctionVector, InlinedFrameVector, LineInfoVector,
+    LineRangeVector, LineTableCoverage, RawLocation, ResolvedBreakpoint, ScopeVector, StringVector,
+    VariableLookup, VariableStatus, VariableVector, WasmLineInfo, WasmValueVector,
+};
+use crate::dwarf::{
+    add_custom_section, format_memory, read_memory_snapshot, transform_dwarf, DwarfDebugData,
+    DwarfDebugInfo, FormatLimits, MemorySlice, SectionMap, SourceLanguage, VariableInfo,
+};
+
+/// Largest read `prepare_read` asks the bridge for
+const MAX_READ_BYTES: usize = 4096;
 
 #[wasm_bindgen]
 pub struct DwarfDebugSymbolContainer {
     debug_info: DwarfDebugInfo,
+    module_index: usize,
     code_base: usize,
+    runtime_offset: usize,
     data_base: usize,
+    tls_base_global: Option<u32>,
+    memory_base_global: Option<u32>,
+    function_names: WasmFunctionNames,
+    format_limits: FormatLimits,
+    artificial_variables: ArtificialVariables,
+    /// Linear memory captured up front, for post-mortem debugging
+    memory_snapshot: Option<Rc<[u8]>>,
+    /// Reads `memory_snapshot` for frames given no memory callback
+    snapshot_reader: Option<JsMemoryReader>,
 }
 
 #[wasm_bindgen]
 impl DwarfDebugSymbolContainer {
-    pub fn new(data: &[u8]) -> Self {
-        let base = calculate_code_base(data).ok().unwrap_or((0, 0));
+    pub fn new(data: &[u8]) -> Result<DwarfDebugSymbolContainer, JsValue> {
+        Self::load(data, None, 0).map_err(to_js_error)
+    }
+
+    /// Load the debug info of one module of a binary holding nested modules
+    ///
+    /// The outermost module is 0, and nested modules are numbered in the order
+    /// their module sections appear. Addresses passed to and returned by the
+    /// container are relative to that module's code section.
+    pub fn new_for_module(
+        data: &[u8],
+        module_index: usize,
+    ) -> Result<DwarfDebugSymbolContainer, JsValue> {
+        Self::load(data, None, module_index).map_err(to_js_error)
+    }
+
+    /// Load a binary built with `-gsplit-dwarf` together with its `.dwo` or
+    /// `.dwp` companion
+    pub fn new_with_split_dwarf(
+        data: &[u8],
+        split_data: &[u8],
+    ) -> Result<DwarfDebugSymbolContainer, JsValue> {
+        Self::load(data, Some(split_data), 0).map_err(to_js_error)
+    }
+
+    /// Load a binary whose debug sections were stripped and saved apart, given
+    /// as a `Map` of section names such as `.debug_info` to `Uint8Array`s
+    ///
+    /// The binary is still read for its code section and function names, and
+    /// the given sections take the place of its own. At least `.debug_info` and
+    /// `.debug_abbrev` must be given.
+    pub fn from_debug_sections(
+        data: &[u8],
+        sections: js_sys::Map,
+    ) -> Result<DwarfDebugSymbolContainer, JsValue> {
+        js_section_map(&sections)
+            .and_then(|sections| Self::load_with_sections(data, sections))
+            .map_err(to_js_error)
+    }
 
+    /// Load a binary for post-mortem debugging with the whole linear memory
+    /// captured up front, e.g. from a crash report
+    ///
+    /// Variables then dereference pointers and read strings from `memory` as
+    /// they are evaluated, without asking for memory slices or calling
+    /// `read_memory`. Only reads past the end of `memory` are still asked for.
+    pub fn new_with_memory_snapshot(
+        data: &[u8],
+        memory: Box<[u8]>,
+    ) -> Result<DwarfDebugSymbolContainer, JsValue> {
+        let mut container = Self::load(data, None, 0).map_err(to_js_error)?;
+        container.set_memory_snapshot(Rc::from(memory));
+        Ok(container)
+    }
+
+    /// Load a binary from an index written by `serialize_index`, skipping the
+    /// parse of its line tables and unit ranges
+    ///
+    /// Fails when the index was written by another version of this crate or
+    /// for a different binary, in which case the caller should load the binary
+    /// with `new` and write a new index. Binaries with split DWARF aren't
+    /// supported.
+    pub fn from_index(index: &[u8], data: &[u8]) -> Result<DwarfDebugSymbolContainer, JsValue> {
+        let module_index = index_module(index).map_err(to_js_error)?;
+        let mut module = scan_module(data, module_index).map_err(to_js_error)?;
+        let sections = std::mem::take(&mut module.sections);
+        let debug_info = DwarfDebugData::from_sections(sections, None)
+            .and_then(|dwarf_data| transform_index(index, dwarf_data))
+            .map_err(to_js_error)?;
+        Ok(Self::with_debug_info(module, debug_info, module_index))
+    }
+
+    /// Encode the line tables and unit ranges for `from_index`, e.g. to cache
+    /// them across page reloads
+    ///
+    /// Path substitutions are not part of the index.
+    pub fn serialize_index(&self) -> Vec<u8> {
+        serialize_index(&self.debug_info, self.module_index)
+    }
+
+    fn load(
+        data: &[u8],
+        split_data: Option<&[u8]>,
+        module_index: usize,
+    ) -> Result<DwarfDebugSymbolContainer> {
+        let mut module = scan_module(data, module_index)?;
+        let sections = std::mem::take(&mut module.sections);
+        let debug_info =
+            DwarfDebugData::from_sections(sections, split_data).and_then(transform_dwarf)?;
+        Ok(Self::with_debug_info(module, debug_info, module_index))
+    }
+
+    fn load_with_sections(
+        data: &[u8],
+        debug_sections: SectionMap,
+    ) -> Result<DwarfDebugSymbolContainer> {
+        for name in [".debug_info", ".debug_abbrev"].iter() {
+            if !debug_sections.contains_key(*name) {
+                Err(BridgeError::InvalidDwarf(format!(
+                    "the {} section is missing",
+                    name
+                )))?
+            }
+        }
+        let mut module = scan_module(data, 0)?;
+        let mut sections = std::mem::take(&mut module.sections);
+        sections.extend(debug_sections);
+        let debug_info = DwarfDebugData::from_sections(sections, None).and_then(transform_dwarf)?;
+        Ok(Self::with_debug_info(module, debug_info, 0))
+    }
+
+    fn with_debug_info(
+        module: WasmModule,
+        debug_info: DwarfDebugInfo,
+        module_index: usize,
+    ) -> DwarfDebugSymbolContainer {
         DwarfDebugSymbolContainer {
-            code_base: base.0,
-            data_base: base.1,
-            debug_info: transform_dwarf(data).unwrap(),
+            code_base: module.code_base,
+            runtime_offset: 0,
+            data_base: module.data_base,
+            tls_base_global: module.tls_base_global,
+            memory_base_global: module.memory_base_global,
+            debug_info,
+            module_index,
+            function_names: module.function_names,
+            format_limits: FormatLimits::default(),
+            artificial_variables: ArtificialVariables::OnlyThis,
+            memory_snapshot: None,
+            snapshot_reader: None,
         }
     }
 
+    /// Evaluate variables against `snapshot` from now on
+    fn set_memory_snapshot(&mut self, snapshot: Rc<[u8]>) {
+        let reader = snapshot.clone();
+        self.snapshot_reader = Some(Box::new(move |address, size| {
+            read_memory_snapshot(&reader, address, size)
+        }));
+        self.memory_snapshot = Some(snapshot);
+    }
+
+    /// Offset of the module's code section in the binary, which instruction
+    /// offsets passed to the container include
+    pub fn code_base(&self) -> usize {
+        self.code_base
+    }
+
+    /// Memory offset of the module's first active data segment
+    pub fn data_base(&self) -> usize {
+        self.data_base
+    }
+
+    /// Offset included in instruction offsets passed to and returned by the
+    /// container on top of `code_base`, for a module relocated at runtime such
+    /// as a side module loaded with `dlopen`
+    ///
+    /// Defaults to 0.
+    pub fn set_runtime_offset(&mut self, offset: usize) {
+        self.runtime_offset = offset;
+    }
+
+    /// Levels of nested arrays, enum fields and pointees evaluated variables
+    /// expand, past which `[...]` or `{...}` stands in for them
+    pub fn set_max_format_depth(&mut self, depth: usize) {
+        self.format_limits.max_depth = depth;
+    }
+
+    /// Elements or fields shown of each array or enum variant, the rest being
+    /// cut off with an ellipsis
+    pub fn set_max_format_children(&mut self, count: usize) {
+        self.format_limits.max_children = count;
+    }
+
+    /// Characters read and shown of a string
+    pub fn set_max_string_length(&mut self, length: usize) {
+        self.format_limits.max_string_length = length;
+    }
+
+    /// Which variables marked `DW_AT_artificial` the variable lists include,
+    /// by default only `this`
+    pub fn set_artificial_variables(&mut self, policy: ArtificialVariables) {
+        self.artificial_variables = policy;
+    }
+
+    pub fn source_file_list(&self) -> StringVector {
+        StringVector::from_vec(self.debug_info.sourcemap.file_list())
+    }
+
+    /// Report the source files recorded under `from` as living under `to`,
+    /// e.g. to point a CI machine's paths at a local checkout
+    ///
+    /// The longest matching prefix wins. File lookups accept paths under
+    /// either prefix.
+    pub fn add_path_substitution(&self, from: &str, to: &str) {
+        self.debug_info.sourcemap.add_path_substitution(from, to);
+    }
+
     pub fn find_file_info_from_address(&self, instruction_offset: usize) -> Option<WasmLineInfo> {
+        let offset = instruction_offset.checked_sub(self.instruction_base())?;
         self.debug_info
             .sourcemap
-            .find_line_info(instruction_offset - self.code_base)
+            .find_line_info(offset)
             .map(|x| WasmLineInfo::from_line_info(&x))
     }
 
+    /// Resolve several addresses in one call, e.g. every frame of a call stack
+    ///
+    /// Addresses outside the code section or any line sequence resolve to `None`.
+    pub fn find_file_info_from_addresses(&self, instruction_offsets: &[usize]) -> LineInfoVector {
+        LineInfoVector::from_vec(
+            instruction_offsets
+                .iter()
+                .map(|offset| {
+                    let offset = offset.checked_sub(self.instruction_base())?;
+                    self.debug_info.sourcemap.find_line_info(offset)
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether a line row or a subroutine's ranges cover an instruction, i.e.
+    /// whether stepping at it can be source-level rather than raw
+    pub fn has_debug_info(&self, instruction_offset: usize) -> bool {
+        let offset = match instruction_offset.checked_sub(self.instruction_base()) {
+            Some(offset) => offset,
+            None => return false,
+        };
+        self.debug_info.sourcemap.find_line_info(offset).is_some()
+            || self.debug_info.subroutine.find_subroutine(offset).is_ok()
+    }
+
+    /// Whether a line table row flagged as a statement starts at an
+    /// instruction, e.g. to only place breakpoints on statement boundaries
+    ///
+    /// Instructions inside a row but past its start are not statement starts.
+    pub fn is_statement_start(&self, instruction_offset: usize) -> bool {
+        match instruction_offset.checked_sub(self.instruction_base()) {
+            Some(offset) => self.debug_info.sourcemap.is_statement_start(offset),
+            None => false,
+        }
+    }
+
+    /// Whether an instruction is the first of a function with debug info, e.g.
+    /// to step into a call rather than over it
+    pub fn is_function_entry(&self, instruction_offset: usize) -> bool {
+        let offset = match instruction_offset.checked_sub(self.instruction_base()) {
+            Some(offset) => offset,
+            None => return false,
+        };
+        self.debug_info
+            .subroutine
+            .is_function_entry(offset)
+            .unwrap_or_else(|e| {
+                console_log!("failed to look up function entries: {}", e);
+                false
+            })
+    }
+
     pub fn find_address_from_file_info(&self, info: &WasmLineInfo) -> Option<usize> {
         let file_info = WasmLineInfo::into_line_info(info);
         self.debug_info
             .sourcemap
             .find_address(&file_info)
-            .map(|x| x + self.code_base)
+            .map(|x| x + self.instruction_base())
+    }
+
+    pub fn find_addresses_from_file_info(&self, info: &WasmLineInfo) -> Vec<usize> {
+        let file_info = WasmLineInfo::into_line_info(info);
+        self.debug_info
+            .sourcemap
+            .find_addresses(&file_info)
+            .into_iter()
+            .map(|x| x + self.instruction_base())
+            .collect()
     }
 
-    pub fn variable_name_list(&self, instruction_offset: usize) -> Option<VariableVector> {
-        match self
+    /// Every source location of a file that has code, e.g. to mark the lines
+    /// breakpoints can be set on
+    ///
+    /// Sorted by line and then column. Locations at the left edge of a line
+    /// have no column.
+    pub fn breakable_locations(&self, filepath: &str) -> LineInfoVector {
+        LineInfoVector::from_vec(
+            self.debug_info
+                .sourcemap
+                .find_breakable_locations(filepath)
+                .into_iter()
+                .map(Some)
+                .collect(),
+        )
+    }
+
+    /// The line a breakpoint set on `line` of a file stops at, which is the
+    /// next line with code when that one has none
+    ///
+    /// Returns `None` when no later line of the file has code.
+    pub fn resolve_breakpoint(&self, filepath: &str, line: usize) -> Option<ResolvedBreakpoint> {
+        let (line, address) = self
             .debug_info
-            .subroutine
-            .variable_name_list(instruction_offset - self.code_base, 1000)
-        {
-            Ok(x) => Some(VariableVector::from_vec(x)),
-            Err(e) => {
-                console_log!("{}", e);
-                None
-            }
-        }
+            .sourcemap
+            .resolve_breakpoint(filepath, line as u64)?;
+        Some(ResolvedBreakpoint {
+            filepath: filepath.to_string(),
+            line: line as usize,
+            address: address + self.instruction_base(),
+        })
+    }
+
+    /// Address ranges of every line in the line tables, sorted by start, e.g.
+    /// to compute the range a step runs through without querying each
+    /// instruction
+    ///
+    /// Rows of a line that follow each other are merged, and ranges stop at
+    /// the end of their sequence.
+    pub fn line_ranges(&self) -> LineRangeVector {
+        LineRangeVector::from_ranges(
+            self.debug_info.sourcemap.line_ranges(),
+            self.instruction_base(),
+        )
+    }
+
+    /// Number of line sequences, the bytes they cover and the gaps between
+    /// them, e.g. to tell why some addresses resolve to no line
+    pub fn line_table_coverage(&self) -> LineTableCoverage {
+        LineTableCoverage::from_sequences(
+            &self.debug_info.sourcemap.sequences(),
+            self.instruction_base(),
+        )
+    }
+
+    /// Every function of the module
+    ///
+    /// Subprograms are only read once a query lands in their unit, so the first
+    /// call reads every unit left unread, e.g. 20 ms of the 21 ms taken for the
+    /// 8000 functions of the `load_latency` test. Later calls reuse them.
+    pub fn function_list(&self) -> FunctionVector {
+        let units = self.debug_info.subroutine.all_subroutines();
+        FunctionVector::from_subroutines(
+            units.iter().flat_map(|unit| &unit.subroutines),
+            self.instruction_base(),
+        )
+        .substitute_paths(&self.debug_info.sourcemap)
+    }
+
+    /// Functions called `name`, as typed in `break <name>`
+    ///
+    /// Each match is listed once, with `low_pc` at its entry point. The other
+    /// pieces of a non-contiguous function are returned by `function_ranges`.
+    pub fn find_subroutine_by_name(&self, name: &str) -> FunctionVector {
+        let found = self.debug_info.subroutine.find_subroutines_by_name(name);
+        FunctionVector::from_entry_points(found.iter().map(|x| &**x), self.instruction_base())
+            .substitute_paths(&self.debug_info.sourcemap)
     }
 
-    pub fn global_variable_name_list(&self, instruction: usize) -> Option<VariableVector> {
-        let subroutine = match self
+    /// Function holding the code of a source line, to check the scope of a
+    /// `break <file>:<line>`
+    ///
+    /// Returns `None` for lines with no code of their own.
+    pub fn function_from_file_info(&self, info: &WasmLineInfo) -> Option<FunctionVector> {
+        let file_info = WasmLineInfo::into_line_info(info);
+        let sourcemap = &self.debug_info.sourcemap;
+        let offset =
+            sourcemap
+                .find_addresses(&file_info)
+                .into_iter()
+                .find(|offset| match sourcemap.find_line_info(*offset) {
+                    Some(found) => found.line == file_info.line,
+                    None => false,
+                })?;
+        let subroutine = self.debug_info.subroutine.find_subroutine(offset).ok()?;
+        Some(
+            FunctionVector::from_subroutines(
+                std::iter::once(&*subroutine),
+                self.instruction_base(),
+            )
+            .substitute_paths(sourcemap),
+        )
+    }
+
+    /// Address ranges of the function containing an instruction
+    ///
+    /// Stepping out runs until the pc leaves all of them. Functions split into
+    /// several pieces have one range per piece.
+    pub fn function_ranges(&self, instruction_offset: usize) -> Option<AddressRangeVector> {
+        let offset = instruction_offset.checked_sub(self.instruction_base())?;
+        let subroutine = self.debug_info.subroutine.find_subroutine(offset).ok()?;
+        Some(AddressRangeVector::from_ranges(
+            &subroutine.ranges,
+            self.instruction_base(),
+        ))
+    }
+
+    /// Address where the body of the function entered at `instruction_offset`
+    /// starts, with its arguments in place
+    ///
+    /// Returns `None` if no line row follows the entry within the function.
+    pub fn find_prologue_end(&self, instruction_offset: usize) -> Option<usize> {
+        let offset = instruction_offset.checked_sub(self.instruction_base())? as u64;
+        let subroutine = self
             .debug_info
             .subroutine
-            .find_subroutine(instruction - self.code_base)
-        {
-            Ok(x) => x,
-            Err(e) => {
-                console_log!("{}", e);
-                return None;
-            }
-        };
+            .find_subroutine(offset as usize)
+            .ok()?;
+        let range = subroutine
+            .ranges
+            .iter()
+            .find(|range| range.contains(&offset))?;
+        let range = offset..range.end;
+        self.debug_info
+            .sourcemap
+            .find_prologue_end(&range)
+            .map(|x| x as usize + self.instruction_base())
+    }
 
-        match self
-            .debug_info
+    /// Source language of the function containing an instruction
+    ///
+    /// Units without `DW_AT_language` are reported as C.
+    pub fn source_language(&self, instruction_offset: usize) -> Result<SourceLanguage, JsValue> {
+        self.code_offset(instruction_offset)
+            .and_then(|offset| self.debug_info.subroutine.source_language(offset))
+            .map_err(to_js_error)
+    }
+
+    /// `DW_AT_producer` of the unit containing an instruction, e.g.
+    /// `clang version 15.0.0`, or `None` when the unit doesn't name one
+    pub fn producer(&self, instruction_offset: usize) -> Result<Option<String>, JsValue> {
+        self.code_offset(instruction_offset)
+            .and_then(|offset| self.debug_info.subroutine.producer(offset))
+            .map_err(to_js_error)
+    }
+
+    /// Name of the function containing an instruction qualified by its
+    /// namespaces and classes, for the call stack, e.g. `ns::Widget::resize`
+    pub fn qualified_function_name(
+        &self,
+        instruction_offset: usize,
+    ) -> Result<Option<String>, JsValue> {
+        self.code_offset(instruction_offset)
+            .and_then(|offset| self.debug_info.subroutine.qualified_name(offset))
+            .map_err(to_js_error)
+    }
+
+    /// Signature of the function containing an instruction, for the call
+    /// stack, e.g. `int foo(char *, size_t)`
+    pub fn function_signature(&self, instruction_offset: usize) -> Result<String, JsValue> {
+        self.code_offset(instruction_offset)
+            .and_then(|offset| self.debug_info.subroutine.function_signature(offset))
+            .map_err(to_js_error)
+    }
+
+    pub fn inlined_frame_list(
+        &self,
+        instruction_offset: usize,
+    ) -> Result<InlinedFrameVector, JsValue> {
+        self.code_offset(instruction_offset)
+            .and_then(|offset| self.debug_info.subroutine.inlined_subroutine_stack(offset))
+            .map(InlinedFrameVector::from_inlined_subroutines)
+            .map(|frames| frames.substitute_paths(&self.debug_info.sourcemap))
+            .map_err(to_js_error)
+    }
+
+    pub fn variable_name_list(&self, instruction_offset: usize) -> Result<VariableVector, JsValue> {
+        self.code_offset(instruction_offset)
+            .and_then(|offset| self.debug_info.subroutine.variable_name_list(offset, 1000))
+            .map(|variables| VariableVector::from_vec(self.visible_variables(variables)))
+            .map(|variables| variables.relocate(self.instruction_base()))
+            .map(|variables| variables.substitute_paths(&self.debug_info.sourcemap))
+            .map_err(to_js_error)
+    }
+
+    /// Name of a function as recorded in the `name` section
+    pub fn function_name(&self, function_index: u32) -> Option<String> {
+        self.function_names.names.get(&function_index).cloned()
+    }
+
+    /// Name of the function containing an instruction, from the `name` section
+    ///
+    /// Unlike `function_list` this needs no DWARF, so it also labels functions
+    /// whose debug info lacks `DW_AT_name` or was stripped.
+    pub fn function_name_from_address(&self, instruction_offset: usize) -> Option<String> {
+        // the bodies are recorded at their offsets into the module
+        let instruction_offset = instruction_offset.checked_sub(self.runtime_offset)?;
+        let bodies = &self.function_names.bodies;
+        let position = bodies
+            .binary_search_by(|body| {
+                if body.end <= instruction_offset {
+                    std::cmp::Ordering::Less
+                } else if body.start > instruction_offset {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .ok()?;
+        let function_index = self.function_names.imported_function_count + position as u32;
+        self.function_name(function_index)
+    }
+
+    pub fn global_variable_name_list(&self, instruction: usize) -> Result<VariableVector, JsValue> {
+        let subroutine = self
+            .code_offset(instruction)
+            .and_then(|offset| self.debug_info.subroutine.find_subroutine(offset))
+            .map_err(to_js_error)?;
+
+        self.debug_info
             .global_variables
             .variable_name_list(subroutine.unit_offset, 1001)
-        {
-            Ok(x) => Some(VariableVector::from_vec(x)),
-            Err(e) => {
-                console_log!("{}", e);
-                None
-            }
+            .map(|variables| VariableVector::from_vec(self.visible_variables(variables)))
+            .map(|variables| variables.substitute_paths(&self.debug_info.sourcemap))
+            .map_err(to_js_error)
+    }
+
+    /// Variables in scope at an instruction grouped by scope, innermost first,
+    /// e.g. for the scope pane
+    ///
+    /// The blocks containing the instruction come first, then the function's
+    /// parameters and locals, a closure's captures and the globals of the
+    /// unit. Each scope lists its variables under a group of its own.
+    pub fn scope_list(&self, instruction_offset: usize) -> Result<ScopeVector, JsValue> {
+        let offset = self.code_offset(instruction_offset).map_err(to_js_error)?;
+        let subroutine = &self.debug_info.subroutine;
+        let mut scopes = subroutine.scope_list(offset).map_err(to_js_error)?;
+        for scope in &mut scopes {
+            hide_artificial(&mut scope.variables, self.artificial_variables);
         }
+        let unit_offset = subroutine
+            .find_subroutine(offset)
+            .map_err(to_js_error)?
+            .unit_offset;
+        scopes.push(VariableScope {
+            label: "Global",
+            group_id: 1001,
+            variables: self.visible_variables(
+                self.debug_info
+                    .global_variables
+                    .variable_name_list(unit_offset, 1001)
+                    .map_err(to_js_error)?,
+            ),
+        });
+        Ok(ScopeVector::from_vec(scopes)
+            .relocate(self.instruction_base())
+            .substitute_paths(&self.debug_info.sourcemap))
     }
 
+    /// Globals of every unit, for when the program is paused outside any
+    /// function, such as while the module initializes
+    pub fn all_global_variable_name_list(&self) -> Result<VariableVector, JsValue> {
+        self.debug_info
+            .global_variables
+            .all_variable_names(1001)
+            .map(|variables| VariableVector::from_vec(self.visible_variables(variables)))
+            .map(|variables| variables.substitute_paths(&self.debug_info.sourcemap))
+            .map_err(to_js_error)
+    }
+
+    /// Evaluate a variable in scope at `instruction_offset`
+    ///
+    /// `globals` holds every global of the module by index, imported ones
+    /// first, as the `globals` of the frame's module scope list them.
+    /// Thread-local variables are read at offsets from the module's
+    /// `__tls_base` global in there.
+    ///
+    /// `read_memory(address, size)` returns the bytes of linear memory as a
+    /// `Uint8Array`, for locations that dereference pointers. Without it or a
+    /// memory snapshot such variables are reported as unavailable.
+    ///
+    /// `read_register(register)` returns the value of a DWARF register as a
+    /// number, or `undefined` if it isn't known, for `DW_OP_breg*` locations
+    /// left by toolchains targeting something else before wasm. Without it
+    /// such variables are reported as unavailable.
+    #[allow(clippy::too_many_arguments)]
     pub fn get_variable_info(
         &self,
         opts: String,
@@ -90,75 +628,2200 @@ impl DwarfDebugSymbolContainer {
         globals: &WasmValueVector,
         stacks: &WasmValueVector,
         instruction_offset: usize,
-    ) -> Option<VariableInfo> {
-        match self.debug_info.subroutine.get_variable_info(
-            &opts,
+        read_memory: Option<js_sys::Function>,
+        read_register: Option<js_sys::Function>,
+    ) -> Result<VariableLookup, JsValue> {
+        let memory = read_memory.map(js_memory_reader);
+        let registers = read_register.map(js_register_reader);
+        let mut frame = self.frame(locals, globals, stacks, &memory);
+        frame.registers = registers.as_deref();
+        variable_lookup(self.evaluate_variable(
+            &VariableQuery::Name(&opts),
+            &frame,
+            instruction_offset,
+        ))
+    }
+
+    /// Evaluate the variable whose DIE offset was listed by `variable_name_list`
+    ///
+    /// Unlike `get_variable_info` this picks the exact variable even when an
+    /// inner scope shadows another variable of the same name. Globals listed by
+    /// `all_global_variable_name_list` are found whichever unit declares them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_variable_info_by_offset(
+        &self,
+        die_offset: usize,
+        locals: &WasmValueVector,
+        globals: &WasmValueVector,
+        stacks: &WasmValueVector,
+        instruction_offset: usize,
+        read_memory: Option<js_sys::Function>,
+        read_register: Option<js_sys::Function>,
+    ) -> Result<VariableLookup, JsValue> {
+        let memory = read_memory.map(js_memory_reader);
+        let registers = read_register.map(js_register_reader);
+        let mut frame = self.frame(locals, globals, stacks, &memory);
+        frame.registers = registers.as_deref();
+        variable_lookup(self.evaluate_variable(
+            &VariableQuery::DieOffset(die_offset),
+            &frame,
+            instruction_offset,
+        ))
+    }
+
+    /// Raw `DW_AT_location` expression of a variable at an instruction, for
+    /// bridges evaluating operations `get_variable_info` doesn't support
+    ///
+    /// Returns `None` for variables without a location there, such as
+    /// constants, members or variables optimized out.
+    pub fn raw_variable_location(
+        &self,
+        name: String,
+        instruction_offset: usize,
+    ) -> Result<Option<RawLocation>, JsValue> {
+        let query = VariableQuery::Name(&name);
+        let code_offset = self.code_offset(instruction_offset).map_err(to_js_error)?;
+        let subroutine = &self.debug_info.subroutine;
+        match subroutine.raw_variable_location(&query, code_offset) {
+            Err(e) if matches!(e.downcast_ref(), Some(BridgeError::NotFound(_))) => {
+                subroutine.find_subroutine(code_offset).and_then(|found| {
+                    self.debug_info
+                        .global_variables
+                        .raw_variable_location(&query, found.unit_offset)
+                })
+            }
+            result => result,
+        }
+        .map_err(to_js_error)
+    }
+
+    /// Describe a read of `size` bytes of linear memory at `address`
+    ///
+    /// The bridge fetches `byte_size` bytes at `address` of the returned
+    /// slice, which may be fewer than asked for, stores them with
+    /// `set_memory_slice` and hands the slice to `complete_read`. This is the
+    /// same protocol `VariableInfo::required_memory_slice` follows.
+    pub fn prepare_read(&self, address: usize, size: usize) -> MemorySlice {
+        let mut slice = MemorySlice::new();
+        slice.address = address;
+        slice.byte_size = size.min(MAX_READ_BYTES).min(usize::MAX - address);
+        slice
+    }
+
+    /// Format the bytes read for a `prepare_read` as a hex dump
+    pub fn complete_read(&self, slice: &MemorySlice) -> Result<String, JsValue> {
+        format_memory(slice).map_err(to_js_error)
+    }
+
+    /// Leave out the artificial variables `set_artificial_variables` hides
+    fn visible_variables(&self, mut variables: Vec<VariableName>) -> Vec<VariableName> {
+        hide_artificial(&mut variables, self.artificial_variables);
+        variables
+    }
+
+    /// Where the code section starts among the instruction offsets passed to
+    /// and returned by the container
+    fn instruction_base(&self) -> usize {
+        self.code_base + self.runtime_offset
+    }
+
+    /// Offset of an instruction into the code section
+    fn code_offset(&self, instruction_offset: usize) -> Result<usize> {
+        instruction_offset
+            .checked_sub(self.instruction_base())
+            .ok_or_else(|| {
+                BridgeError::NotFound(format!(
+                    "{:#x} is before the code section at {:#x}",
+                    instruction_offset,
+                    self.instruction_base()
+                ))
+                .into()
+            })
+    }
+
+    fn frame<'a>(
+        &'a self,
+        locals: &'a WasmValueVector,
+        globals: &'a WasmValueVector,
+        stacks: &'a WasmValueVector,
+        memory: &'a Option<JsMemoryReader>,
+    ) -> WasmFrame<'a> {
+        let mut frame = WasmFrame {
             locals,
             globals,
             stacks,
-            instruction_offset - self.code_base,
-        ) {
-            Ok(Some(x)) => return Some(x),
-            Ok(None) => {}
-            Err(e) => {
-                console_log!("{}", e)
-            }
+            data_base: 0,
+            tls_base: self.tls_base_global,
+            memory: memory.as_deref().or(self.snapshot_reader.as_deref()),
+            registers: None,
         };
+        // only a relocatable module's data moves, to the `__memory_base` it imports
+        if let Some(index) = self.memory_base_global {
+            frame.data_base = match frame.value_at(&WasmLoc::Global(index as u64)) {
+                Ok(base) => base.to_bits(),
+                Err(_) => self.data_base as u64,
+            };
+        }
+        frame
+    }
 
-        let subroutine = match self
-            .debug_info
-            .subroutine
-            .find_subroutine(instruction_offset - self.code_base)
-        {
-            Ok(x) => x,
-            Err(e) => {
-                console_log!("{}", e);
-                return None;
+    fn evaluate_variable(
+        &self,
+        query: &VariableQuery,
+        frame: &WasmFrame,
+        instruction_offset: usize,
+    ) -> Result<Option<VariableInfo>, BridgeError> {
+        let mut info = self.lookup_variable(query, frame, instruction_offset)?;
+        if let Some(info) = info.as_mut() {
+            info.set_format_limits(self.format_limits);
+            if let Some(snapshot) = &self.memory_snapshot {
+                info.set_memory_snapshot(snapshot.clone());
             }
+        }
+        Ok(info)
+    }
+
+    fn lookup_variable(
+        &self,
+        query: &VariableQuery,
+        frame: &WasmFrame,
+        instruction_offset: usize,
+    ) -> Result<Option<VariableInfo>, BridgeError> {
+        let code_offset = self
+            .code_offset(instruction_offset)
+            .map_err(BridgeError::from_anyhow)?;
+        let local_error =
+            match self
+                .debug_info
+                .subroutine
+                .get_variable_info(query, frame, code_offset)
+            {
+                Ok(Some(x)) => return Ok(Some(x)),
+                Ok(None) => None,
+                Err(e) => Some(BridgeError::from_anyhow(e)),
+            };
+
+        // a global listed by its DIE offset may belong to any unit
+        let globals = &self.debug_info.global_variables;
+        let unit_offset = match query {
+            VariableQuery::DieOffset(die_offset) => globals.unit_containing(*die_offset),
+            VariableQuery::Name(_) => self
+                .debug_info
+                .subroutine
+                .find_subroutine(code_offset)
+                .map(|subroutine| Some(subroutine.unit_offset)),
+        }
+        .map_err(BridgeError::from_anyhow)?;
+        let unit_offset = match unit_offset {
+            Some(unit_offset) => unit_offset,
+            None => return local_error.map_or(Ok(None), Err),
         };
 
-        match self.debug_info.global_variables.get_variable_info(
-            &opts,
-            subroutine.unit_offset,
-            self.data_base,
-            globals,
-        ) {
-            Ok(x) => x,
-            Err(e) => {
-                console_log!("{}", e);
-                None
-            }
+        match globals.get_variable_info(query, unit_offset, frame) {
+            Ok(x) => Ok(x),
+            // a local that failed to evaluate is more relevant than a missing global
+            Err(e) => match local_error {
+                Some(local_error) if !matches!(local_error, BridgeError::NotFound(_)) => {
+                    Err(local_error)
+                }
+                _ => Err(BridgeError::from_anyhow(e)),
+            },
         }
     }
 }
 
-fn calculate_code_base(data: &[u8]) -> Result<(usize, usize)> {
-    let parser = Parser::new(0);
-    let mut code_section_offset = 0;
-    let data_section_offset = 0;
+impl DwarfDebugSymbolContainer {
+    /// Load a binary like `new`, returning the error instead of converting it
+    /// into a JS value, e.g. for callers outside of JS such as fuzz targets
+    pub fn parse(data: &[u8]) -> Result<DwarfDebugSymbolContainer> {
+        Self::load(data, None, 0)
+    }
+}
 
-    for payload in parser.parse_all(data) {
+/// Report variables that are missing or optimized out as a status, and throw
+/// the errors of variables that failed to evaluate
+fn variable_lookup(
+    result: Result<Option<VariableInfo>, BridgeError>,
+) -> Result<VariableLookup, JsValue> {
+    match result {
+        Ok(Some(info)) => Ok(VariableLookup::new(VariableStatus::Found, Some(info))),
+        Ok(None) | Err(BridgeError::NotFound(_)) => {
+            Ok(VariableLookup::new(VariableStatus::NotFound, None))
+        }
+        Err(BridgeError::OptimizedOut(_)) => {
+            Ok(VariableLookup::new(VariableStatus::OptimizedOut, None))
+        }
+        Err(BridgeError::Unavailable(_)) => {
+            Ok(VariableLookup::new(VariableStatus::Unavailable, None))
+        }
+        Err(e) => Err(e.into_js_value()),
+    }
+}
+
+type JsMemoryReader = Box<dyn Fn(u64, usize) -> Option<Vec<u8>>>;
+
+/// Adapt a JS `(address, size) => Uint8Array` callback to read linear memory
+fn js_memory_reader(read_memory: js_sys::Function) -> JsMemoryReader {
+    Box::new(move |address, size| {
+        let bytes = read_memory
+            .call2(
+                &JsValue::NULL,
+                &JsValue::from_f64(address as f64),
+                &JsValue::from_f64(size as f64),
+            )
+            .ok()?;
+        if bytes.is_undefined() || bytes.is_null() {
+            return None;
+        }
+        Some(js_sys::Uint8Array::new(&bytes).to_vec())
+    })
+}
+
+type JsRegisterReader = Box<dyn Fn(u16) -> Option<u64>>;
+
+/// Adapt a JS `(register) => number` callback to read DWARF registers
+fn js_register_reader(read_register: js_sys::Function) -> JsRegisterReader {
+    Box::new(move |register| {
+        read_register
+            .call1(&JsValue::NULL, &JsValue::from_f64(register as f64))
+            .ok()?
+            .as_f64()
+            .map(|value| value as u64)
+    })
+}
+
+/// Copy the sections of a JS `Map` of section names to `Uint8Array`s
+fn js_section_map(sections: &js_sys::Map) -> Result<SectionMap> {
+    let mut section_map = SectionMap::new();
+    let mut result = Ok(());
+    sections.for_each(&mut |data, name| {
+        if result.is_err() {
+            return;
+        }
+        result = match (name.as_string(), data.dyn_into::<js_sys::Uint8Array>()) {
+            (Some(name), Ok(data)) => add_custom_section(&mut section_map, &name, &data.to_vec()),
+            _ => Err(anyhow::anyhow!(
+                "debug sections must map section names to Uint8Arrays"
+            )),
+        };
+    });
+    result.map(|()| section_map)
+}
+
+/// What the container reads from a module, gathered in one pass over the binary
+struct WasmModule {
+    /// Custom sections, handed over to the debug info
+    sections: SectionMap,
+    /// Offset of the code section, which instruction offsets are relative to
+    code_base: usize,
+    /// Memory offset of the first data segment
+    data_base: usize,
+    /// Index of the `__tls_base` global, if the module names it
+    tls_base_global: Option<u32>,
+    /// Index of the imported `__memory_base` global of a relocatable module
+    memory_base_global: Option<u32>,
+    function_names: WasmFunctionNames,
+}
+
+fn scan_module(data: &[u8], module_index: usize) -> Result<WasmModule> {
+    let mut sections = SectionMap::new();
+    let mut code_section_offset = None;
+    let mut data_section_offset = None;
+    let mut tls_base_global = None;
+    let mut memory_base_global = None;
+    let mut imported_global_count = 0;
+    let mut function_names = WasmFunctionNames::default();
+
+    for payload in module_payloads(data, module_index) {
         match payload? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import?;
+                    match import.ty {
+                        ImportSectionEntryType::Function(_) => {
+                            function_names.imported_function_count += 1;
+                        }
+                        // dynamically linked modules import the base
+                        ImportSectionEntryType::Global(_) => {
+                            if import.field == Some(TLS_BASE) {
+                                tls_base_global = Some(imported_global_count);
+                            }
+                            if import.field == Some(MEMORY_BASE) {
+                                memory_base_global = Some(imported_global_count);
+                            }
+                            imported_global_count += 1;
+                        }
+                        _ => {}
+                    }
+                }
+            }
             Payload::CodeSectionStart { range, .. } => {
-                code_section_offset = range.start;
-            }
-            // Payload::DataSection(ref mut reader) => {
-            //     let data = reader.read().expect("data");
-
-            //     if let DataKind::Active { init_expr, .. } = data.kind {
-            //         let mut init_expr_reader = init_expr.get_binary_reader();
-            //         let op = init_expr_reader.read_operator().expect("op");
-
-            //         match op {
-            //             wasmparser::Operator::I32Const { value } => {
-            //                 data_section_offset = value as usize
-            //             },
-            //             _ => {}
-            //         }
-            //     }
-            // },
+                if code_section_offset.is_none() {
+                    code_section_offset = Some(range.start);
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let range = body.range();
+                function_names.bodies.push(range.start..range.end);
+            }
+            Payload::DataSection(reader) => {
+                if data_section_offset.is_some() {
+                    continue;
+                }
+                data_section_offset = first_active_data_offset(reader).unwrap_or_else(|e| {
+                    console_log!("ignoring the data base: {}", e);
+                    None
+                });
+            }
+            Payload::CustomSection {
+                name,
+                data,
+                data_offset,
+                ..
+            } => {
+                if name == "name" {
+                    if let Err(e) = read_function_names(&mut function_names, data, data_offset) {
+                        console_log!("ignoring the rest of the name section: {}", e);
+                    }
+                    if tls_base_global.is_none() {
+                        tls_base_global = find_tls_base(data, data_offset).unwrap_or_else(|e| {
+                            console_log!("ignoring the global names: {}", e);
+                            None
+                        });
+                    }
+                }
+                add_custom_section(&mut sections, name, data)?;
+            }
             _ => continue,
         }
     }
-    Ok((code_section_offset, data_section_offset))
+    Ok(WasmModule {
+        sections,
+        code_base: code_section_offset.unwrap_or(0),
+        data_base: data_section_offset.unwrap_or(0),
+        tls_base_global,
+        memory_base_global,
+        function_names,
+    })
+}
+
+/// Function names from the `name` section
+#[derive(Default)]
+struct WasmFunctionNames {
+    names: HashMap<u32, String>,
+    imported_function_count: u32,
+    /// Body ranges of the defined functions, in function index order
+    bodies: Vec<Range<usize>>,
+}
+
+fn read_function_names(
+    function_names: &mut WasmFunctionNames,
+    data: &[u8],
+    data_offset: usize,
+) -> Result<()> {
+    let mut reader = NameSectionReader::new(data, data_offset)?;
+    while !reader.eof() {
+        let map = match reader.read()? {
+            Name::Function(map) => map,
+            _ => continue,
+        };
+        let mut naming = map.get_map()?;
+        for _ in 0..naming.get_count() {
+            let naming = naming.read()?;
+            let name = demangle(naming.name).unwrap_or_else(|| naming.name.to_string());
+            function_names.names.insert(naming.index, name);
+        }
+    }
+    Ok(())
+}
+
+/// Global the thread-local variables of a module with shared memory are
+/// relative to
+const TLS_BASE: &str = "__tls_base";
+const MEMORY_BASE: &str = "__memory_base";
+
+/// Find the index of the `__tls_base` global in the global names of the
+/// `name` section
+fn find_tls_base(data: &[u8], data_offset: usize) -> Result<Option<u32>> {
+    let mut reader = NameSectionReader::new(data, data_offset)?;
+    while !reader.eof() {
+        let map = match reader.read()? {
+            Name::Global(map) => map,
+            _ => continue,
+        };
+        let mut naming = map.get_map()?;
+        for _ in 0..naming.get_count() {
+            let naming = naming.read()?;
+            if naming.name == TLS_BASE {
+                return Ok(Some(naming.index));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Find the memory offset of the first active data segment with a constant offset
+fn first_active_data_offset(reader: DataSectionReader) -> Result<Option<usize>> {
+    for segment in reader {
+        let segment = segment?;
+
+        if let DataKind::Active {
+            memory_index: 0,
+            init_expr,
+        } = segment.kind
+        {
+            let mut init_expr_reader = init_expr.get_operators_reader();
+
+            match init_expr_reader.read()? {
+                Operator::I32Const { value } => return Ok(Some(value as u32 as usize)),
+                // memory64 segments may lie beyond what a 32-bit host addresses
+                Operator::I64Const { value } => {
+                    let offset = usize::try_from(value as u64).map_err(|_| {
+                        BridgeError::Unsupported(format!(
+                            "data segment at {:#x} is out of the host's address range",
+                            value
+                        ))
+                    })?;
+                    return Ok(Some(offset));
+                }
+                _ => continue,
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dwarf::wasm_bindings::WasmValue;
+
+    /// `tests/fixtures/basic.rs` built for wasm32-unknown-unknown
+    const BASIC: &[u8] = include_bytes!("../tests/fixtures/basic.wasm");
+    /// `tests/fixtures/memory64.rs` built for wasm64-unknown-unknown
+    const MEMORY64: &[u8] = include_bytes!("../tests/fixtures/memory64.wasm");
+    /// `tests/fixtures/optimized.rs` built at opt-level 2
+    const OPTIMIZED: &[u8] = include_bytes!("../tests/fixtures/optimized.wasm");
+    /// `tests/fixtures/closure.rs` built at opt-level 0
+    const CLOSURE: &[u8] = include_bytes!("../tests/fixtures/closure.wasm");
+    /// `tests/fixtures/dwarf5.rs` built with `-Z dwarf-version=5`
+    const DWARF5: &[u8] = include_bytes!("../tests/fixtures/dwarf5.wasm");
+    /// `tests/fixtures/union.rs` built at opt-level 0
+    const UNION: &[u8] = include_bytes!("../tests/fixtures/union.wasm");
+    /// `tests/fixtures/anonymous.ll` built with llc and linked with rust-lld
+    const ANONYMOUS: &[u8] = include_bytes!("../tests/fixtures/anonymous.wasm");
+    const ANONYMOUS2: &[u8] = include_bytes!("../tests/fixtures/anonymous2.wasm");
+    /// `tests/fixtures/bitfield.ll` with the DWARF 2 and DWARF 4 bit offsets
+    const BITFIELD: &[u8] = include_bytes!("../tests/fixtures/bitfield.wasm");
+    const BITFIELD4: &[u8] = include_bytes!("../tests/fixtures/bitfield4.wasm");
+    /// `tests/fixtures/globals.ll`, with two units describing one variable
+    const GLOBALS: &[u8] = include_bytes!("../tests/fixtures/globals.wasm");
+    /// `tests/fixtures/vla.ll`, with arrays bounded by a variable and an expression
+    const VLA: &[u8] = include_bytes!("../tests/fixtures/vla.wasm");
+    /// `tests/fixtures/tls.ll`, thread-local variables of a shared memory module
+    const TLS: &[u8] = include_bytes!("../tests/fixtures/tls.wasm");
+    /// `tests/fixtures/typed.ll`, values converted with DWARF 5 typed operations
+    const TYPED: &[u8] = include_bytes!("../tests/fixtures/typed.wasm");
+    /// `tests/fixtures/stack.ll`, with a variable on the operand stack
+    const STACK: &[u8] = include_bytes!("../tests/fixtures/stack.wasm");
+    const PARTIAL: &[u8] = include_bytes!("../tests/fixtures/partial.wasm");
+    /// `tests/fixtures/forms.s`, with attributes in the DWARF 5 indexed forms
+    const FORMS: &[u8] = include_bytes!("../tests/fixtures/forms.wasm");
+    /// `tests/fixtures/frame.ll`, with locals placed by DW_OP_fbreg
+    const FRAME: &[u8] = include_bytes!("../tests/fixtures/frame.wasm");
+    /// `tests/fixtures/inline.ll`, with a call inlined into an inlined call
+    const INLINE: &[u8] = include_bytes!("../tests/fixtures/inline.wasm");
+    /// `tests/fixtures/split_a.ll` and `split_b.ll`, with the units split out
+    /// into a `.dwo` file for the first and a `.dwp` package for both
+    const SPLIT: &[u8] = include_bytes!("../tests/fixtures/split.wasm");
+    const SPLIT_A_DWO: &[u8] = include_bytes!("../tests/fixtures/split_a.dwo");
+    const SPLIT_DWP: &[u8] = include_bytes!("../tests/fixtures/split.dwp");
+    /// `tests/fixtures/pic.ll`, a side module placed at its `__memory_base`
+    const PIC: &[u8] = include_bytes!("../tests/fixtures/pic.wasm");
+    /// `tests/fixtures/shadow.ll`, with a local shadowed in an inner block
+    const SHADOW: &[u8] = include_bytes!("../tests/fixtures/shadow.wasm");
+
+    fn values(values: &[i64]) -> WasmValueVector {
+        let mut vector = WasmValueVector::new();
+        for value in values {
+            vector.push(WasmValue::from_i64(*value));
+        }
+        vector
+    }
+
+    /// Evaluate a variable, answering each read from the `(address, bytes)`
+    /// region it falls in
+    fn evaluate(mut info: VariableInfo, memory: &[(usize, &[u8])]) -> Option<String> {
+        let mut result = info.evaluate();
+        while info.is_required_memory_slice() {
+            let mut slice = info.required_memory_slice();
+            let (start, bytes) = memory
+                .iter()
+                .find(|(start, bytes)| (*start..*start + bytes.len()).contains(&slice.address))
+                .unwrap_or_else(|| panic!("unexpected read at {:#x}", slice.address));
+            let offset = slice.address - start;
+            slice.set_memory_slice(&bytes[offset..offset + slice.byte_size]);
+            result = info.resume_with_memory_slice(slice).or(result);
+        }
+        result
+    }
+
+    fn uleb128(mut value: usize, bytes: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                return;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    /// A module of `units` units with `functions` functions each, every function
+    /// taking a parameter and declaring a few locals
+    ///
+    /// Each function is a 4-byte body of `nop`s, listed with two line rows.
+    fn generated_module(units: usize, functions: usize) -> Vec<u8> {
+        use gimli::write::{
+            self, Address, AttributeValue, EndianVec, Expression, LineProgram, LineString,
+        };
+
+        let count = units * functions;
+        let mut code = Vec::new();
+        uleb128(count, &mut code);
+        // where the body of a function starts, past its size and local counts
+        let mut starts = Vec::new();
+        for _ in 0..count {
+            code.extend_from_slice(&[5, 0]);
+            starts.push(code.len() as u64);
+            code.extend_from_slice(&[0x01, 0x01, 0x01, 0x0b]);
+        }
+
+        let encoding = gimli::Encoding {
+            format: gimli::Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let mut dwarf = write::Dwarf::new();
+        for (index, starts) in starts.chunks(functions).enumerate() {
+            let name = format!("unit{}.c", index);
+            let mut program = LineProgram::new(
+                encoding,
+                gimli::LineEncoding::default(),
+                LineString::String(b".".to_vec()),
+                LineString::String(name.as_bytes().to_vec()),
+                None,
+            );
+            let directory = program.default_directory();
+            let file = program.add_file(LineString::String(name.into_bytes()), directory, None);
+            for (function, start) in starts.iter().enumerate() {
+                program.begin_sequence(Some(Address::Constant(*start)));
+                for row in 0..2 {
+                    program.row().file = file;
+                    program.row().line = function as u64 * 10 + row + 1;
+                    program.row().address_offset = row * 2;
+                    program.generate_row();
+                }
+                program.end_sequence(4);
+            }
+
+            let unit = dwarf.units.add(write::Unit::new(encoding, program));
+            let unit = dwarf.units.get_mut(unit);
+            let root = unit.root();
+            let first = starts[0];
+            let last = starts[starts.len() - 1] + 4;
+            let entry = unit.get_mut(root);
+            entry.set(
+                gimli::DW_AT_low_pc,
+                AttributeValue::Address(Address::Constant(first)),
+            );
+            entry.set(gimli::DW_AT_high_pc, AttributeValue::Udata(last - first));
+
+            let int = unit.add(root, gimli::DW_TAG_base_type);
+            let entry = unit.get_mut(int);
+            entry.set(gimli::DW_AT_name, AttributeValue::String(b"int".to_vec()));
+            entry.set(
+                gimli::DW_AT_encoding,
+                AttributeValue::Encoding(gimli::DW_ATE_signed),
+            );
+            entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(4));
+
+            for (function, start) in starts.iter().enumerate() {
+                let subprogram = unit.add(root, gimli::DW_TAG_subprogram);
+                let entry = unit.get_mut(subprogram);
+                let name = format!("function{}_{}", index, function);
+                entry.set(gimli::DW_AT_name, AttributeValue::String(name.into_bytes()));
+                entry.set(
+                    gimli::DW_AT_low_pc,
+                    AttributeValue::Address(Address::Constant(*start)),
+                );
+                entry.set(gimli::DW_AT_high_pc, AttributeValue::Udata(4));
+                for (local, tag) in ["value", "a", "b", "c"].iter().zip(
+                    std::iter::once(gimli::DW_TAG_formal_parameter)
+                        .chain(std::iter::repeat(gimli::DW_TAG_variable)),
+                ) {
+                    let variable = unit.add(subprogram, tag);
+                    let entry = unit.get_mut(variable);
+                    entry.set(
+                        gimli::DW_AT_name,
+                        AttributeValue::String(local.as_bytes().to_vec()),
+                    );
+                    entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(int));
+                    // DW_OP_WASM_location 0x0 0, DW_OP_stack_value
+                    let location = Expression::raw(vec![0xed, 0x00, 0x00, 0x9f]);
+                    entry.set(gimli::DW_AT_location, AttributeValue::Exprloc(location));
+                }
+            }
+        }
+        let mut sections = write::Sections::new(EndianVec::new(gimli::LittleEndian));
+        dwarf.write(&mut sections).unwrap();
+
+        let mut binary = b"\0asm\x01\0\0\0".to_vec();
+        let mut section = |id: u8, payload: &[u8]| {
+            binary.push(id);
+            uleb128(payload.len(), &mut binary);
+            binary.extend_from_slice(payload);
+        };
+        // one `() -> ()` type for every function
+        section(1, &[1, 0x60, 0, 0]);
+        let mut types = Vec::new();
+        uleb128(count, &mut types);
+        types.resize(types.len() + count, 0);
+        section(3, &types);
+        section(10, &code);
+        sections
+            .for_each(|id, data| -> Result<()> {
+                if !data.slice().is_empty() {
+                    let mut payload = Vec::new();
+                    uleb128(id.name().len(), &mut payload);
+                    payload.extend_from_slice(id.name().as_bytes());
+                    payload.extend_from_slice(data.slice());
+                    section(0, &payload);
+                }
+                Ok(())
+            })
+            .unwrap();
+        binary
+    }
+
+    #[test]
+    fn end_to_end() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+
+        // `step` starts its body on line 37 at 0x40 into the code section
+        let info = WasmLineInfo::new("basic.rs".to_string(), Some(37), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        assert_eq!(pc, container.code_base + 0x40);
+        let location = |address| {
+            container
+                .find_file_info_from_address(address)
+                .map(|info| (info.file(), info.line, info.column))
+        };
+        assert_eq!(
+            location(pc),
+            Some(("basic.rs".to_string(), Some(37), Some(14)))
+        );
+        assert_eq!(
+            location(container.code_base + 0x51),
+            Some(("basic.rs".to_string(), Some(38), Some(14)))
+        );
+
+        assert_eq!(
+            container.function_signature(pc).ok().as_deref(),
+            Some("void step(i32)")
+        );
+        let info = WasmLineInfo::new("basic.rs".to_string(), Some(44), None);
+        let first = container.find_address_from_file_info(&info).unwrap();
+        assert_eq!(
+            container.function_signature(first).ok().as_deref(),
+            Some("i32 first(i32, u8 *)")
+        );
+
+        let variables = container.variable_name_list(pc).ok().unwrap();
+        let names: Vec<_> = (0..variables.size())
+            .map(|i| (variables.at_name(i), variables.at_is_parameter(i)))
+            .collect();
+        assert_eq!(
+            names,
+            [("start".to_string(), true), ("current".to_string(), false)]
+        );
+
+        // the frame base lives in local 1
+        let locals = values(&[3, 0x1000]);
+        let empty = values(&[]);
+        let info = |name: &str| {
+            container
+                .get_variable_info(name.to_string(), &locals, &empty, &empty, pc, None, None)
+                .ok()
+                .unwrap()
+                .info()
+                .unwrap()
+        };
+        let mut current = info("current");
+        assert_eq!(
+            evaluate(current.clone(), &[(0x100c, &3i32.to_le_bytes())]).as_deref(),
+            Some("(i32)3")
+        );
+
+        // enough for callers to decode the bytes themselves
+        current.evaluate();
+        let mut slice = current.required_memory_slice();
+        slice.set_memory_slice(&3i32.to_le_bytes());
+        current.resume_with_memory_slice(slice);
+        assert_eq!(current.encoding_code(), gimli::DW_ATE_signed.0);
+        assert_eq!(current.tag_code(), gimli::DW_TAG_base_type.0);
+        assert_eq!(current.byte_size(), 4);
+        assert_eq!(current.required_memory_slice().memory_slice(), [3, 0, 0, 0]);
+        assert_eq!(
+            evaluate(info("STEPS"), &[(0x100000, &5i32.to_le_bytes())]).as_deref(),
+            Some("(i32)5")
+        );
+    }
+
+    #[test]
+    fn debug_info_coverage() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let info = WasmLineInfo::new("basic.rs".to_string(), Some(37), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        assert!(container.has_debug_info(pc));
+        assert!(!container.has_debug_info(container.code_base() - 1));
+        assert!(!container.has_debug_info(container.code_base() + 0x100000));
+    }
+
+    /// Time loading a module of 8000 functions and its first queries, with
+    /// `cargo test --release -- --ignored --nocapture load_latency`
+    #[test]
+    #[ignore]
+    fn load_latency() {
+        let binary = generated_module(400, 20);
+        let start = std::time::Instant::now();
+        let container = DwarfDebugSymbolContainer::new(&binary).ok().unwrap();
+        let load = start.elapsed();
+
+        let info = WasmLineInfo::new("unit200.c".to_string(), Some(101), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        let start = std::time::Instant::now();
+        let variables = container.variable_name_list(pc).ok().unwrap();
+        let first_query = start.elapsed();
+        assert_eq!(variables.size(), 4);
+
+        let start = std::time::Instant::now();
+        assert_eq!(container.function_list().size(), 8000);
+        let first_list = start.elapsed();
+        let start = std::time::Instant::now();
+        container.function_list();
+        let second_list = start.elapsed();
+
+        eprintln!(
+            "{} bytes: new {:?}, first variable list {:?}, function_list {:?} then {:?}",
+            binary.len(),
+            load,
+            first_query,
+            first_list,
+            second_list
+        );
+    }
+
+    #[test]
+    fn nested_modules() {
+        // BASIC followed by a module section nesting FRAME and INLINE
+        let mut modules = Vec::new();
+        uleb128(2, &mut modules);
+        let mut offsets = Vec::new();
+        for module in [FRAME, INLINE].iter() {
+            uleb128(module.len(), &mut modules);
+            offsets.push(modules.len());
+            modules.extend_from_slice(module);
+        }
+        let mut binary = BASIC.to_vec();
+        binary.push(14);
+        uleb128(modules.len(), &mut binary);
+        let section_start = binary.len();
+        binary.extend_from_slice(&modules);
+
+        let line = |container: &DwarfDebugSymbolContainer, file: &str, line| {
+            let info = WasmLineInfo::new(file.to_string(), Some(line), None);
+            container
+                .find_address_from_file_info(&info)
+                .map(|pc| pc - container.code_base())
+        };
+
+        // the outermost module ignores the nested ones
+        let outer = DwarfDebugSymbolContainer::new_for_module(&binary, 0)
+            .ok()
+            .unwrap();
+        let plain = DwarfDebugSymbolContainer::new(&binary).ok().unwrap();
+        let basic = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        assert_eq!(outer.code_base(), basic.code_base());
+        assert_eq!(plain.code_base(), basic.code_base());
+        assert_eq!(line(&outer, "basic.rs", 37), Some(0x40));
+        assert_eq!(line(&plain, "basic.rs", 37), Some(0x40));
+        assert_eq!(line(&outer, "frame.c", 6), None);
+
+        // nested modules are relative to their own code section
+        let frame = DwarfDebugSymbolContainer::new(FRAME).ok().unwrap();
+        let nested = DwarfDebugSymbolContainer::new_for_module(&binary, 1)
+            .ok()
+            .unwrap();
+        assert_eq!(
+            nested.code_base(),
+            section_start + offsets[0] + frame.code_base()
+        );
+        assert_eq!(line(&nested, "frame.c", 6), line(&frame, "frame.c", 6));
+        assert_eq!(line(&nested, "basic.rs", 37), None);
+
+        let inline = DwarfDebugSymbolContainer::new(INLINE).ok().unwrap();
+        let nested = DwarfDebugSymbolContainer::new_for_module(&binary, 2)
+            .ok()
+            .unwrap();
+        assert_eq!(
+            nested.code_base(),
+            section_start + offsets[1] + inline.code_base()
+        );
+        let file = nested
+            .find_file_info_from_address(nested.code_base() + 0x5)
+            .map(|info| info.file());
+        assert!(file.unwrap().ends_with("inline.c"));
+    }
+
+    #[test]
+    fn line_info_of_addresses() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let base = container.code_base();
+        // the last row of `step` ends the sequence at 0x64
+        let found = container.find_file_info_from_addresses(&[base + 0x40, base - 1, base + 0x64]);
+        assert_eq!(found.size(), 3);
+        let line = found.at(0).map(|info| (info.file(), info.line));
+        assert_eq!(line, Some(("basic.rs".to_string(), Some(37))));
+        assert!(found.at(1).is_none());
+        assert!(found.at(2).is_none());
+    }
+
+    #[test]
+    fn function_ranges_of_instructions() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let base = container.code_base();
+        let ranges = |offset: usize| {
+            let ranges = container.function_ranges(base + offset)?;
+            Some(
+                (0..ranges.size())
+                    .map(|i| (ranges.at_start(i), ranges.at_end(i)))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        // `step` covers 0x23..0x64 of the code section
+        assert_eq!(ranges(0x40), Some(vec![(base + 0x23, base + 0x64)]));
+        // between `first` and `step`, and past the last function
+        assert_eq!(ranges(0x22), None);
+        assert_eq!(ranges(0x64), None);
+    }
+
+    #[test]
+    fn function_of_line() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let function = |line| {
+            let info = WasmLineInfo::new("basic.rs".to_string(), Some(line), None);
+            let functions = container.function_from_file_info(&info)?;
+            assert_eq!(functions.size(), 1);
+            functions.at_name(0)
+        };
+        assert_eq!(function(37).as_deref(), Some("step"));
+        assert_eq!(function(42).as_deref(), Some("first"));
+        // a comment and a blank line have no code
+        assert_eq!(function(1), None);
+        assert_eq!(function(40), None);
+    }
+
+    #[test]
+    fn inlined_frames() {
+        let container = DwarfDebugSymbolContainer::new(INLINE).ok().unwrap();
+        let base = container.code_base();
+        let frames = |offset: usize| {
+            let frames = container.inlined_frame_list(base + offset).ok().unwrap();
+            (0..frames.size())
+                .map(|i| {
+                    let file = frames.at_call_file(i).unwrap();
+                    assert!(file.ends_with("inline.c"), "{}", file);
+                    (frames.at_name(i).unwrap(), frames.at_call_line(i).unwrap())
+                })
+                .collect::<Vec<_>>()
+        };
+        // innermost first, each with the site it was called from
+        assert_eq!(
+            frames(0x5),
+            [("twice".to_string(), 6), ("scale".to_string(), 10)]
+        );
+        assert_eq!(frames(0x10), [("scale".to_string(), 10)]);
+        assert!(frames(0x1b).is_empty());
+    }
+
+    #[test]
+    fn function_entries() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let base = container.code_base();
+        assert!(container.is_function_entry(base + 0x2));
+        assert!(container.is_function_entry(base + 0x23));
+        assert!(!container.is_function_entry(base + 0x24));
+        assert!(!container.is_function_entry(base));
+        assert!(!container.is_function_entry(base - 1));
+    }
+
+    #[test]
+    fn runtime_offset() {
+        let mut container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        container.set_runtime_offset(0x10000);
+        let base = container.code_base + 0x10000;
+
+        let info = WasmLineInfo::new("basic.rs".to_string(), Some(37), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        assert_eq!(pc, base + 0x40);
+        let line = container.find_file_info_from_address(pc).unwrap().line;
+        assert_eq!(line, Some(37));
+        assert!(container
+            .find_file_info_from_address(container.code_base + 0x40)
+            .is_none());
+
+        let locals = values(&[3, 0x1000]);
+        let empty = values(&[]);
+        let frame = container.frame(&locals, &empty, &empty, &None);
+        let current = container
+            .evaluate_variable(&VariableQuery::Name("current"), &frame, pc)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            evaluate(current, &[(0x100c, &3i32.to_le_bytes())]).as_deref(),
+            Some("(i32)3")
+        );
+
+        // the name section labels the relocated code too
+        let mut container = DwarfDebugSymbolContainer::parse(FORMS).unwrap();
+        container.set_runtime_offset(0x10000);
+        let info = WasmLineInfo::new("forms.c".to_string(), Some(4), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        assert_eq!(
+            container.function_name_from_address(pc).as_deref(),
+            Some("inspect")
+        );
+        assert_eq!(container.function_name_from_address(pc - 0x10000), None);
+    }
+
+    #[test]
+    fn memory64_pointers() {
+        let container = DwarfDebugSymbolContainer::new(MEMORY64).ok().unwrap();
+        let walk = container.find_subroutine_by_name("walk");
+        let pc = container.find_prologue_end(walk.at_low_pc(0)).unwrap();
+
+        // the frame base lives in local 2, and `n` points past the first 4GiB
+        let locals = values(&[0, 0, 0x1000]);
+        let empty = values(&[]);
+        let node = 0x1_0000_2000u64;
+        let mut node_bytes = 42i32.to_le_bytes().to_vec();
+        node_bytes.resize(16, 0);
+        let memory: [(usize, &[u8]); 2] =
+            [(0x1000, &node.to_le_bytes()), (node as usize, &node_bytes)];
+
+        let info = |name: &str| {
+            container
+                .get_variable_info(name.to_string(), &locals, &empty, &empty, pc, None, None)
+                .ok()
+                .unwrap()
+                .info()
+                .unwrap()
+        };
+
+        let n = info("n");
+        assert_eq!(n.byte_size, 8);
+        assert_eq!(evaluate(n, &memory).as_deref(), Some("0x100002000 -> Node"));
+        assert_eq!(
+            evaluate(info("n.value"), &memory).as_deref(),
+            Some("(i32)42")
+        );
+
+        let counter = info("COUNTER");
+        let counter_bytes = 7u64.to_le_bytes();
+        assert_eq!(
+            evaluate(counter, &[(0x100000, &counter_bytes)]).as_deref(),
+            Some("(u64)7")
+        );
+    }
+
+    #[test]
+    fn pointer_members() {
+        let container = DwarfDebugSymbolContainer::new(MEMORY64).ok().unwrap();
+        let walk = container.find_subroutine_by_name("walk");
+        let pc = container.find_prologue_end(walk.at_low_pc(0)).unwrap();
+        let locals = values(&[0, 0, 0x1000]);
+        let empty = values(&[]);
+        let node = 0x1_0000_2000u64;
+        let mut node_bytes = 42i32.to_le_bytes().to_vec();
+        node_bytes.resize(16, 0);
+        let memory: [(usize, &[u8]); 1] = [(node as usize, &node_bytes)];
+
+        // `n` is read through the memory callback, leaving only the member
+        let reader: JsMemoryReader = Box::new(move |address, size| match address {
+            0x1000 => Some(node.to_le_bytes()[..size].to_vec()),
+            _ => None,
+        });
+        let reader = Some(reader);
+        let frame = container.frame(&locals, &empty, &empty, &reader);
+        for path in ["n.value", "n->value"].iter() {
+            let value = container
+                .evaluate_variable(&VariableQuery::Name(path), &frame, pc)
+                .unwrap()
+                .unwrap();
+            assert_eq!(evaluate(value, &memory).as_deref(), Some("(i32)42"));
+        }
+
+        let null: JsMemoryReader = Box::new(|_, size| Some(vec![0; size]));
+        let null = Some(null);
+        let frame = container.frame(&locals, &empty, &empty, &null);
+        match container.evaluate_variable(&VariableQuery::Name("n->value"), &frame, pc) {
+            Err(BridgeError::Unavailable(message)) => {
+                assert!(message.contains("null dereference"))
+            }
+            _ => panic!("a null pointer was followed"),
+        }
+    }
+
+    #[test]
+    fn line_ranges() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let ranges = container.line_ranges();
+        let base = container.code_base();
+        let ranges: Vec<_> = (0..ranges.size())
+            .map(|i| {
+                assert!(ranges.at_file(i).ends_with("basic.rs"));
+                let line = ranges.at_line(i).unwrap();
+                (ranges.at_start(i) - base, ranges.at_end(i) - base, line)
+            })
+            .collect();
+        // the two rows of line 38 are merged, and nothing covers the byte
+        // between the sequences
+        assert_eq!(
+            ranges,
+            [
+                (0x02, 0x1e, 42),
+                (0x1e, 0x22, 44),
+                (0x23, 0x40, 35),
+                (0x40, 0x48, 37),
+                (0x48, 0x57, 38),
+                (0x57, 0x64, 39),
+            ]
+        );
+    }
+
+    #[test]
+    fn statement_starts() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let base = container.code_base();
+        for offset in &[0x02, 0x1e, 0x23, 0x40, 0x48, 0x57] {
+            assert!(container.is_statement_start(base + offset), "{:#x}", offset);
+        }
+        // 0x51 starts a row of line 38 that isn't a statement, 0x49 is inside
+        // one, and 0x22 ends a sequence
+        for offset in &[0x51, 0x49, 0x22, 0x64] {
+            assert!(
+                !container.is_statement_start(base + offset),
+                "{:#x}",
+                offset
+            );
+        }
+        assert!(!container.is_statement_start(0));
+    }
+
+    #[test]
+    fn line_table_coverage() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let coverage = container.line_table_coverage();
+        let base = container.code_base();
+        assert_eq!(coverage.sequences, 2);
+        assert_eq!(coverage.covered_bytes, 0x20 + 0x41);
+        assert_eq!(coverage.gap_count(), 1);
+        assert_eq!(coverage.at_gap_start(0) - base, 0x22);
+        assert_eq!(coverage.at_gap_end(0) - base, 0x23);
+        assert_eq!(
+            coverage.to_json(),
+            format!(
+                "{{\"sequences\":2,\"coveredBytes\":97,\"gaps\":[[{},{}]]}}",
+                base + 0x22,
+                base + 0x23
+            )
+        );
+    }
+
+    /// Copy of a binary without its `.debug_*` sections
+    fn strip_debug_sections(binary: &[u8]) -> Vec<u8> {
+        let mut stripped = binary[..8].to_vec();
+        let mut rest = &binary[8..];
+        while !rest.is_empty() {
+            let mut reader = wasmparser::BinaryReader::new(&rest[1..]);
+            let size = reader.read_var_u32().unwrap() as usize;
+            let end = 1 + reader.original_position() + size;
+            let is_debug = rest[0] == 0 && reader.read_string().unwrap().starts_with(".debug_");
+            if !is_debug {
+                stripped.extend_from_slice(&rest[..end]);
+            }
+            rest = &rest[end..];
+        }
+        stripped
+    }
+
+    #[test]
+    fn separate_debug_sections() {
+        let stripped = strip_debug_sections(BASIC);
+        let stripped_only = DwarfDebugSymbolContainer::parse(&stripped).unwrap();
+        assert_eq!(stripped_only.line_table_coverage().sequences, 0);
+
+        let mut sections = crate::dwarf::load_custom_sections(BASIC, 0).unwrap();
+        sections.retain(|name, _| name.starts_with(".debug_"));
+        let container =
+            DwarfDebugSymbolContainer::load_with_sections(&stripped, sections.clone()).unwrap();
+        let original = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        assert_eq!(container.code_base(), original.code_base());
+        assert_eq!(
+            container.line_table_coverage().to_json(),
+            original.line_table_coverage().to_json()
+        );
+
+        sections.remove(".debug_abbrev");
+        let missing = DwarfDebugSymbolContainer::load_with_sections(&stripped, sections);
+        let error = BridgeError::from_anyhow(missing.err().unwrap());
+        assert_eq!(error.kind(), "InvalidDwarf");
+        assert_eq!(error.message(), "the .debug_abbrev section is missing");
+    }
+
+    #[test]
+    fn memory_snapshot() {
+        let mut memory = vec![0; 0x1801];
+        memory[0x1008..0x100c].copy_from_slice(&7i32.to_le_bytes());
+        memory[0x100c..0x1010].copy_from_slice(&0x1800u32.to_le_bytes());
+        memory[0x1800] = b'a';
+        let mut container = DwarfDebugSymbolContainer::parse(BASIC).unwrap();
+        container.set_memory_snapshot(Rc::from(memory));
+        let info = WasmLineInfo::new("basic.rs".to_string(), Some(44), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        // the frame base lives in local 2, and the pointer is followed in the
+        // snapshot right away
+        let locals = values(&[0, 0, 0x1000]);
+        let empty = values(&[]);
+        let frame = container.frame(&locals, &empty, &empty, &None);
+        let variable = |name| {
+            container
+                .evaluate_variable(&VariableQuery::Name(name), &frame, pc)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(variable("value").evaluate().as_deref(), Some("(i32)7"));
+        assert_eq!(
+            variable("_rest").evaluate().as_deref(),
+            Some("0x1800 -> (u8)97")
+        );
+
+        // memory past the end of the snapshot is still asked for
+        container.set_memory_snapshot(Rc::from(vec![0; 0x100e]));
+        let frame = container.frame(&locals, &empty, &empty, &None);
+        let mut rest = container
+            .evaluate_variable(&VariableQuery::Name("_rest"), &frame, pc)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rest.evaluate(), None);
+        assert_eq!(rest.required_memory_slice().address, 0x100c);
+    }
+
+    #[test]
+    fn breakable_locations() {
+        let container = DwarfDebugSymbolContainer::new(MEMORY64).ok().unwrap();
+        let files = container.source_file_list();
+        let source = (0..files.size())
+            .map(|i| files.at(i))
+            .find(|file| file.ends_with("memory64.rs"))
+            .unwrap();
+
+        let locations = container.breakable_locations(&source);
+        let locations: Vec<_> = (0..locations.size())
+            .map(|i| {
+                let location = locations.at(i).unwrap();
+                (location.line, location.column)
+            })
+            .collect();
+        assert_eq!(
+            locations,
+            [(Some(42), None), (Some(44), Some(14)), (Some(45), Some(2))]
+        );
+        assert_eq!(container.breakable_locations("missing.rs").size(), 0);
+
+        // breakpoints move forward to the next line with code
+        let resolve = |line| {
+            container
+                .resolve_breakpoint(&source, line)
+                .map(|breakpoint| (breakpoint.file(), breakpoint.line, breakpoint.address))
+        };
+        let line_44 = WasmLineInfo::new(source.clone(), Some(44), None);
+        let address = container.find_address_from_file_info(&line_44).unwrap();
+        assert_eq!(resolve(43), Some((source.clone(), 44, address)));
+        assert_eq!(resolve(44), resolve(43));
+        assert_eq!(resolve(42).map(|x| x.1), Some(42));
+        assert_eq!(resolve(46), None);
+        assert!(container.resolve_breakpoint("missing.rs", 1).is_none());
+    }
+
+    #[test]
+    fn path_substitution() {
+        let container = DwarfDebugSymbolContainer::new(MEMORY64).ok().unwrap();
+        // built with `--remap-path-prefix "$PWD=."`, so the path is relative
+        let files = |container: &DwarfDebugSymbolContainer| {
+            let files = container.source_file_list();
+            (0..files.size()).map(|i| files.at(i)).collect::<Vec<_>>()
+        };
+        assert!(files(&container).contains(&"memory64.rs".to_string()));
+
+        container.add_path_substitution(".", "/home/dev/fixtures");
+        assert!(files(&container).contains(&"/home/dev/fixtures/memory64.rs".to_string()));
+
+        let address = container
+            .find_address_from_file_info(&WasmLineInfo::new(
+                "/home/dev/fixtures/./memory64.rs".to_string(),
+                Some(45),
+                None,
+            ))
+            .unwrap();
+        let info = container.find_file_info_from_address(address).unwrap();
+        assert_eq!(info.file(), "/home/dev/fixtures/memory64.rs");
+        assert_eq!(info.line, Some(45));
+
+        // the recorded path keeps working
+        let info = WasmLineInfo::new("memory64.rs".to_string(), Some(45), None);
+        assert_eq!(container.find_address_from_file_info(&info), Some(address));
+        assert_eq!(
+            container
+                .breakable_locations("/home/dev/src/memory64.rs")
+                .size(),
+            0
+        );
+
+        let functions = container.function_list();
+        assert!((0..functions.size()).any(|i| {
+            functions.at_decl_file(i).as_deref() == Some("/home/dev/fixtures/memory64.rs")
+        }));
+
+        // a longer prefix takes precedence over the directory substitution
+        container.add_path_substitution("memory64.rs", "/ci/checkout/memory64.rs");
+        assert!(files(&container).contains(&"/ci/checkout/memory64.rs".to_string()));
+        let info = WasmLineInfo::new("/ci/checkout/memory64.rs".to_string(), Some(45), None);
+        assert_eq!(container.find_address_from_file_info(&info), Some(address));
+    }
+
+    #[test]
+    fn overlapping_path_substitutions() {
+        let info = |path: &str| WasmLineInfo::new(path.to_string(), Some(45), None);
+        let substitutions = [(".", "/src"), ("memory64.rs", "/src/lib/memory64.rs")];
+        // the longest prefix wins whichever order the substitutions come in
+        for order in [[0, 1], [1, 0]].iter() {
+            let container = DwarfDebugSymbolContainer::new(MEMORY64).ok().unwrap();
+            let address = container
+                .find_address_from_file_info(&info("memory64.rs"))
+                .unwrap();
+            for i in order {
+                let (from, to) = substitutions[*i];
+                container.add_path_substitution(from, to);
+            }
+
+            let files = container.source_file_list();
+            let files: Vec<_> = (0..files.size()).map(|i| files.at(i)).collect();
+            assert!(files.contains(&"/src/lib/memory64.rs".to_string()));
+            assert!(!files.contains(&"/src/memory64.rs".to_string()));
+            let file = container.find_file_info_from_address(address).unwrap();
+            assert_eq!(file.file(), "/src/lib/memory64.rs");
+
+            // undoing the shorter prefix would look for `lib/memory64.rs`
+            let found = container.find_address_from_file_info(&info("/src/lib/memory64.rs"));
+            assert_eq!(found, Some(address));
+        }
+    }
+
+    #[test]
+    fn index_roundtrip() {
+        let parsed = DwarfDebugSymbolContainer::new(MEMORY64).ok().unwrap();
+        let index = parsed.serialize_index();
+        let indexed = DwarfDebugSymbolContainer::from_index(&index, MEMORY64)
+            .ok()
+            .unwrap();
+        assert_eq!(indexed.serialize_index(), index);
+
+        let walk = parsed.find_subroutine_by_name("walk");
+        let indexed_walk = indexed.find_subroutine_by_name("walk");
+        assert_eq!(indexed_walk.at_low_pc(0), walk.at_low_pc(0));
+        assert_eq!(indexed_walk.at_high_pc(0), walk.at_high_pc(0));
+        for address in walk.at_low_pc(0)..walk.at_high_pc(0) {
+            let location = |container: &DwarfDebugSymbolContainer| {
+                container
+                    .find_file_info_from_address(address)
+                    .map(|info| (info.file(), info.line, info.column))
+            };
+            assert_eq!(location(&indexed), location(&parsed));
+        }
+        assert_eq!(
+            indexed.find_prologue_end(walk.at_low_pc(0)),
+            parsed.find_prologue_end(walk.at_low_pc(0))
+        );
+
+        let counter = indexed
+            .get_variable_info(
+                "COUNTER".to_string(),
+                &values(&[]),
+                &values(&[]),
+                &values(&[]),
+                walk.at_low_pc(0),
+                None,
+                None,
+            )
+            .ok()
+            .unwrap()
+            .info()
+            .unwrap();
+        let counter_bytes = 7u64.to_le_bytes();
+        assert_eq!(
+            evaluate(counter, &[(0x100000, &counter_bytes)]).as_deref(),
+            Some("(u64)7")
+        );
+    }
+
+    #[test]
+    fn variable_status() {
+        let container = DwarfDebugSymbolContainer::new(OPTIMIZED).ok().unwrap();
+        let files = container.source_file_list();
+        let source = (0..files.size())
+            .map(|i| files.at(i))
+            .find(|file| file.ends_with("optimized.rs"))
+            .unwrap();
+        let line = |line| {
+            let info = WasmLineInfo::new(source.clone(), Some(line), None);
+            container.find_address_from_file_info(&info).unwrap()
+        };
+
+        // `first` lives on the operand stack until its last use on line 51
+        let locals = values(&[5]);
+        let stacks = values(&[9]);
+        let empty = values(&[]);
+        let lookup = |name: &str, pc| {
+            container
+                .get_variable_info(name.to_string(), &locals, &empty, &stacks, pc, None, None)
+                .ok()
+                .unwrap()
+        };
+
+        let found = lookup("first", line(51));
+        assert_eq!(found.status(), VariableStatus::Found);
+        assert_eq!(
+            evaluate(found.info().unwrap(), &[]).as_deref(),
+            Some("(i32)9")
+        );
+
+        let optimized_out = lookup("first", line(52));
+        assert_eq!(optimized_out.status(), VariableStatus::OptimizedOut);
+        assert!(optimized_out.info().is_none());
+        assert_eq!(lookup("a", line(52)).status(), VariableStatus::Found);
+
+        let missing = lookup("second", line(52));
+        assert_eq!(missing.status(), VariableStatus::NotFound);
+        assert!(missing.info().is_none());
+    }
+
+    #[test]
+    fn operand_stack_values() {
+        let container = DwarfDebugSymbolContainer::new(STACK).ok().unwrap();
+        // at the call on line 6 its arguments are on the stack, `offset` being
+        // the second, and only the low byte of the i32 holding it belongs to it
+        let pc = container.code_base() + 0x12;
+        let locals = values(&[-2, 0x2fe]);
+        let stacks = values(&[-6, 0x2ff]);
+        let empty = values(&[]);
+        let frame = container.frame(&locals, &empty, &stacks, &None);
+        let offset = container
+            .evaluate_variable(&VariableQuery::Name("offset"), &frame, pc)
+            .unwrap()
+            .unwrap();
+        assert_eq!(evaluate(offset, &[]).as_deref(), Some("(unsigned char)255"));
+
+        // a runtime reporting a shallower stack leaves it unavailable
+        let stacks = values(&[-6]);
+        let frame = container.frame(&locals, &empty, &stacks, &None);
+        let missing = container.evaluate_variable(&VariableQuery::Name("offset"), &frame, pc);
+        assert!(matches!(missing, Err(BridgeError::Unavailable(_))));
+    }
+
+    #[test]
+    fn dwarf5_forms() {
+        let container = DwarfDebugSymbolContainer::parse(FORMS).unwrap();
+        // the unit and `inspect` only list their ranges with DW_FORM_rnglistx
+        let info = WasmLineInfo::new("forms.c".to_string(), Some(4), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        assert_eq!(
+            container.function_name_from_address(pc).as_deref(),
+            Some("inspect")
+        );
+        assert_eq!(
+            container.function_signature(pc).ok().unwrap(),
+            "int inspect(int)"
+        );
+
+        let locals = values(&[0, 0, 0, 0x1000]);
+        let empty = values(&[]);
+        let frame = container.frame(&locals, &empty, &empty, &None);
+        let variable = |name| {
+            container
+                .evaluate_variable(&VariableQuery::Name(name), &frame, pc)
+                .unwrap()
+                .unwrap()
+        };
+        let mut memory = 1i32.to_le_bytes().to_vec();
+        memory.extend_from_slice(&2i32.to_le_bytes());
+        memory.extend_from_slice(&[0; 4]);
+        memory.extend_from_slice(&41i32.to_le_bytes());
+        let memory = [(0x1000, &memory[..])];
+        assert_eq!(
+            evaluate(variable("value"), &memory).as_deref(),
+            Some("(int)41")
+        );
+        assert_eq!(
+            evaluate(variable("limit"), &memory).as_deref(),
+            Some("(int)-2")
+        );
+        // `pair` and its members have their sizes and offsets as implicit constants
+        assert_eq!(evaluate(variable("pair"), &memory).as_deref(), Some("pair"));
+        assert_eq!(
+            evaluate(variable("pair.second"), &memory).as_deref(),
+            Some("(int)2")
+        );
+    }
+
+    #[test]
+    fn partial_units() {
+        let parsed = DwarfDebugSymbolContainer::new(PARTIAL).ok().unwrap();
+        let indexed = DwarfDebugSymbolContainer::from_index(&parsed.serialize_index(), PARTIAL)
+            .ok()
+            .unwrap();
+        for container in [parsed, indexed].iter() {
+            // the line program of the importing unit covers `inspect`, which
+            // lives in the partial unit along with its `int`
+            let info = WasmLineInfo::new("partial.c".to_string(), Some(4), None);
+            let pc = container.find_address_from_file_info(&info).unwrap();
+            assert_eq!(
+                container.function_name_from_address(pc).as_deref(),
+                Some("inspect")
+            );
+            // the partial unit's root leaves the producer to its importer
+            assert_eq!(
+                container.producer(pc).ok().unwrap().as_deref(),
+                Some("hand-written")
+            );
+
+            let locals = values(&[0, 0, 0, 0x1000]);
+            let empty = values(&[]);
+            let frame = container.frame(&locals, &empty, &empty, &None);
+            let value = container
+                .evaluate_variable(&VariableQuery::Name("value"), &frame, pc)
+                .unwrap()
+                .unwrap();
+            let bytes = 41i32.to_le_bytes();
+            assert_eq!(
+                evaluate(value, &[(0x100c, &bytes)]).as_deref(),
+                Some("(int)41")
+            );
+        }
+    }
+
+    #[test]
+    fn raw_locations() {
+        let container = DwarfDebugSymbolContainer::new(OPTIMIZED).ok().unwrap();
+        let info = WasmLineInfo::new("optimized.rs".to_string(), Some(51), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        let location = |container: &DwarfDebugSymbolContainer, name: &str, pc| {
+            container
+                .raw_variable_location(name.to_string(), pc)
+                .ok()
+                .unwrap()
+                .map(|raw| (raw.expression(), raw.is_location_list))
+        };
+
+        // `first` is on the operand stack at line 51 and gone right after
+        let first = location(&container, "first", pc);
+        assert_eq!(first, Some((vec![0xed, 0x02, 0x00, 0x9f], true)));
+        let info = WasmLineInfo::new("optimized.rs".to_string(), Some(52), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        assert_eq!(location(&container, "first", pc), None);
+
+        // globals are looked up once no local of the name is in scope
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let info = WasmLineInfo::new("basic.rs".to_string(), Some(37), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        let (expression, is_location_list) = location(&container, "STEPS", pc).unwrap();
+        assert_eq!(expression[0], gimli::DW_OP_addr.0);
+        assert!(!is_location_list);
+    }
+
+    #[test]
+    fn available_ranges() {
+        let container = DwarfDebugSymbolContainer::new(OPTIMIZED).ok().unwrap();
+        let info = WasmLineInfo::new("optimized.rs".to_string(), Some(51), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        let base = container.code_base();
+        let variables = container.variable_name_list(pc).ok().unwrap();
+        let found: Vec<_> = (0..variables.size())
+            .map(|i| {
+                let ranges: Vec<_> = (0..variables.at_available_range_count(i))
+                    .map(|range| {
+                        (
+                            variables.at_available_range_start(i, range) - base,
+                            variables.at_available_range_end(i, range) - base,
+                        )
+                    })
+                    .collect();
+                (
+                    variables.at_name(i),
+                    variables.at_is_available_throughout(i),
+                    ranges,
+                )
+            })
+            .collect();
+        // `a` has a location list covering all of the function
+        assert_eq!(
+            found,
+            [
+                ("a".to_string(), false, vec![(0x2, 0x20)]),
+                ("first".to_string(), false, vec![(0x9, 0xf)]),
+            ]
+        );
+
+        // a single expression locates the variables of unoptimized code
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        let info = WasmLineInfo::new("basic.rs".to_string(), Some(37), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        let variables = container.variable_name_list(pc).ok().unwrap();
+        assert!((0..variables.size()).all(|i| variables.at_is_available_throughout(i)));
+        assert_eq!(variables.at_available_range_count(0), 0);
+    }
+
+    #[test]
+    fn dereferenced_captures() {
+        let container = DwarfDebugSymbolContainer::new(CLOSURE).ok().unwrap();
+        let info = WasmLineInfo::new("closure.rs".to_string(), Some(70), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        // the closure's frame base lives in local 2, and `total` is found
+        // through the environment pointer stored in the frame
+        let locals = values(&[0, 0, 0x1000]);
+        let empty = values(&[]);
+        let read = |address: u64, size: usize| {
+            let value: u32 = match address {
+                0x1008 => 0x2000,
+                0x2000 => 0x3000,
+                _ => return None,
+            };
+            Some(value.to_le_bytes()[..size].to_vec())
+        };
+        let frame = WasmFrame {
+            locals: &locals,
+            globals: &empty,
+            stacks: &empty,
+            data_base: 0,
+            tls_base: None,
+            memory: Some(&read),
+            registers: None,
+        };
+        let total = container
+            .evaluate_variable(&VariableQuery::Name("total"), &frame, pc)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            evaluate(total, &[(0x3000, &42i32.to_le_bytes())]).as_deref(),
+            Some("(i32)42")
+        );
+
+        let unreadable = WasmFrame {
+            memory: None,
+            ..frame
+        };
+        assert!(matches!(
+            container.evaluate_variable(&VariableQuery::Name("total"), &unreadable, pc),
+            Err(BridgeError::Unavailable(_))
+        ));
+        let lookup = container
+            .get_variable_info("total".to_string(), &locals, &empty, &empty, pc, None, None)
+            .ok()
+            .unwrap();
+        assert_eq!(lookup.status(), VariableStatus::Unavailable);
+        assert_eq!(
+            container
+                .get_variable_info("v".to_string(), &locals, &empty, &empty, pc, None, None)
+                .ok()
+                .unwrap()
+                .status(),
+            VariableStatus::Found
+        );
+    }
+
+    #[test]
+    fn thread_local_variables() {
+        let container = DwarfDebugSymbolContainer::new(TLS).ok().unwrap();
+        assert_eq!(container.tls_base_global, Some(1));
+        let info = WasmLineInfo::new("tls.c".to_string(), Some(5), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        // this thread's block starts at 0x3000, past `__stack_pointer`
+        let globals = values(&[0x10000, 0x3000]);
+        let empty = values(&[]);
+        let frame = container.frame(&empty, &globals, &empty, &None);
+        let lookup = |name: &str| {
+            container
+                .evaluate_variable(&VariableQuery::Name(name), &frame, pc)
+                .unwrap()
+                .unwrap()
+        };
+        let memory: &[(usize, &[u8])] = &[(0x3000, &[1, 0, 0, 0, 5, 0, 0, 0])];
+        assert_eq!(evaluate(lookup("depth"), memory).as_deref(), Some("(int)1"));
+        assert_eq!(
+            evaluate(lookup("counter"), memory).as_deref(),
+            Some("(int)5")
+        );
+    }
+
+    #[test]
+    fn producers() {
+        let producer = |data: &[u8], pc: usize| {
+            let container = DwarfDebugSymbolContainer::new(data).ok().unwrap();
+            container.producer(container.code_base + pc).ok().unwrap()
+        };
+        assert!(producer(BASIC, 0x02)
+            .unwrap()
+            .starts_with("clang LLVM (rustc version"));
+        assert_eq!(producer(TYPED, 0x02).as_deref(), Some("hand-written"));
+    }
+
+    #[test]
+    fn typed_operations() {
+        let container = DwarfDebugSymbolContainer::new(TYPED).ok().unwrap();
+        let info = WasmLineInfo::new("typed.c".to_string(), Some(6), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        let locals = values(&[0x1234_80ff]);
+        let empty = values(&[]);
+        let frame = container.frame(&locals, &empty, &empty, &None);
+        let lookup = |name: &str| {
+            let info = container
+                .evaluate_variable(&VariableQuery::Name(name), &frame, pc)
+                .unwrap()
+                .unwrap();
+            evaluate(info, &[])
+        };
+        assert_eq!(lookup("packed").as_deref(), Some("(int)305430783"));
+        assert_eq!(lookup("low").as_deref(), Some("(int)-1"));
+        assert_eq!(lookup("high").as_deref(), Some("(unsigned long long)33023"));
+    }
+
+    #[test]
+    fn scope_groups() {
+        let container = DwarfDebugSymbolContainer::new(CLOSURE).ok().unwrap();
+        let info = WasmLineInfo::new("closure.rs".to_string(), Some(70), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        let scopes = container.scope_list(pc).ok().unwrap();
+        let names = |index: usize| {
+            let variables = scopes.at_variables(index);
+            (0..variables.size())
+                .filter(|&i| variables.at_group_id(i) == scopes.at_group_id(index))
+                .map(|i| variables.at_name(i))
+                .collect::<Vec<_>>()
+        };
+        let labels = (0..scopes.size())
+            .map(|i| scopes.at_label(i))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["Local", "Closure", "Global"]);
+        assert!(names(0).contains(&"v".to_string()));
+        assert!(!names(0).contains(&"total".to_string()));
+        assert_eq!(names(1), ["total"]);
+        assert_ne!(scopes.at_group_id(0), scopes.at_group_id(1));
+    }
+
+    #[test]
+    fn indexed_strings() {
+        let container = DwarfDebugSymbolContainer::new(DWARF5).ok().unwrap();
+        let files = container.source_file_list();
+        assert!((0..files.size()).any(|i| files.at(i) == "dwarf5.rs"));
+
+        let functions = container.function_list();
+        let index = (0..functions.size())
+            .find(|i| functions.at_name(*i).as_deref() == Some("indexed"))
+            .unwrap();
+        assert_eq!(functions.at_decl_file(index).as_deref(), Some("dwarf5.rs"));
+
+        let info = WasmLineInfo::new("dwarf5.rs".to_string(), Some(37), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+        let variables = container.variable_name_list(pc).ok().unwrap();
+        let variables: Vec<_> = (0..variables.size())
+            .map(|i| (variables.at_name(i), variables.at_type_name(i)))
+            .collect();
+        assert!(variables.contains(&("count".to_string(), "i32".to_string())));
+        assert!(variables.contains(&("doubled".to_string(), "i32".to_string())));
+
+        let globals = container.global_variable_name_list(pc).ok().unwrap();
+        assert!((0..globals.size()).any(|i| globals.at_name(i) == "LIMIT"));
+    }
+
+    #[test]
+    fn union_arms() {
+        let container = DwarfDebugSymbolContainer::new(UNION).ok().unwrap();
+        let info = WasmLineInfo::new("union.rs".to_string(), Some(42), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        // the frame base lives in local 1, and `bits` is 8 bytes into it
+        let locals = values(&[0, 0x1000]);
+        let empty = values(&[]);
+        let bytes = 1.5f32.to_le_bytes();
+        let memory: [(usize, &[u8]); 1] = [(0x1008, &bytes)];
+        let info = |name: &str| {
+            container
+                .get_variable_info(name.to_string(), &locals, &empty, &empty, pc, None, None)
+                .ok()
+                .unwrap()
+                .info()
+                .unwrap()
+        };
+
+        let bits = info("bits");
+        let arms = bits.members_at(0);
+        let arms: Vec<_> = (0..arms.size())
+            .map(|i| (arms.at_name(i), arms.at_type_name(i)))
+            .collect();
+        assert_eq!(
+            arms,
+            [
+                ("int".to_string(), "i32".to_string()),
+                ("float".to_string(), "f32".to_string())
+            ]
+        );
+        assert_eq!(bits.members_at(4).size(), 0);
+
+        assert_eq!(
+            evaluate(info("bits.float"), &memory).as_deref(),
+            Some("(f32)1.5")
+        );
+        assert_eq!(
+            evaluate(info("bits.int"), &memory).as_deref(),
+            Some("(i32)1069547520")
+        );
+    }
+
+    #[test]
+    fn frame_base_locals() {
+        let container = DwarfDebugSymbolContainer::new(FRAME).ok().unwrap();
+        let info = WasmLineInfo::new("frame.c".to_string(), Some(6), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        // the frame base is the stack pointer copied into local 3, and each
+        // local is at its DW_OP_fbreg offset from it
+        let locals = values(&[0, 0, 0, 0x1000]);
+        let empty = values(&[]);
+        let bytes = [12, 0, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 5, 0, 0, 0];
+        let memory: [(usize, &[u8]); 1] = [(0x1000, &bytes)];
+        let info = |name: &str| {
+            container
+                .get_variable_info(name.to_string(), &locals, &empty, &empty, pc, None, None)
+                .ok()
+                .unwrap()
+                .info()
+                .unwrap()
+        };
+        assert_eq!(
+            evaluate(info("total"), &memory).as_deref(),
+            Some("(long long)12")
+        );
+        assert_eq!(evaluate(info("count"), &memory).as_deref(), Some("(int)6"));
+        assert_eq!(evaluate(info("value"), &memory).as_deref(), Some("(int)5"));
+    }
+
+    #[test]
+    fn relocatable_data() {
+        let read_counter = |binary, globals: &[i64], address| {
+            let container = DwarfDebugSymbolContainer::new(binary).ok().unwrap();
+            let info = WasmLineInfo::new("pic.c".to_string(), Some(4), None);
+            let pc = container.find_address_from_file_info(&info).unwrap();
+            let (locals, globals, empty) = (values(&[1]), values(globals), values(&[]));
+            let info = container
+                .get_variable_info(
+                    "counter".to_string(),
+                    &locals,
+                    &globals,
+                    &empty,
+                    pc,
+                    None,
+                    None,
+                )
+                .ok()
+                .unwrap()
+                .info()
+                .unwrap();
+            evaluate(info, &[(address, &7i32.to_le_bytes())])
+        };
+        // `counter` is at 0 from the imported `__memory_base`, global 0
+        assert_eq!(
+            read_counter(PIC, &[0x2000, 0, 0], 0x2000).as_deref(),
+            Some("(int)7")
+        );
+        // a module linked at fixed addresses keeps them as they are, wherever
+        // its data segments start
+        let container = DwarfDebugSymbolContainer::new(GLOBALS).ok().unwrap();
+        assert_eq!(container.data_base(), 0x10000);
+        let empty = values(&[]);
+        let frame = container.frame(&empty, &empty, &empty, &None);
+        assert_eq!(frame.data_base, 0);
+    }
+
+    #[test]
+    fn shadowed_variables() {
+        let container = DwarfDebugSymbolContainer::new(SHADOW).ok().unwrap();
+        let info = WasmLineInfo::new("shadow.c".to_string(), Some(7), None);
+        let pc = container.find_address_from_file_info(&info).unwrap();
+
+        // both `value`s are listed, each evaluated from its own DIE
+        let variables = container.variable_name_list(pc).ok().unwrap();
+        let offsets: Vec<_> = (0..variables.size())
+            .filter(|&i| variables.at_name(i) == "value")
+            .map(|i| variables.at_die_offset(i).unwrap())
+            .collect();
+        assert_eq!(offsets.len(), 2);
+        assert_ne!(offsets[0], offsets[1]);
+
+        let locals = values(&[3, 0, 4, 0, 6]);
+        let empty = values(&[]);
+        let mut evaluated: Vec<_> = offsets
+            .iter()
+            .map(|&offset| {
+                let info = container
+                    .get_variable_info_by_offset(offset, &locals, &empty, &empty, pc, None, None)
+                    .ok()
+                    .unwrap()
+                    .info()
+                    .unwrap();
+                evaluate(info, &[]).unwrap()
+            })
+            .collect();
+        evaluated.sort();
+        assert_eq!(evaluated, ["(int)4", "(int)6"]);
+    }
+
+    #[test]
+    fn split_units() {
+        // the frame base is the stack pointer in global 0
+        let locals = values(&[5]);
+        let globals = values(&[0x1000]);
+        let empty = values(&[]);
+        let variable = |container: &DwarfDebugSymbolContainer, file: &str, line, name: &str| {
+            let info = WasmLineInfo::new(file.to_string(), Some(line), None);
+            let pc = container.find_address_from_file_info(&info).unwrap();
+            let info = container
+                .get_variable_info(name.to_string(), &locals, &globals, &empty, pc, None, None)
+                .ok()?
+                .info()?;
+            evaluate(info, &[(0x10000, &7i32.to_le_bytes())])
+        };
+
+        // the skeleton units only hold line tables and ranges, and the
+        // variables are found in the split units
+        let container = DwarfDebugSymbolContainer::new_with_split_dwarf(SPLIT, SPLIT_DWP)
+            .ok()
+            .unwrap();
+        let value = variable(&container, "split_a.c", 5, "value");
+        assert_eq!(value.as_deref(), Some("(int)5"));
+        let limit = variable(&container, "split_a.c", 5, "limit");
+        assert_eq!(limit.as_deref(), Some("(int)7"));
+        let input = variable(&container, "split_b.c", 2, "input");
+        assert_eq!(input.as_deref(), Some("(int)5"));
+
+        // the `.dwo` file of the first unit has no split unit for the second
+        let container = DwarfDebugSymbolContainer::new_with_split_dwarf(SPLIT, SPLIT_A_DWO)
+            .ok()
+            .unwrap();
+        let value = variable(&container, "split_a.c", 5, "value");
+        assert_eq!(value.as_deref(), Some("(int)5"));
+        assert_eq!(variable(&container, "split_b.c", 2, "input"), None);
+
+        let debug_data = DwarfDebugData::from_sections(
+            crate::dwarf::load_custom_sections(SPLIT, 0).unwrap(),
+            Some(SPLIT_A_DWO),
+        )
+        .unwrap();
+        let dwarf = debug_data.parse_dwarf().unwrap();
+        let mut headers = dwarf.units();
+        let mut split = Vec::new();
+        while let Some(header) = headers.next().unwrap() {
+            let skeleton = dwarf.unit(header).unwrap();
+            let unit = debug_data.split_unit(&skeleton).unwrap();
+            split.push(unit.map(|(_, unit)| unit.dwo_id));
+        }
+        assert_eq!(split.len(), 2);
+        assert!(split[0].is_some());
+        assert_eq!(split[1], None);
+    }
+
+    #[test]
+    fn anonymous_members() {
+        // DWARF 2 places the members with DW_OP_plus_uconst expressions
+        for binary in [ANONYMOUS, ANONYMOUS2].iter() {
+            let container = DwarfDebugSymbolContainer::new(binary).ok().unwrap();
+            let info = WasmLineInfo::new("anonymous.c".to_string(), Some(19), None);
+            let pc = container.find_address_from_file_info(&info).unwrap();
+
+            // the anonymous union and the structure nested in it add no level
+            let names = container.variable_name_list(pc).ok().unwrap();
+            let names: Vec<_> = (0..names.size())
+                .map(|i| names.at_display_name(i))
+                .filter(|name| name.starts_with("packet."))
+                .collect();
+            assert_eq!(
+                names,
+                ["packet.kind", "packet.count", "packet.low", "packet.high"]
+            );
+
+            // the frame base lives in local 3, and `packet` is right at it
+            let locals = values(&[0, 0, 0, 0x1000]);
+            let empty = values(&[]);
+            let bytes = [1, 0, 0, 0, 5, 0, 7, 0];
+            let memory: [(usize, &[u8]); 1] = [(0x1000, &bytes)];
+            let info = |name: &str| {
+                container
+                    .get_variable_info(name.to_string(), &locals, &empty, &empty, pc, None, None)
+                    .ok()
+                    .unwrap()
+                    .info()
+                    .unwrap()
+            };
+
+            let packet = info("packet");
+            let members = packet.members_at(4);
+            let members: Vec<_> = (0..members.size()).map(|i| members.at_name(i)).collect();
+            assert_eq!(members, ["count", "low"]);
+            assert_eq!(packet.members_at(6).at_name(0), "high");
+
+            assert_eq!(
+                evaluate(info("packet.count"), &memory).as_deref(),
+                Some("(int)458757")
+            );
+            assert_eq!(
+                evaluate(info("packet.high"), &memory).as_deref(),
+                Some("(short)7")
+            );
+        }
+    }
+
+    #[test]
+    fn packed_bit_fields() {
+        // ready = 1, level = -3, mode = 2 and count = -200 share one word
+        let bytes = (43u32 | (824 << 6)).to_le_bytes();
+        let memory: [(usize, &[u8]); 1] = [(0x1008, &bytes)];
+
+        for binary in [BITFIELD, BITFIELD4].iter() {
+            let container = DwarfDebugSymbolContainer::new(binary).ok().unwrap();
+            let info = WasmLineInfo::new("bitfield.c".to_string(), Some(15), None);
+            let pc = container.find_address_from_file_info(&info).unwrap();
+
+            // the frame base lives in local 3, and `flags` is 8 bytes into it
+            let locals = values(&[0, 0, 0, 0x1000]);
+            let empty = values(&[]);
+            let field = |name: &str| {
+                let info = container
+                    .get_variable_info(name.to_string(), &locals, &empty, &empty, pc, None, None)
+                    .ok()
+                    .unwrap()
+                    .info()
+                    .unwrap();
+                evaluate(info, &memory)
+            };
+
+            assert_eq!(field("flags.ready").as_deref(), Some("(unsigned int)1"));
+            assert_eq!(field("flags.level").as_deref(), Some("(int)-3"));
+            assert_eq!(field("flags.mode").as_deref(), Some("(unsigned char)2"));
+            assert_eq!(field("flags.count").as_deref(), Some("(short)-200"));
+        }
+    }
+
+    #[test]
+    fn globals_of_all_units() {
+        let container = DwarfDebugSymbolContainer::new(GLOBALS).ok().unwrap();
+        let globals = container.all_global_variable_name_list().ok().unwrap();
+        let globals: Vec<_> = (0..globals.size())
+            .map(|i| {
+                (
+                    globals.at_name(i),
+                    globals.at_type_name(i),
+                    globals.at_group_id(i),
+                    globals.at_chile_group_id(i),
+                )
+            })
+            .collect();
+
+        // both units' `config` namespaces are merged, and `limit` is listed once
+        let namespaces: Vec<_> = globals.iter().filter(|g| g.0 == "config").collect();
+        assert_eq!(namespaces.len(), 1);
+        assert_eq!(namespaces[0].2, 1001);
+        let mut members: Vec<_> = globals
+            .iter()
+            .filter(|g| Some(g.2) == namespaces[0].3)
+            .map(|g| (g.0.as_str(), g.1.as_str()))
+            .collect();
+        members.sort_unstable();
+        assert_eq!(members, [("limit", "int"), ("retries", "int")]);
+    }
+
+    #[test]
+    fn global_die_offsets() {
+        let container = DwarfDebugSymbolContainer::new(GLOBALS).ok().unwrap();
+        let globals = container.all_global_variable_name_list().ok().unwrap();
+        let retries = (0..globals.size())
+            .find(|&i| globals.at_name(i) == "retries")
+            .unwrap();
+        // declared in the second unit, which the pc doesn't belong to
+        assert_eq!(globals.at_unit_offset(retries), Some(0x41));
+        let die_offset = globals.at_die_offset(retries).unwrap();
+
+        let empty = values(&[]);
+        let frame = container.frame(&empty, &empty, &empty, &None);
+        let info = container
+            .evaluate_variable(
+                &VariableQuery::DieOffset(die_offset),
+                &frame,
+                container.code_base(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            evaluate(info, &[(0x10004, &3i32.to_le_bytes())]).as_deref(),
+            Some("(int)3")
+        );
+    }
+
+    #[test]
+    fn artificial_variables() {
+        let mut container = DwarfDebugSymbolContainer::new(VLA).ok().unwrap();
+        let pc = container.code_base() + 0x90;
+        let names = |container: &DwarfDebugSymbolContainer| {
+            let variables = container.variable_name_list(pc).ok().unwrap();
+            (0..variables.size())
+                .filter(|&i| variables.at_group_id(i) == 1000)
+                .map(|i| (variables.at_name(i), variables.at_is_artificial(i)))
+                .collect::<Vec<_>>()
+        };
+
+        // the length clang stores for `values` is hidden unless asked for
+        let listed = names(&container);
+        assert!(listed.iter().all(|(name, _)| name != "__vla_expr0"));
+        assert!(listed.contains(&("values".to_string(), false)));
+        container.set_artificial_variables(ArtificialVariables::Show);
+        assert!(names(&container).contains(&("__vla_expr0".to_string(), true)));
+    }
+
+    #[test]
+    fn dynamic_array_bounds() {
+        let container = DwarfDebugSymbolContainer::new(VLA).ok().unwrap();
+        let pc = container.code_base() + 0x90;
+        // the frame base lives in local 4, and the array `values` in local 14
+        let mut frame = vec![0; 15];
+        frame[4] = 0x1000;
+        frame[14] = 0x2000;
+        let locals = values(&frame);
+        let empty = values(&[]);
+        let info = |name: &str| {
+            container
+                .get_variable_info(name.to_string(), &locals, &empty, &empty, pc, None, None)
+                .ok()
+                .unwrap()
+                .info()
+                .unwrap()
+        };
+        let squares: Vec<u8> = (1..=8i32).flat_map(|v| (v * v).to_le_bytes()).collect();
+
+        // `__vla_expr0` holds the length of `values`, and is read first
+        let memory = [(0x1014, &3u32.to_le_bytes()[..]), (0x2000, &squares)];
+        assert_eq!(
+            evaluate(info("values"), &memory).as_deref(),
+            Some("[(int)1, (int)4, (int)9]")
+        );
+        assert_eq!(
+            evaluate(info("triple"), &[(0x1008, &squares)]).as_deref(),
+            Some("[(int)1, (int)4, (int)9]")
+        );
+
+        // a count supplied by the caller replaces the read
+        let mut values = info("values");
+        values.set_element_count(2);
+        assert_eq!(
+            evaluate(values, &[(0x2000, &squares)]).as_deref(),
+            Some("[(int)1, (int)4]")
+        );
+    }
+
+    /// Load a binary and ask for everything the container describes, ignoring
+    /// the errors
+    fn query_everything(data: &[u8]) {
+        let container = match DwarfDebugSymbolContainer::parse(data) {
+            Ok(container) => container,
+            Err(_) => return,
+        };
+        let info = &container.debug_info;
+        let _ = info.sourcemap.file_list();
+        let _ = info.global_variables.all_variable_names(1000);
+        for offset in (0..0x80).step_by(0x10) {
+            let _ = info.sourcemap.find_line_info(offset);
+            let _ = info.subroutine.variable_name_list(offset, 1000);
+            let _ = info.subroutine.inlined_subroutine_stack(offset);
+        }
+    }
+
+    #[test]
+    fn malformed_modules() {
+        for fixture in &[BASIC, ANONYMOUS, GLOBALS] {
+            // cut the binary short at every byte of its header and at sizes
+            // spread over the rest of it
+            let cuts = (0..64).chain((64..fixture.len()).step_by(fixture.len() / 512 + 1));
+            for len in cuts {
+                let _ = DwarfDebugSymbolContainer::parse(&fixture[..len]);
+            }
+
+            // corrupt bytes spread over the sections, answering every query
+            // the loaded containers are asked
+            for position in (8..fixture.len()).step_by(fixture.len() / 128 + 1) {
+                let mut corrupted = fixture.to_vec();
+                corrupted[position] ^= 0xff;
+                query_everything(&corrupted);
+            }
+        }
+
+        // point the anonymous structure member of the anonymous union at the
+        // union, which then holds itself
+        let mut cyclic = ANONYMOUS2.to_vec();
+        assert_eq!(cyclic[492], 0x9b);
+        cyclic[492] = 0x7f;
+        query_everything(&cyclic);
+
+        // instruction offsets before the code section are rejected
+        let container = DwarfDebugSymbolContainer::parse(BASIC).unwrap();
+        assert!(container.code_offset(container.code_base() - 1).is_err());
+        assert!(container.find_file_info_from_address(0).is_none());
+    }
+
+    #[test]
+    fn memory_reads() {
+        let container = DwarfDebugSymbolContainer::new(BASIC).ok().unwrap();
+        assert_eq!(container.prepare_read(0x1000, 1 << 20).byte_size, 4096);
+        assert_eq!(container.prepare_read(usize::MAX - 1, 4).byte_size, 1);
+
+        let mut slice = container.prepare_read(0x1000, 4);
+        slice.set_memory_slice(&42i32.to_le_bytes());
+        assert_eq!(
+            container.complete_read(&slice).ok().as_deref(),
+            Some("0x1000: 0x2a 0x00 0x00 0x00")
+        );
+    }
 }
@@ -0,0 +1,89 @@
+; Fixture for C11 anonymous members, lowered by hand from this anonymous.c
+; since neither rustc nor the sandbox's toolchain can produce them:
+;
+;     struct packet {
+;         int kind;
+;         union {
+;             int count;
+;             struct {
+;                 short low;
+;                 short high;
+;             };
+;         };
+;     };
+;
+;     void sink(struct packet *packet);
+;
+;     void inspect(int value) {
+;         struct packet packet = {1, {value}};
+;         sink(&packet);
+;     }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj anonymous.ll -o anonymous.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     anonymous.o -o anonymous.wasm
+; ```
+;
+; `anonymous2.wasm` is built the same way with `-dwarf-version=2` passed to
+; llc, which places members with `DW_OP_plus_uconst` expressions.
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+%struct.packet = type { i32, %union.anon }
+%union.anon = type { i32 }
+
+define void @inspect(i32 %value) !dbg !10 {
+entry:
+  %value.addr = alloca i32, align 4
+  %packet = alloca %struct.packet, align 4
+  store i32 %value, i32* %value.addr, align 4
+  call void @llvm.dbg.declare(metadata i32* %value.addr, metadata !15, metadata !DIExpression()), !dbg !16
+  call void @llvm.dbg.declare(metadata %struct.packet* %packet, metadata !17, metadata !DIExpression()), !dbg !36
+  %kind = getelementptr inbounds %struct.packet, %struct.packet* %packet, i32 0, i32 0, !dbg !36
+  store i32 1, i32* %kind, align 4, !dbg !36
+  %anon = getelementptr inbounds %struct.packet, %struct.packet* %packet, i32 0, i32 1, !dbg !36
+  %count = bitcast %union.anon* %anon to i32*, !dbg !36
+  %0 = load i32, i32* %value.addr, align 4, !dbg !37
+  store i32 %0, i32* %count, align 4, !dbg !36
+  call void @sink(%struct.packet* %packet), !dbg !38
+  ret void, !dbg !39
+}
+
+declare void @llvm.dbg.declare(metadata, metadata, metadata)
+
+declare void @sink(%struct.packet*)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "anonymous.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 17, type: !11, scopeLine: 17, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{null, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "value", arg: 1, scope: !10, file: !1, line: 17, type: !13)
+!16 = !DILocation(line: 17, column: 18, scope: !10)
+!17 = !DILocalVariable(name: "packet", scope: !10, file: !1, line: 18, type: !18)
+!18 = distinct !DICompositeType(tag: DW_TAG_structure_type, name: "packet", file: !1, line: 1, size: 64, elements: !19)
+!19 = !{!20, !21}
+!20 = !DIDerivedType(tag: DW_TAG_member, name: "kind", scope: !18, file: !1, line: 2, baseType: !13, size: 32)
+!21 = !DIDerivedType(tag: DW_TAG_member, scope: !18, file: !1, line: 3, baseType: !22, size: 32, offset: 32)
+!22 = distinct !DICompositeType(tag: DW_TAG_union_type, scope: !18, file: !1, line: 3, size: 32, elements: !23)
+!23 = !{!24, !25}
+!24 = !DIDerivedType(tag: DW_TAG_member, name: "count", scope: !22, file: !1, line: 4, baseType: !13, size: 32)
+!25 = !DIDerivedType(tag: DW_TAG_member, scope: !22, file: !1, line: 5, baseType: !26, size: 32)
+!26 = distinct !DICompositeType(tag: DW_TAG_structure_type, scope: !22, file: !1, line: 5, size: 32, elements: !27)
+!27 = !{!28, !30}
+!28 = !DIDerivedType(tag: DW_TAG_member, name: "low", scope: !26, file: !1, line: 6, baseType: !29, size: 16)
+!29 = !DIBasicType(name: "short", size: 16, encoding: DW_ATE_signed)
+!30 = !DIDerivedType(tag: DW_TAG_member, name: "high", scope: !26, file: !1, line: 7, baseType: !29, size: 16, offset: 16)
+!36 = !DILocation(line: 18, column: 19, scope: !10)
+!37 = !DILocation(line: 18, column: 33, scope: !10)
+!38 = !DILocation(line: 19, column: 5, scope: !10)
+!39 = !DILocation(line: 20, column: 1, scope: !10)
@@ -0,0 +1,44 @@
+//! Fixture for the end-to-end tests of a plain wasm32 module, with a global
+//! and locals kept in the stack frame:
+//!
+//! ```sh
+//! rustc +nightly --target wasm32-unknown-unknown --crate-type cdylib -g \
+//!     -C opt-level=0 -C overflow-checks=off -C link-arg=--allow-undefined \
+//!     --remap-path-prefix "$PWD=." basic.rs -o basic.wasm
+//! ```
+#![feature(no_core, lang_items)]
+#![no_core]
+#![no_main]
+#![allow(internal_features)]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for i32 {}
+#[lang = "drop_glue"]
+pub unsafe fn drop_glue<T: ?Sized>(_: *mut T) {}
+
+#[no_mangle]
+pub static mut STEPS: i32 = 5;
+
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn sink(v: i32);
+}
+
+#[no_mangle]
+pub extern "C" fn step(start: i32) {
+    let current = start;
+    unsafe { sink(current) };
+    unsafe { sink(STEPS) };
+}
+
+#[no_mangle]
+pub extern "C" fn first(value: i32, _rest: *const u8) -> i32 {
+    value
+}
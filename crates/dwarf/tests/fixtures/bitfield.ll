@@ -0,0 +1,86 @@
+; Fixture for bit-fields packed into one word, lowered by hand from this
+; bitfield.c:
+;
+;     struct flags {
+;         unsigned ready : 1;
+;         int level : 3;
+;         unsigned char mode : 2;
+;         short count : 10;
+;     };
+;
+;     void sink(struct flags *flags);
+;
+;     void inspect(int value) {
+;         struct flags flags = {1, -3, 2, value};
+;         sink(&flags);
+;     }
+;
+; Tuned for gdb, llc describes the fields with the DWARF 2 `DW_AT_bit_offset`,
+; and tuned for lldb with the DWARF 4 `DW_AT_data_bit_offset`:
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj -debugger-tune=gdb \
+;     bitfield.ll -o bitfield.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     bitfield.o -o bitfield.wasm
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj -debugger-tune=lldb \
+;     bitfield.ll -o bitfield4.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     bitfield4.o -o bitfield4.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+%struct.flags = type { i32 }
+
+define void @inspect(i32 %value) !dbg !10 {
+entry:
+  %value.addr = alloca i32, align 4
+  %flags = alloca %struct.flags, align 4
+  store i32 %value, i32* %value.addr, align 4
+  call void @llvm.dbg.declare(metadata i32* %value.addr, metadata !15, metadata !DIExpression()), !dbg !16
+  call void @llvm.dbg.declare(metadata %struct.flags* %flags, metadata !17, metadata !DIExpression()), !dbg !30
+  %word = getelementptr inbounds %struct.flags, %struct.flags* %flags, i32 0, i32 0, !dbg !30
+  %0 = load i32, i32* %value.addr, align 4, !dbg !31
+  %1 = and i32 %0, 1023, !dbg !30
+  %2 = shl i32 %1, 6, !dbg !30
+  ; ready = 1, level = -3 (0b101), mode = 2
+  %3 = or i32 %2, 43, !dbg !30
+  store i32 %3, i32* %word, align 4, !dbg !30
+  call void @sink(%struct.flags* %flags), !dbg !32
+  ret void, !dbg !33
+}
+
+declare void @llvm.dbg.declare(metadata, metadata, metadata)
+
+declare void @sink(%struct.flags*)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "bitfield.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 13, type: !11, scopeLine: 13, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{null, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "value", arg: 1, scope: !10, file: !1, line: 13, type: !13)
+!16 = !DILocation(line: 13, column: 18, scope: !10)
+!17 = !DILocalVariable(name: "flags", scope: !10, file: !1, line: 14, type: !18)
+!18 = distinct !DICompositeType(tag: DW_TAG_structure_type, name: "flags", file: !1, line: 1, size: 32, elements: !19)
+!19 = !{!20, !22, !23, !25}
+!20 = !DIDerivedType(tag: DW_TAG_member, name: "ready", scope: !18, file: !1, line: 2, baseType: !21, size: 1, flags: DIFlagBitField, extraData: i64 0)
+!21 = !DIBasicType(name: "unsigned int", size: 32, encoding: DW_ATE_unsigned)
+!22 = !DIDerivedType(tag: DW_TAG_member, name: "level", scope: !18, file: !1, line: 3, baseType: !13, size: 3, offset: 1, flags: DIFlagBitField, extraData: i64 0)
+!23 = !DIDerivedType(tag: DW_TAG_member, name: "mode", scope: !18, file: !1, line: 4, baseType: !24, size: 2, offset: 4, flags: DIFlagBitField, extraData: i64 0)
+!24 = !DIBasicType(name: "unsigned char", size: 8, encoding: DW_ATE_unsigned_char)
+!25 = !DIDerivedType(tag: DW_TAG_member, name: "count", scope: !18, file: !1, line: 5, baseType: !26, size: 10, offset: 6, flags: DIFlagBitField, extraData: i64 0)
+!26 = !DIBasicType(name: "short", size: 16, encoding: DW_ATE_signed)
+!30 = !DILocation(line: 14, column: 18, scope: !10)
+!31 = !DILocation(line: 14, column: 35, scope: !10)
+!32 = !DILocation(line: 15, column: 5, scope: !10)
+!33 = !DILocation(line: 16, column: 1, scope: !10)
@@ -0,0 +1,74 @@
+//! Fixture for a closure whose captures are reached through the environment
+//! pointer, so their locations dereference memory:
+//!
+//! ```sh
+//! rustc +nightly --target wasm32-unknown-unknown --crate-type cdylib -g \
+//!     -C opt-level=0 -C overflow-checks=off -C link-arg=--allow-undefined \
+//!     --remap-path-prefix "$PWD=." closure.rs -o closure.wasm
+//! ```
+#![feature(no_core, lang_items, unboxed_closures, rustc_attrs, auto_traits)]
+#![no_core]
+#![no_main]
+#![allow(internal_features)]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for i32 {}
+#[lang = "freeze"]
+pub unsafe auto trait Freeze {}
+#[lang = "legacy_receiver"]
+pub trait LegacyReceiver {}
+impl<T: ?Sized> LegacyReceiver for &T {}
+impl<T: ?Sized> LegacyReceiver for &mut T {}
+#[lang = "tuple_trait"]
+pub trait Tuple {}
+#[lang = "fn_once"]
+#[rustc_paren_sugar]
+pub trait FnOnce<Args: Tuple> {
+    #[lang = "fn_once_output"]
+    type Output;
+    extern "rust-call" fn call_once(self, args: Args) -> Self::Output;
+}
+#[lang = "fn_mut"]
+#[rustc_paren_sugar]
+pub trait FnMut<Args: Tuple>: FnOnce<Args> {
+    extern "rust-call" fn call_mut(&mut self, args: Args) -> Self::Output;
+}
+#[lang = "add_assign"]
+pub trait AddAssign<Rhs = Self> {
+    fn add_assign(&mut self, rhs: Rhs);
+}
+impl AddAssign for i32 {
+    fn add_assign(&mut self, rhs: i32) {
+        *self += rhs;
+    }
+}
+#[lang = "drop_glue"]
+pub unsafe fn drop_glue<T: ?Sized>(_: *mut T) {}
+
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn sink(v: i32);
+}
+
+#[inline(never)]
+fn apply<F: FnMut(i32)>(mut f: F) {
+    f(1);
+    f(2);
+}
+
+#[no_mangle]
+pub extern "C" fn closure(a: i32) {
+    let mut total = a;
+    apply(|v| {
+        total += v;
+        unsafe { sink(total) }
+    });
+    unsafe { sink(total) }
+}
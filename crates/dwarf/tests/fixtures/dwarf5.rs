@@ -0,0 +1,38 @@
+//! Fixture for DWARF 5 debug info, whose names are `DW_FORM_strx*` indexes
+//! into `.debug_str_offsets`:
+//!
+//! ```sh
+//! rustc +nightly --target wasm32-unknown-unknown --crate-type cdylib -g \
+//!     -Z dwarf-version=5 -C opt-level=0 -C link-arg=--allow-undefined \
+//!     --remap-path-prefix "$PWD=." dwarf5.rs -o dwarf5.wasm
+//! ```
+#![feature(no_core, lang_items)]
+#![no_core]
+#![no_main]
+#![allow(internal_features)]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for i32 {}
+#[lang = "drop_glue"]
+pub unsafe fn drop_glue<T: ?Sized>(_: *mut T) {}
+
+#[no_mangle]
+pub static mut LIMIT: i32 = 3;
+
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn sink(v: i32);
+}
+
+#[no_mangle]
+pub extern "C" fn indexed(count: i32) {
+    let doubled = count;
+    unsafe { sink(doubled) }
+}
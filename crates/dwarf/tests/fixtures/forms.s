@@ -0,0 +1,368 @@
+# Fixture for the DWARF 5 forms that move attribute values out of the DIEs,
+# written by hand around llc's output for forms.c since no single producer
+# emits all of them:
+#
+#     struct pair {
+#         int first;
+#         int second;
+#     };
+#
+#     int inspect(int value) {
+#         const int limit = -2;
+#         struct pair pair;
+#         return value + 1;
+#     }
+#
+# Constants shared by every DIE of an abbreviation are DW_FORM_implicit_const,
+# the unit's names are DW_FORM_line_strp, the code ranges DW_FORM_rnglistx and
+# the location of `value` DW_FORM_loclistx. The line program llvm-mc writes is
+# left at version 4, as version 5 would have it fill `.debug_line_str` too.
+#
+# ```sh
+# llvm-mc -triple=wasm32-unknown-unknown -filetype=obj forms.s -o forms.o
+# rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+#     forms.o -o forms.wasm
+# ```
+	.text
+	.file	"forms.c"
+	.section	.text.inspect,"",@
+	.globl	inspect                         # -- Begin function inspect
+	.globaltype	__stack_pointer, i32
+	.type	inspect,@function
+inspect:                                # @inspect
+.Lfunc_begin0:
+	.file	1 "." "forms.c"
+	.loc	1 3 0                           # forms.c:3:0
+	.functype	inspect (i32) -> (i32)
+	.local  	i32, i32, i32, i32, i32, i32
+# %bb.0:                                # %entry
+	global.get	__stack_pointer
+	local.set	1
+	i32.const	16
+	local.set	2
+	local.get	1
+	local.get	2
+	i32.sub 
+	local.set	3
+	local.get	3
+	local.get	0
+	i32.store	12
+.Ltmp0:
+	.loc	1 4 5 prologue_end              # forms.c:4:5
+	local.get	3
+	i32.load	12
+	local.set	4
+	i32.const	1
+	local.set	5
+	local.get	4
+	local.get	5
+	i32.add 
+	local.set	6
+	local.get	6
+	return
+	end_function
+.Ltmp1:
+.Lfunc_end0:
+	.size	inspect, .Lfunc_end0-inspect
+                                        # -- End function
+	.section	.debug_abbrev,"",@
+	.int8	1                               # Abbreviation Code
+	.int8	17                              # DW_TAG_compile_unit
+	.int8	1                               # DW_CHILDREN_yes
+	.int8	37                              # DW_AT_producer
+	.int8	37                              # DW_FORM_strx1
+	.int8	19                              # DW_AT_language
+	.int8	5                               # DW_FORM_data2
+	.int8	3                               # DW_AT_name
+	.int8	31                              # DW_FORM_line_strp
+	.int8	114                             # DW_AT_str_offsets_base
+	.int8	23                              # DW_FORM_sec_offset
+	.int8	115                             # DW_AT_addr_base
+	.int8	23                              # DW_FORM_sec_offset
+	.int8	116                             # DW_AT_rnglists_base
+	.int8	23                              # DW_FORM_sec_offset
+	.int8	140                             # DW_AT_loclists_base
+	.int8	1
+	.int8	23                              # DW_FORM_sec_offset
+	.int8	16                              # DW_AT_stmt_list
+	.int8	23                              # DW_FORM_sec_offset
+	.int8	27                              # DW_AT_comp_dir
+	.int8	31                              # DW_FORM_line_strp
+	.int8	17                              # DW_AT_low_pc
+	.int8	27                              # DW_FORM_addrx
+	.int8	85                              # DW_AT_ranges
+	.int8	35                              # DW_FORM_rnglistx
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	2                               # Abbreviation Code
+	.int8	46                              # DW_TAG_subprogram
+	.int8	1                               # DW_CHILDREN_yes
+	.int8	85                              # DW_AT_ranges
+	.int8	35                              # DW_FORM_rnglistx
+	.int8	64                              # DW_AT_frame_base
+	.int8	24                              # DW_FORM_exprloc
+	.int8	3                               # DW_AT_name
+	.int8	37                              # DW_FORM_strx1
+	.int8	58                              # DW_AT_decl_file
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	1                       # implicit value
+	.int8	59                              # DW_AT_decl_line
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	3                       # implicit value
+	.int8	39                              # DW_AT_prototyped
+	.int8	25                              # DW_FORM_flag_present
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	63                              # DW_AT_external
+	.int8	25                              # DW_FORM_flag_present
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	3                               # Abbreviation Code
+	.int8	5                               # DW_TAG_formal_parameter
+	.int8	0                               # DW_CHILDREN_no
+	.int8	2                               # DW_AT_location
+	.int8	34                              # DW_FORM_loclistx
+	.int8	3                               # DW_AT_name
+	.int8	37                              # DW_FORM_strx1
+	.int8	58                              # DW_AT_decl_file
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	1                       # implicit value
+	.int8	59                              # DW_AT_decl_line
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	3                       # implicit value
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	4                               # Abbreviation Code
+	.int8	52                              # DW_TAG_variable
+	.int8	0                               # DW_CHILDREN_no
+	.int8	28                              # DW_AT_const_value
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	-2                      # implicit value
+	.int8	3                               # DW_AT_name
+	.int8	37                              # DW_FORM_strx1
+	.int8	58                              # DW_AT_decl_file
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	1                       # implicit value
+	.int8	59                              # DW_AT_decl_line
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	4                       # implicit value
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	5                               # Abbreviation Code
+	.int8	52                              # DW_TAG_variable
+	.int8	0                               # DW_CHILDREN_no
+	.int8	2                               # DW_AT_location
+	.int8	24                              # DW_FORM_exprloc
+	.int8	3                               # DW_AT_name
+	.int8	37                              # DW_FORM_strx1
+	.int8	58                              # DW_AT_decl_file
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	1                       # implicit value
+	.int8	59                              # DW_AT_decl_line
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	5                       # implicit value
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	6                               # Abbreviation Code
+	.int8	36                              # DW_TAG_base_type
+	.int8	0                               # DW_CHILDREN_no
+	.int8	3                               # DW_AT_name
+	.int8	37                              # DW_FORM_strx1
+	.int8	62                              # DW_AT_encoding
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	5                       # implicit value
+	.int8	11                              # DW_AT_byte_size
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	4                       # implicit value
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	7                               # Abbreviation Code
+	.int8	19                              # DW_TAG_structure_type
+	.int8	1                               # DW_CHILDREN_yes
+	.int8	3                               # DW_AT_name
+	.int8	31                              # DW_FORM_line_strp
+	.int8	11                              # DW_AT_byte_size
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	8                       # implicit value
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	8                               # Abbreviation Code
+	.int8	13                              # DW_TAG_member
+	.int8	0                               # DW_CHILDREN_no
+	.int8	3                               # DW_AT_name
+	.int8	37                              # DW_FORM_strx1
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	56                              # DW_AT_data_member_location
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	0                       # implicit value
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	9                               # Abbreviation Code
+	.int8	13                              # DW_TAG_member
+	.int8	0                               # DW_CHILDREN_no
+	.int8	3                               # DW_AT_name
+	.int8	37                              # DW_FORM_strx1
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	56                              # DW_AT_data_member_location
+	.int8	33                              # DW_FORM_implicit_const
+	.sleb128	4                       # implicit value
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	0                               # EOM(3)
+	.section	.debug_info,"",@
+.Lcu_begin0:
+	.int32	.Ldebug_info_end0-.Ldebug_info_start0 # Length of Unit
+.Ldebug_info_start0:
+	.int16	5                               # DWARF version number
+	.int8	1                               # DWARF Unit Type
+	.int8	4                               # Address Size (in bytes)
+	.int32	.debug_abbrev0                  # Offset Into Abbrev. Section
+	.int8	1                               # Abbrev [1] DW_TAG_compile_unit
+	.int8	0                               # DW_AT_producer
+	.int16	12                              # DW_AT_language
+	.int32	.Lline_string0                  # DW_AT_name
+	.int32	.Lstr_offsets_base0             # DW_AT_str_offsets_base
+	.int32	.Laddr_table_base0              # DW_AT_addr_base
+	.int32	.Lrnglists_table_base0          # DW_AT_rnglists_base
+	.int32	.Lloclists_table_base0          # DW_AT_loclists_base
+	.int32	.Lline_table_start0             # DW_AT_stmt_list
+	.int32	.Lline_string1                  # DW_AT_comp_dir
+	.int8	0                               # DW_AT_low_pc
+	.int8	0                               # DW_AT_ranges
+	.int8	2                               # Abbrev [2] DW_TAG_subprogram
+	.int8	0                               # DW_AT_ranges
+	.int8	4                               # DW_AT_frame_base
+	.int8	237
+	.int8	0
+	.int8	3
+	.int8	159
+	.int8	1                               # DW_AT_name
+	.int32	.Lint_type-.Lcu_begin0          # DW_AT_type
+	.int8	3                               # Abbrev [3] DW_TAG_formal_parameter
+	.int8	0                               # DW_AT_location
+	.int8	3                               # DW_AT_name
+	.int32	.Lint_type-.Lcu_begin0          # DW_AT_type
+	.int8	4                               # Abbrev [4] DW_TAG_variable
+	.int8	4                               # DW_AT_name
+	.int32	.Lint_type-.Lcu_begin0          # DW_AT_type
+	.int8	5                               # Abbrev [5] DW_TAG_variable
+	.int8	2                               # DW_AT_location
+	.int8	145
+	.int8	0
+	.int8	5                               # DW_AT_name
+	.int32	.Lpair_type-.Lcu_begin0         # DW_AT_type
+	.int8	0                               # End Of Children Mark
+.Lint_type:
+	.int8	6                               # Abbrev [6] DW_TAG_base_type
+	.int8	2                               # DW_AT_name
+.Lpair_type:
+	.int8	7                               # Abbrev [7] DW_TAG_structure_type
+	.int32	.Lline_string2                  # DW_AT_name
+	.int8	8                               # Abbrev [8] DW_TAG_member
+	.int8	6                               # DW_AT_name
+	.int32	.Lint_type-.Lcu_begin0          # DW_AT_type
+	.int8	9                               # Abbrev [9] DW_TAG_member
+	.int8	7                               # DW_AT_name
+	.int32	.Lint_type-.Lcu_begin0          # DW_AT_type
+	.int8	0                               # End Of Children Mark
+	.int8	0                               # End Of Children Mark
+.Ldebug_info_end0:
+	.section	.debug_str_offsets,"",@
+	.int32	.Ldebug_str_offsets_end0-.Ldebug_str_offsets_start0 # Length of String Offsets Set
+.Ldebug_str_offsets_start0:
+	.int16	5                               # Version
+	.int16	0                               # Padding
+.Lstr_offsets_base0:
+	.int32	.Linfo_string0                  # hand-written
+	.int32	.Linfo_string1                  # inspect
+	.int32	.Linfo_string2                  # int
+	.int32	.Linfo_string3                  # value
+	.int32	.Linfo_string4                  # limit
+	.int32	.Linfo_string5                  # pair
+	.int32	.Linfo_string6                  # first
+	.int32	.Linfo_string7                  # second
+.Ldebug_str_offsets_end0:
+	.section	.debug_str,"S",@
+.Linfo_string0:
+	.asciz	"hand-written"                  # string offset=0
+.Linfo_string1:
+	.asciz	"inspect"                       # string offset=13
+.Linfo_string2:
+	.asciz	"int"                           # string offset=21
+.Linfo_string3:
+	.asciz	"value"                         # string offset=25
+.Linfo_string4:
+	.asciz	"limit"                         # string offset=31
+.Linfo_string5:
+	.asciz	"pair"                          # string offset=37
+.Linfo_string6:
+	.asciz	"first"                         # string offset=42
+.Linfo_string7:
+	.asciz	"second"                        # string offset=48
+	.section	.debug_line_str,"S",@
+.Lline_string0:
+	.asciz	"forms.c"
+.Lline_string1:
+	.asciz	"."
+.Lline_string2:
+	.asciz	"pair"
+	.section	.debug_addr,"",@
+	.int32	.Ldebug_addr_end0-.Ldebug_addr_start0 # Length of contribution
+.Ldebug_addr_start0:
+	.int16	5                               # DWARF version number
+	.int8	4                               # Address size
+	.int8	0                               # Segment selector size
+.Laddr_table_base0:
+	.int32	.Lfunc_begin0
+.Ldebug_addr_end0:
+	.section	.debug_rnglists,"",@
+	.int32	.Ldebug_ranges_table_end0-.Ldebug_ranges_table_start0 # Length
+.Ldebug_ranges_table_start0:
+	.int16	5                               # Version
+	.int8	4                               # Address size
+	.int8	0                               # Segment selector size
+	.int32	1                               # Offset entry count
+.Lrnglists_table_base0:
+	.int32	.Ldebug_ranges0-.Lrnglists_table_base0
+.Ldebug_ranges0:
+	.int8	3                               # DW_RLE_startx_length
+	.uleb128	0                       # start index
+	.uleb128	.Lfunc_end0-.Lfunc_begin0# length
+	.int8	0                               # DW_RLE_end_of_list
+.Ldebug_ranges_table_end0:
+	.section	.debug_loclists,"",@
+	.int32	.Ldebug_loc_table_end0-.Ldebug_loc_table_start0 # Length
+.Ldebug_loc_table_start0:
+	.int16	5                               # Version
+	.int8	4                               # Address size
+	.int8	0                               # Segment selector size
+	.int32	1                               # Offset entry count
+.Lloclists_table_base0:
+	.int32	.Ldebug_loc0-.Lloclists_table_base0
+.Ldebug_loc0:
+	.int8	3                               # DW_LLE_startx_length
+	.uleb128	0                       # start index
+	.uleb128	.Lfunc_end0-.Lfunc_begin0# length
+	.uleb128	2                       # Loc expr size
+	.int8	145                             # DW_OP_fbreg
+	.int8	12
+	.int8	0                               # DW_LLE_end_of_list
+.Ldebug_loc_table_end0:
+	.section	.custom_section.producers,"",@
+	.int8	1
+	.int8	8
+	.ascii	"language"
+	.int8	1
+	.int8	3
+	.ascii	"C99"
+	.int8	0
+	.section	.debug_line,"",@
+.Lline_table_start0:
@@ -0,0 +1,75 @@
+; Fixture for locals clang keeps in the stack frame, lowered by hand from this
+; frame.c the way clang -O0 emits it, with each local in an alloca that
+; DW_OP_fbreg places relative to DW_AT_frame_base:
+;
+;     void sink(int *count);
+;
+;     int inspect(int value) {
+;         int count = value + 1;
+;         long long total = count * 2;
+;         sink(&count);
+;         return count + total;
+;     }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj frame.ll -o frame.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     frame.o -o frame.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+define i32 @inspect(i32 %value) !dbg !10 {
+entry:
+  %value.addr = alloca i32, align 4
+  %count = alloca i32, align 4
+  %total = alloca i64, align 8
+  store i32 %value, i32* %value.addr, align 4
+  call void @llvm.dbg.declare(metadata i32* %value.addr, metadata !15, metadata !DIExpression()), !dbg !16
+  call void @llvm.dbg.declare(metadata i32* %count, metadata !17, metadata !DIExpression()), !dbg !18
+  %0 = load i32, i32* %value.addr, align 4, !dbg !19
+  %add = add nsw i32 %0, 1, !dbg !19
+  store i32 %add, i32* %count, align 4, !dbg !18
+  call void @llvm.dbg.declare(metadata i64* %total, metadata !20, metadata !DIExpression()), !dbg !22
+  %1 = load i32, i32* %count, align 4, !dbg !23
+  %mul = mul nsw i32 %1, 2, !dbg !23
+  %conv = sext i32 %mul to i64, !dbg !23
+  store i64 %conv, i64* %total, align 8, !dbg !22
+  call void @sink(i32* %count), !dbg !24
+  %2 = load i32, i32* %count, align 4, !dbg !25
+  %conv1 = sext i32 %2 to i64, !dbg !25
+  %3 = load i64, i64* %total, align 8, !dbg !25
+  %add2 = add nsw i64 %conv1, %3, !dbg !25
+  %conv3 = trunc i64 %add2 to i32, !dbg !25
+  ret i32 %conv3, !dbg !26
+}
+
+declare void @llvm.dbg.declare(metadata, metadata, metadata)
+
+declare void @sink(i32*)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "frame.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 3, type: !11, scopeLine: 3, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "value", arg: 1, scope: !10, file: !1, line: 3, type: !13)
+!16 = !DILocation(line: 3, column: 17, scope: !10)
+!17 = !DILocalVariable(name: "count", scope: !10, file: !1, line: 4, type: !13)
+!18 = !DILocation(line: 4, column: 9, scope: !10)
+!19 = !DILocation(line: 4, column: 23, scope: !10)
+!20 = !DILocalVariable(name: "total", scope: !10, file: !1, line: 5, type: !21)
+!21 = !DIBasicType(name: "long long", size: 64, encoding: DW_ATE_signed)
+!22 = !DILocation(line: 5, column: 15, scope: !10)
+!23 = !DILocation(line: 5, column: 23, scope: !10)
+!24 = !DILocation(line: 6, column: 5, scope: !10)
+!25 = !DILocation(line: 7, column: 12, scope: !10)
+!26 = !DILocation(line: 7, column: 5, scope: !10)
@@ -0,0 +1,41 @@
+; Fixture for globals described by two units, written by hand as the two C++
+; files below would be after merging into one module, each unit with an `int`
+; of its own. `config::limit` is an inline variable, so both describe it:
+;
+;     // first.cpp
+;     namespace config { inline int limit = 8; }
+;
+;     // second.cpp
+;     namespace config { inline int limit = 8; int retries = 3; }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj globals.ll -o globals.o
+; rust-lld -flavor wasm --no-entry --export-all globals.o -o globals.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+@_ZN6config5limitE = global i32 8, align 4, !dbg !20, !dbg !40
+@_ZN6config7retriesE = global i32 3, align 4, !dbg !42
+
+!llvm.dbg.cu = !{!0, !30}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C_plus_plus, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, globals: !10, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "first.cpp", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!10 = !{!20}
+!11 = !DINamespace(name: "config", scope: null)
+!12 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!20 = !DIGlobalVariableExpression(var: !21, expr: !DIExpression())
+!21 = distinct !DIGlobalVariable(name: "limit", linkageName: "_ZN6config5limitE", scope: !11, file: !1, line: 1, type: !12, isLocal: false, isDefinition: true)
+!30 = distinct !DICompileUnit(language: DW_LANG_C_plus_plus, file: !31, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, globals: !32, splitDebugInlining: false, nameTableKind: None)
+!31 = !DIFile(filename: "second.cpp", directory: ".")
+!32 = !{!40, !42}
+!33 = !DINamespace(name: "config", scope: null)
+!34 = distinct !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!40 = !DIGlobalVariableExpression(var: !41, expr: !DIExpression())
+!41 = distinct !DIGlobalVariable(name: "limit", linkageName: "_ZN6config5limitE", scope: !33, file: !31, line: 1, type: !34, isLocal: false, isDefinition: true)
+!42 = !DIGlobalVariableExpression(var: !43, expr: !DIExpression())
+!43 = distinct !DIGlobalVariable(name: "retries", linkageName: "_ZN6config7retriesE", scope: !33, file: !31, line: 1, type: !34, isLocal: false, isDefinition: true)
@@ -0,0 +1,60 @@
+; Fixture for functions inlined into each other, lowered by hand from this
+; inline.c the way clang leaves it after inlining the always_inline callees:
+;
+;     static inline __attribute__((always_inline)) int twice(int x) {
+;         return x * 2;
+;     }
+;
+;     static inline __attribute__((always_inline)) int scale(int x) {
+;         return twice(x) + 1;
+;     }
+;
+;     int inspect(int value) {
+;         return scale(value);
+;     }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj inline.ll -o inline.o
+; rust-lld -flavor wasm --no-entry --export=inspect inline.o -o inline.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+define i32 @inspect(i32 %value) !dbg !10 {
+entry:
+  call void @llvm.dbg.value(metadata i32 %value, metadata !15, metadata !DIExpression()), !dbg !16
+  call void @llvm.dbg.value(metadata i32 %value, metadata !20, metadata !DIExpression()), !dbg !22
+  call void @llvm.dbg.value(metadata i32 %value, metadata !25, metadata !DIExpression()), !dbg !27
+  %mul = mul nsw i32 %value, 3, !dbg !28
+  %add = add nsw i32 %mul, 1, !dbg !30
+  ret i32 %add, !dbg !31
+}
+
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "inline.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 9, type: !11, scopeLine: 9, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "value", arg: 1, scope: !10, file: !1, line: 9, type: !13)
+!16 = !DILocation(line: 9, column: 17, scope: !10)
+!17 = distinct !DISubprogram(name: "scale", scope: !1, file: !1, line: 5, type: !11, scopeLine: 5, flags: DIFlagPrototyped, spFlags: DISPFlagLocalToUnit | DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!18 = distinct !DISubprogram(name: "twice", scope: !1, file: !1, line: 1, type: !11, scopeLine: 1, flags: DIFlagPrototyped, spFlags: DISPFlagLocalToUnit | DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!20 = !DILocalVariable(name: "x", arg: 1, scope: !17, file: !1, line: 5, type: !13)
+!21 = !DILocation(line: 10, column: 12, scope: !10)
+!22 = !DILocation(line: 5, column: 55, scope: !17, inlinedAt: !21)
+!23 = !DILocation(line: 6, column: 12, scope: !17, inlinedAt: !21)
+!25 = !DILocalVariable(name: "x", arg: 1, scope: !18, file: !1, line: 1, type: !13)
+!27 = !DILocation(line: 1, column: 55, scope: !18, inlinedAt: !23)
+!28 = !DILocation(line: 2, column: 14, scope: !18, inlinedAt: !23)
+!30 = !DILocation(line: 6, column: 21, scope: !17, inlinedAt: !21)
+!31 = !DILocation(line: 10, column: 5, scope: !10)
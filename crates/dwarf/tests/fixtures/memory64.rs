@@ -0,0 +1,45 @@
+//! Fixture for memory64 (wasm64) debug info, where addresses and pointers are
+//! 8 bytes wide. Built without the standard library, which wasm64 lacks:
+//!
+//! ```sh
+//! rustc +nightly --target wasm64-unknown-unknown --crate-type cdylib -g \
+//!     -C opt-level=0 -C link-arg=--allow-undefined \
+//!     --remap-path-prefix "$PWD=." memory64.rs -o memory64.wasm
+//! ```
+#![feature(no_core, lang_items)]
+#![no_core]
+#![no_main]
+#![allow(internal_features)]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+#[lang = "drop_glue"]
+pub unsafe fn drop_glue<T: ?Sized>(_: *mut T) {}
+
+#[repr(C)]
+pub struct Node {
+    value: i32,
+    next: *const Node,
+}
+
+#[no_mangle]
+pub static mut COUNTER: u64 = 7;
+#[no_mangle]
+pub static mut HEAD: *const Node = 0 as *const Node;
+
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn sink(n: &Node, p: *const i32);
+}
+
+#[no_mangle]
+pub extern "C" fn walk(n: &Node, p: *const i32) {
+    let local = n;
+    unsafe { sink(local, p) };
+}
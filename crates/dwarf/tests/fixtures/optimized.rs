@@ -0,0 +1,55 @@
+//! Fixture for variables with location lists, whose values live in wasm
+//! locals for part of the function only:
+//!
+//! ```sh
+//! rustc +nightly --target wasm32-unknown-unknown --crate-type cdylib -g \
+//!     -C opt-level=2 -C link-arg=--allow-undefined \
+//!     --remap-path-prefix "$PWD=." optimized.rs -o optimized.wasm
+//! ```
+#![feature(no_core, lang_items)]
+#![no_core]
+#![no_main]
+#![allow(internal_features)]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for i32 {}
+
+#[repr(C)]
+pub struct Pt {
+    x: i32,
+    y: i32,
+}
+
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn sink(v: i32);
+    fn source() -> i32;
+}
+
+#[inline(never)]
+#[no_mangle]
+pub extern "C" fn split(a: i32, b: i32) {
+    let p = Pt { x: a, y: b };
+    unsafe {
+        sink(p.x);
+        sink(p.y);
+    }
+}
+
+#[inline(never)]
+#[no_mangle]
+pub extern "C" fn early(a: i32) {
+    let first = unsafe { source() };
+    unsafe {
+        sink(first);
+        sink(a);
+        sink(a);
+    }
+}
@@ -0,0 +1,223 @@
+# Fixture for a partial unit pulled in with DW_TAG_imported_unit, the way dwz
+# moves shared DIEs out of compile units. No compiler emits this layout, so
+# the debug sections are written by hand around llc's output for partial.c:
+#
+#     int inspect(int value) {
+#         return value + 1;
+#     }
+#
+# The compile unit keeps the line program and the code range, and imports the
+# unit holding `inspect` and its `int`.
+#
+# ```sh
+# llvm-mc -triple=wasm32-unknown-unknown -filetype=obj partial.s -o partial.o
+# rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+#     partial.o -o partial.wasm
+# ```
+	.text
+	.file	"partial.c"
+	.section	.text.inspect,"",@
+	.globl	inspect                         # -- Begin function inspect
+	.globaltype	__stack_pointer, i32
+	.type	inspect,@function
+inspect:                                # @inspect
+.Lfunc_begin0:
+	.file	1 "." "partial.c"
+	.loc	1 3 0                           # partial.c:3:0
+	.functype	inspect (i32) -> (i32)
+	.local  	i32, i32, i32, i32, i32, i32
+# %bb.0:                                # %entry
+	global.get	__stack_pointer
+	local.set	1
+	i32.const	16
+	local.set	2
+	local.get	1
+	local.get	2
+	i32.sub 
+	local.set	3
+	local.get	3
+	local.get	0
+	i32.store	12
+.Ltmp0:
+	.loc	1 4 5 prologue_end              # partial.c:4:5
+	local.get	3
+	i32.load	12
+	local.set	4
+	i32.const	1
+	local.set	5
+	local.get	4
+	local.get	5
+	i32.add 
+	local.set	6
+	local.get	6
+	return
+	end_function
+.Ltmp1:
+.Lfunc_end0:
+	.size	inspect, .Lfunc_end0-inspect
+                                        # -- End function
+	.section	.debug_abbrev,"",@
+	.int8	1                               # Abbreviation Code
+	.int8	17                              # DW_TAG_compile_unit
+	.int8	1                               # DW_CHILDREN_yes
+	.int8	37                              # DW_AT_producer
+	.int8	14                              # DW_FORM_strp
+	.int8	19                              # DW_AT_language
+	.int8	5                               # DW_FORM_data2
+	.int8	3                               # DW_AT_name
+	.int8	14                              # DW_FORM_strp
+	.int8	16                              # DW_AT_stmt_list
+	.int8	23                              # DW_FORM_sec_offset
+	.int8	27                              # DW_AT_comp_dir
+	.int8	14                              # DW_FORM_strp
+	.int8	17                              # DW_AT_low_pc
+	.int8	1                               # DW_FORM_addr
+	.int8	18                              # DW_AT_high_pc
+	.int8	6                               # DW_FORM_data4
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	2                               # Abbreviation Code
+	.int8	61                              # DW_TAG_imported_unit
+	.int8	0                               # DW_CHILDREN_no
+	.int8	24                              # DW_AT_import
+	.int8	16                              # DW_FORM_ref_addr
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	3                               # Abbreviation Code
+	.int8	60                              # DW_TAG_partial_unit
+	.int8	1                               # DW_CHILDREN_yes
+	.int8	16                              # DW_AT_stmt_list
+	.int8	23                              # DW_FORM_sec_offset
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	4                               # Abbreviation Code
+	.int8	46                              # DW_TAG_subprogram
+	.int8	1                               # DW_CHILDREN_yes
+	.int8	17                              # DW_AT_low_pc
+	.int8	1                               # DW_FORM_addr
+	.int8	18                              # DW_AT_high_pc
+	.int8	6                               # DW_FORM_data4
+	.int8	64                              # DW_AT_frame_base
+	.int8	24                              # DW_FORM_exprloc
+	.int8	3                               # DW_AT_name
+	.int8	14                              # DW_FORM_strp
+	.int8	58                              # DW_AT_decl_file
+	.int8	11                              # DW_FORM_data1
+	.int8	59                              # DW_AT_decl_line
+	.int8	11                              # DW_FORM_data1
+	.int8	39                              # DW_AT_prototyped
+	.int8	25                              # DW_FORM_flag_present
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	63                              # DW_AT_external
+	.int8	25                              # DW_FORM_flag_present
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	5                               # Abbreviation Code
+	.int8	5                               # DW_TAG_formal_parameter
+	.int8	0                               # DW_CHILDREN_no
+	.int8	2                               # DW_AT_location
+	.int8	24                              # DW_FORM_exprloc
+	.int8	3                               # DW_AT_name
+	.int8	14                              # DW_FORM_strp
+	.int8	58                              # DW_AT_decl_file
+	.int8	11                              # DW_FORM_data1
+	.int8	59                              # DW_AT_decl_line
+	.int8	11                              # DW_FORM_data1
+	.int8	73                              # DW_AT_type
+	.int8	19                              # DW_FORM_ref4
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	6                               # Abbreviation Code
+	.int8	36                              # DW_TAG_base_type
+	.int8	0                               # DW_CHILDREN_no
+	.int8	3                               # DW_AT_name
+	.int8	14                              # DW_FORM_strp
+	.int8	62                              # DW_AT_encoding
+	.int8	11                              # DW_FORM_data1
+	.int8	11                              # DW_AT_byte_size
+	.int8	11                              # DW_FORM_data1
+	.int8	0                               # EOM(1)
+	.int8	0                               # EOM(2)
+	.int8	0                               # EOM(3)
+	.section	.debug_info,"",@
+.Lcu_begin0:
+	.int32	.Ldebug_info_end0-.Ldebug_info_start0 # Length of Unit
+.Ldebug_info_start0:
+	.int16	4                               # DWARF version number
+	.int32	.debug_abbrev0                  # Offset Into Abbrev. Section
+	.int8	4                               # Address Size (in bytes)
+	.int8	1                               # Abbrev [1] DW_TAG_compile_unit
+	.int32	.Linfo_string0                  # DW_AT_producer
+	.int16	12                              # DW_AT_language
+	.int32	.Linfo_string1                  # DW_AT_name
+	.int32	.Lline_table_start0             # DW_AT_stmt_list
+	.int32	.Linfo_string2                  # DW_AT_comp_dir
+	.int32	.Lfunc_begin0                   # DW_AT_low_pc
+	.int32	.Lfunc_end0-.Lfunc_begin0       # DW_AT_high_pc
+	.int8	2                               # Abbrev [2] DW_TAG_imported_unit
+	.int32	.Lpartial_root                  # DW_AT_import
+	.int8	0                               # End Of Children Mark
+.Ldebug_info_end0:
+.Lcu_begin1:
+	.int32	.Ldebug_info_end1-.Ldebug_info_start1 # Length of Unit
+.Ldebug_info_start1:
+	.int16	4                               # DWARF version number
+	.int32	.debug_abbrev0                  # Offset Into Abbrev. Section
+	.int8	4                               # Address Size (in bytes)
+.Lpartial_root:
+	.int8	3                               # Abbrev [3] 0xb DW_TAG_partial_unit
+	.int32	.Lline_table_start0             # DW_AT_stmt_list
+	.int8	4                               # Abbrev [4] 0x10 DW_TAG_subprogram
+	.int32	.Lfunc_begin0                   # DW_AT_low_pc
+	.int32	.Lfunc_end0-.Lfunc_begin0       # DW_AT_high_pc
+	.int8	4                               # DW_AT_frame_base
+	.int8	237
+	.int8	0
+	.int8	3
+	.int8	159
+	.int32	.Linfo_string3                  # DW_AT_name
+	.int8	1                               # DW_AT_decl_file
+	.int8	3                               # DW_AT_decl_line
+                                        # DW_AT_prototyped
+	.int32	55                              # DW_AT_type
+                                        # DW_AT_external
+	.int8	5                               # Abbrev [5] 0x28 DW_TAG_formal_parameter
+	.int8	2                               # DW_AT_location
+	.int8	145
+	.int8	12
+	.int32	.Linfo_string5                  # DW_AT_name
+	.int8	1                               # DW_AT_decl_file
+	.int8	3                               # DW_AT_decl_line
+	.int32	55                              # DW_AT_type
+	.int8	0                               # End Of Children Mark
+	.int8	6                               # Abbrev [6] 0x37 DW_TAG_base_type
+	.int32	.Linfo_string4                  # DW_AT_name
+	.int8	5                               # DW_AT_encoding
+	.int8	4                               # DW_AT_byte_size
+	.int8	0                               # End Of Children Mark
+.Ldebug_info_end1:
+	.section	.debug_str,"S",@
+.Linfo_string0:
+	.asciz	"hand-written"                  # string offset=0
+.Linfo_string1:
+	.asciz	"partial.c"                     # string offset=13
+.Linfo_string2:
+	.asciz	"."                             # string offset=23
+.Linfo_string3:
+	.asciz	"inspect"                       # string offset=25
+.Linfo_string4:
+	.asciz	"int"                           # string offset=33
+.Linfo_string5:
+	.asciz	"value"                         # string offset=37
+	.section	.custom_section.producers,"",@
+	.int8	1
+	.int8	8
+	.ascii	"language"
+	.int8	1
+	.int8	3
+	.ascii	"C99"
+	.int8	0
+	.section	.debug_str,"S",@
+	.section	.debug_line,"",@
+.Lline_table_start0:
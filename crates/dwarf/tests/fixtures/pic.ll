@@ -0,0 +1,49 @@
+; Fixture for a relocatable side module, whose data is placed at the
+; `__memory_base` it imports, lowered by hand from this pic.c:
+;
+;     int counter = 7;
+;
+;     int inspect(int step) {
+;         return counter + step;
+;     }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-emscripten -relocation-model=pic \
+;     -filetype=obj pic.ll -o pic.o
+; rust-lld -flavor wasm --experimental-pic -shared --export=inspect \
+;     pic.o -o pic.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-emscripten"
+
+@counter = global i32 7, align 4, !dbg !20
+
+define i32 @inspect(i32 %step) !dbg !10 {
+entry:
+  call void @llvm.dbg.value(metadata i32 %step, metadata !15, metadata !DIExpression()), !dbg !16
+  %value = load i32, i32* @counter, align 4, !dbg !17
+  %sum = add nsw i32 %value, %step, !dbg !17
+  ret i32 %sum, !dbg !18
+}
+
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, globals: !19, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "pic.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 3, type: !11, scopeLine: 3, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "step", arg: 1, scope: !10, file: !1, line: 3, type: !13)
+!16 = !DILocation(line: 3, column: 17, scope: !10)
+!17 = !DILocation(line: 4, column: 20, scope: !10)
+!18 = !DILocation(line: 4, column: 5, scope: !10)
+!19 = !{!20}
+!20 = !DIGlobalVariableExpression(var: !21, expr: !DIExpression())
+!21 = distinct !DIGlobalVariable(name: "counter", scope: !0, file: !1, line: 1, type: !13, isLocal: false, isDefinition: true)
@@ -0,0 +1,60 @@
+; Fixture for a variable shadowed by one of the same name in an inner block,
+; lowered by hand from this shadow.c:
+;
+;     int combine(int);
+;
+;     int inspect(int a) {
+;         int value = a + 1;
+;         {
+;             int value = a * 2;
+;             a = combine(value);
+;         }
+;         return a + value;
+;     }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj shadow.ll -o shadow.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     shadow.o -o shadow.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+define i32 @inspect(i32 %a) !dbg !10 {
+entry:
+  call void @llvm.dbg.value(metadata i32 %a, metadata !15, metadata !DIExpression()), !dbg !20
+  %outer = add nsw i32 %a, 1, !dbg !21
+  call void @llvm.dbg.value(metadata i32 %outer, metadata !16, metadata !DIExpression()), !dbg !21
+  %inner = mul nsw i32 %a, 2, !dbg !22
+  call void @llvm.dbg.value(metadata i32 %inner, metadata !17, metadata !DIExpression()), !dbg !22
+  %call = call i32 @combine(i32 %inner), !dbg !23
+  call void @llvm.dbg.value(metadata i32 %call, metadata !15, metadata !DIExpression()), !dbg !23
+  %sum = add nsw i32 %call, %outer, !dbg !24
+  ret i32 %sum, !dbg !25
+}
+
+declare i32 @combine(i32)
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "shadow.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 3, type: !11, scopeLine: 3, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{!15, !16}
+!15 = !DILocalVariable(name: "a", arg: 1, scope: !10, file: !1, line: 3, type: !13)
+!16 = !DILocalVariable(name: "value", scope: !10, file: !1, line: 4, type: !13)
+!17 = !DILocalVariable(name: "value", scope: !18, file: !1, line: 6, type: !13)
+!18 = distinct !DILexicalBlock(scope: !10, file: !1, line: 5, column: 5)
+!20 = !DILocation(line: 3, column: 17, scope: !10)
+!21 = !DILocation(line: 4, column: 17, scope: !10)
+!22 = !DILocation(line: 6, column: 21, scope: !18)
+!23 = !DILocation(line: 7, column: 13, scope: !18)
+!24 = !DILocation(line: 9, column: 14, scope: !10)
+!25 = !DILocation(line: 9, column: 5, scope: !10)
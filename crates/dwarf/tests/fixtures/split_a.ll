@@ -0,0 +1,54 @@
+; Fixture for split DWARF, lowered by hand from this split_a.c and the
+; split_b.c of `split_b.ll`, each compiled with `-gsplit-dwarf`:
+;
+;     int limit = 7;
+;     int clamp(int input);
+;
+;     int inspect(int value) {
+;         return clamp(value);
+;     }
+;
+; ```sh
+; for unit in split_a split_b; do
+;     llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj \
+;         -split-dwarf-file=$unit.dwo -split-dwarf-output=$unit.dwo $unit.ll -o $unit.o
+; done
+; rust-lld -flavor wasm --no-entry --export=inspect --export=limit \
+;     split_a.o split_b.o -o split.wasm
+; llvm-dwp split_a.dwo split_b.dwo -o split.dwp
+; ```
+;
+; Only `split_a.dwo` and `split.dwp` are kept.
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+@limit = global i32 7, align 4, !dbg !20
+
+define i32 @inspect(i32 %value) !dbg !10 {
+entry:
+  call void @llvm.dbg.value(metadata i32 %value, metadata !15, metadata !DIExpression()), !dbg !16
+  %r = call i32 @clamp(i32 %value), !dbg !17
+  ret i32 %r, !dbg !17
+}
+
+declare i32 @clamp(i32)
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, splitDebugFilename: "split_a.dwo", emissionKind: FullDebug, globals: !19, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "split_a.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 4, type: !11, scopeLine: 4, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "value", arg: 1, scope: !10, file: !1, line: 4, type: !13)
+!16 = !DILocation(line: 4, column: 17, scope: !10)
+!17 = !DILocation(line: 5, column: 12, scope: !10)
+!19 = !{!20}
+!20 = !DIGlobalVariableExpression(var: !21, expr: !DIExpression())
+!21 = distinct !DIGlobalVariable(name: "limit", scope: !0, file: !1, line: 1, type: !13, isLocal: false, isDefinition: true)
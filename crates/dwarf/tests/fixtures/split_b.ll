@@ -0,0 +1,33 @@
+; Second unit of the split DWARF fixture, lowered by hand from this
+; split_b.c and built as described in `split_a.ll`:
+;
+;     int clamp(int input) {
+;         return input + 1;
+;     }
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+define i32 @clamp(i32 %input) !dbg !10 {
+entry:
+  call void @llvm.dbg.value(metadata i32 %input, metadata !15, metadata !DIExpression()), !dbg !16
+  %r = add nsw i32 %input, 1, !dbg !17
+  ret i32 %r, !dbg !17
+}
+
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, splitDebugFilename: "split_b.dwo", emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "split_b.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!10 = distinct !DISubprogram(name: "clamp", scope: !1, file: !1, line: 1, type: !11, scopeLine: 1, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "input", arg: 1, scope: !10, file: !1, line: 1, type: !13)
+!16 = !DILocation(line: 1, column: 15, scope: !10)
+!17 = !DILocation(line: 2, column: 12, scope: !10)
@@ -0,0 +1,62 @@
+; Fixture for values the debug info places on the wasm operand stack, lowered
+; by hand from this stack.c the way clang -O2 leaves `offset` on the stack as
+; the second argument of the call, with the bits past its type not cleared:
+;
+;     int combine(int, int);
+;
+;     int inspect(int a, int b) {
+;         short scaled = a * 3;
+;         unsigned char offset = b + 1;
+;         return combine(scaled, b + 1);
+;     }
+;
+; ```sh
+; llc -O2 -mtriple=wasm32-unknown-unknown -filetype=obj stack.ll -o stack.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     stack.o -o stack.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+define i32 @inspect(i32 %a, i32 %b) !dbg !10 {
+entry:
+  call void @llvm.dbg.value(metadata i32 %a, metadata !15, metadata !DIExpression()), !dbg !20
+  call void @llvm.dbg.value(metadata i32 %b, metadata !16, metadata !DIExpression()), !dbg !20
+  %mul = mul i32 %a, 3, !dbg !21
+  %scaled = trunc i32 %mul to i16, !dbg !21
+  call void @llvm.dbg.value(metadata i16 %scaled, metadata !17, metadata !DIExpression()), !dbg !21
+  %add = add i32 %b, 1, !dbg !22
+  %offset = trunc i32 %add to i8, !dbg !22
+  call void @llvm.dbg.value(metadata i8 %offset, metadata !18, metadata !DIExpression()), !dbg !22
+  %wide = sext i16 %scaled to i32, !dbg !23
+  %call = call i32 @combine(i32 %wide, i32 %add), !dbg !23
+  ret i32 %call, !dbg !24
+}
+
+declare i32 @combine(i32, i32)
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: true, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "stack.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 3, type: !11, scopeLine: 3, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition | DISPFlagOptimized, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{!15, !16, !17, !18}
+!15 = !DILocalVariable(name: "a", arg: 1, scope: !10, file: !1, line: 3, type: !13)
+!16 = !DILocalVariable(name: "b", arg: 2, scope: !10, file: !1, line: 3, type: !13)
+!17 = !DILocalVariable(name: "scaled", scope: !10, file: !1, line: 4, type: !19)
+!18 = !DILocalVariable(name: "offset", scope: !10, file: !1, line: 5, type: !25)
+!19 = !DIBasicType(name: "short", size: 16, encoding: DW_ATE_signed)
+!25 = !DIBasicType(name: "unsigned char", size: 8, encoding: DW_ATE_unsigned_char)
+!20 = !DILocation(line: 3, column: 17, scope: !10)
+!21 = !DILocation(line: 4, column: 20, scope: !10)
+!22 = !DILocation(line: 5, column: 28, scope: !10)
+!23 = !DILocation(line: 6, column: 12, scope: !10)
+!24 = !DILocation(line: 6, column: 5, scope: !10)
@@ -0,0 +1,61 @@
+; Fixture for C11 thread-local variables, lowered by hand from this tls.c. In
+; a module with shared memory they live at offsets from the `__tls_base`
+; global, which llc describes with `DW_OP_GNU_push_tls_address`:
+;
+;     _Thread_local int depth;
+;     _Thread_local int counter = 5;
+;
+;     int inspect(int value) {
+;         counter += value; depth = 1;
+;         return counter;
+;     }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -mattr=+atomics,+bulk-memory \
+;     -filetype=obj tls.ll -o tls.o
+; rust-lld -flavor wasm --no-entry --export=inspect --shared-memory \
+;     --import-memory --max-memory=131072 tls.o -o tls.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+@depth = thread_local(localexec) global i32 0, align 4, !dbg !22
+@counter = thread_local(localexec) global i32 5, align 4, !dbg !20
+
+define i32 @inspect(i32 %value) !dbg !10 {
+entry:
+  %value.addr = alloca i32, align 4
+  store i32 %value, i32* %value.addr, align 4
+  call void @llvm.dbg.declare(metadata i32* %value.addr, metadata !15, metadata !DIExpression()), !dbg !16
+  %0 = load i32, i32* %value.addr, align 4, !dbg !17
+  %1 = load i32, i32* @counter, align 4, !dbg !17
+  %add = add nsw i32 %1, %0, !dbg !17
+  store i32 %add, i32* @counter, align 4, !dbg !17
+  store i32 1, i32* @depth, align 4, !dbg !17
+  ret i32 %add, !dbg !18
+}
+
+declare void @llvm.dbg.declare(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C11, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, globals: !19, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "tls.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 4, type: !11, scopeLine: 4, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "value", arg: 1, scope: !10, file: !1, line: 4, type: !13)
+!16 = !DILocation(line: 4, column: 17, scope: !10)
+!17 = !DILocation(line: 5, column: 13, scope: !10)
+!18 = !DILocation(line: 6, column: 5, scope: !10)
+!19 = !{!22, !20}
+!20 = !DIGlobalVariableExpression(var: !21, expr: !DIExpression())
+!21 = distinct !DIGlobalVariable(name: "counter", scope: !0, file: !1, line: 2, type: !13, isLocal: false, isDefinition: true)
+!22 = !DIGlobalVariableExpression(var: !23, expr: !DIExpression())
+!23 = distinct !DIGlobalVariable(name: "depth", scope: !0, file: !1, line: 1, type: !13, isLocal: false, isDefinition: true)
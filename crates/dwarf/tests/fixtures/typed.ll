@@ -0,0 +1,52 @@
+; Fixture for typed DWARF 5 operations, lowered by hand from this typed.c the
+; way clang -O2 -gdwarf-5 describes the narrowed copies of `packed`, as
+; DW_OP_convert between base types:
+;
+;     int sink(int);
+;
+;     int inspect(int packed) {
+;         int low = (signed char)packed;
+;         unsigned long long high = (unsigned short)packed;
+;         return sink(packed);
+;     }
+;
+; ```sh
+; llc -O2 -mtriple=wasm32-unknown-unknown -filetype=obj typed.ll -o typed.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     typed.o -o typed.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+define i32 @inspect(i32 %packed) !dbg !10 {
+entry:
+  call void @llvm.dbg.value(metadata i32 %packed, metadata !15, metadata !DIExpression()), !dbg !20
+  call void @llvm.dbg.value(metadata i32 %packed, metadata !16, metadata !DIExpression(DW_OP_LLVM_convert, 8, DW_ATE_signed, DW_OP_LLVM_convert, 32, DW_ATE_signed, DW_OP_stack_value)), !dbg !20
+  call void @llvm.dbg.value(metadata i32 %packed, metadata !17, metadata !DIExpression(DW_OP_LLVM_convert, 16, DW_ATE_unsigned, DW_OP_LLVM_convert, 64, DW_ATE_unsigned, DW_OP_stack_value)), !dbg !20
+  %call = call i32 @sink(i32 %packed), !dbg !21
+  ret i32 %call, !dbg !22
+}
+
+declare i32 @sink(i32)
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C11, file: !1, producer: "hand-written", isOptimized: true, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "typed.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 5}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 3, type: !11, scopeLine: 3, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition | DISPFlagOptimized, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{!15, !16, !17}
+!15 = !DILocalVariable(name: "packed", arg: 1, scope: !10, file: !1, line: 3, type: !13)
+!16 = !DILocalVariable(name: "low", scope: !10, file: !1, line: 4, type: !13)
+!17 = !DILocalVariable(name: "high", scope: !10, file: !1, line: 5, type: !18)
+!18 = !DIBasicType(name: "unsigned long long", size: 64, encoding: DW_ATE_unsigned)
+!20 = !DILocation(line: 3, column: 17, scope: !10)
+!21 = !DILocation(line: 6, column: 12, scope: !10)
+!22 = !DILocation(line: 6, column: 5, scope: !10)
@@ -0,0 +1,43 @@
+//! Fixture for a union whose arms share one address:
+//!
+//! ```sh
+//! rustc +nightly --target wasm32-unknown-unknown --crate-type cdylib -g \
+//!     -C opt-level=0 -C link-arg=--allow-undefined \
+//!     --remap-path-prefix "$PWD=." union.rs -o union.wasm
+//! ```
+#![feature(no_core, lang_items, auto_traits)]
+#![no_core]
+#![no_main]
+#![allow(internal_features)]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for i32 {}
+impl Copy for f32 {}
+#[lang = "freeze"]
+pub unsafe auto trait Freeze {}
+#[lang = "drop_glue"]
+pub unsafe fn drop_glue<T: ?Sized>(_: *mut T) {}
+
+#[repr(C)]
+pub union Bits {
+    int: i32,
+    float: f32,
+}
+
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn sink(bits: &Bits);
+}
+
+#[no_mangle]
+pub extern "C" fn reinterpret(value: i32) {
+    let bits = Bits { int: value };
+    unsafe { sink(&bits) };
+}
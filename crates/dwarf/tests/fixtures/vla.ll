@@ -0,0 +1,78 @@
+; Fixture for arrays bounded at run time, lowered by hand from this vla.c,
+; with `triple` standing in for a Fortran style bound computed by a DWARF
+; expression, which C can't express:
+;
+;     void sink(int *values);
+;
+;     void inspect(int n) {
+;         int values[n];
+;         int triple[3];
+;         sink(values);
+;     }
+;
+; ```sh
+; llc -O0 -mtriple=wasm32-unknown-unknown -filetype=obj vla.ll -o vla.o
+; rust-lld -flavor wasm --no-entry --export=inspect --allow-undefined \
+;     vla.o -o vla.wasm
+; ```
+target datalayout = "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128-ni:1:10:20"
+target triple = "wasm32-unknown-unknown"
+
+define void @inspect(i32 %n) !dbg !10 {
+entry:
+  %n.addr = alloca i32, align 4
+  %saved_stack = alloca i8*, align 4
+  %__vla_expr0 = alloca i32, align 4
+  %triple = alloca [3 x i32], align 4
+  store i32 %n, i32* %n.addr, align 4
+  call void @llvm.dbg.declare(metadata i32* %n.addr, metadata !15, metadata !DIExpression()), !dbg !16
+  %0 = load i32, i32* %n.addr, align 4, !dbg !17
+  %1 = call i8* @llvm.stacksave(), !dbg !17
+  store i8* %1, i8** %saved_stack, align 4, !dbg !17
+  %vla = alloca i32, i32 %0, align 16, !dbg !17
+  store i32 %0, i32* %__vla_expr0, align 4, !dbg !17
+  call void @llvm.dbg.declare(metadata i32* %__vla_expr0, metadata !18, metadata !DIExpression()), !dbg !20
+  call void @llvm.dbg.declare(metadata i32* %vla, metadata !21, metadata !DIExpression()), !dbg !25
+  call void @llvm.dbg.declare(metadata [3 x i32]* %triple, metadata !26, metadata !DIExpression()), !dbg !30
+  call void @sink(i32* %vla), !dbg !31
+  %2 = load i8*, i8** %saved_stack, align 4, !dbg !32
+  call void @llvm.stackrestore(i8* %2), !dbg !32
+  ret void, !dbg !32
+}
+
+declare void @llvm.dbg.declare(metadata, metadata, metadata)
+declare i8* @llvm.stacksave()
+declare void @llvm.stackrestore(i8*)
+declare void @sink(i32*)
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "hand-written", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, splitDebugInlining: false, nameTableKind: None)
+!1 = !DIFile(filename: "vla.c", directory: ".")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !{i32 1, !"wchar_size", i32 4}
+!10 = distinct !DISubprogram(name: "inspect", scope: !1, file: !1, line: 3, type: !11, scopeLine: 3, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !0, retainedNodes: !14)
+!11 = !DISubroutineType(types: !12)
+!12 = !{null, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !{}
+!15 = !DILocalVariable(name: "n", arg: 1, scope: !10, file: !1, line: 3, type: !13)
+!16 = !DILocation(line: 3, column: 18, scope: !10)
+!17 = !DILocation(line: 4, column: 5, scope: !10)
+!18 = !DILocalVariable(name: "__vla_expr0", scope: !10, type: !19, flags: DIFlagArtificial)
+!19 = !DIBasicType(name: "__ARRAY_SIZE_TYPE__", size: 32, encoding: DW_ATE_unsigned)
+!20 = !DILocation(line: 0, scope: !10)
+!21 = !DILocalVariable(name: "values", scope: !10, file: !1, line: 4, type: !22)
+!22 = !DICompositeType(tag: DW_TAG_array_type, baseType: !13, elements: !23)
+!23 = !{!24}
+!24 = !DISubrange(count: !18)
+!25 = !DILocation(line: 4, column: 9, scope: !10)
+!26 = !DILocalVariable(name: "triple", scope: !10, file: !1, line: 5, type: !27)
+!27 = !DICompositeType(tag: DW_TAG_array_type, baseType: !13, elements: !28)
+!28 = !{!29}
+!29 = !DISubrange(upperBound: !DIExpression(DW_OP_constu, 2))
+!30 = !DILocation(line: 5, column: 9, scope: !10)
+!31 = !DILocation(line: 6, column: 5, scope: !10)
+!32 = !DILocation(line: 7, column: 1, scope: !10)
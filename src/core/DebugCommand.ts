@@ -6,6 +6,7 @@ export interface Variable {
     displayName: string;
     type: string;
     childGroupId?: number;
+    isParameter?: boolean;
 }
 
 export interface IBreakPoint {
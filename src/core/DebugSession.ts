@@ -4,12 +4,19 @@ import {
 	StoppedEvent, BreakpointEvent, ContinuedEvent
 } from '@vscode/debugadapter';
 import { WebAssemblyFile } from "./Source"
-import { DwarfDebugSymbolContainer, WasmLineInfo } from "../../crates/dwarf/pkg";
+import { DwarfDebugSymbolContainer, VariableInfo, VariableStatus, WasmLineInfo } from "../../crates/dwarf/pkg";
 import { DebugAdapter } from './DebugAdapterInterface';
 import { DebuggerWorkflowCommand, DebuggerDumpCommand, DebuggerCommand, WebAssemblyDebugState, RuntimeBreakPoint, IBreakPoint, FileLocation, RuntimeStackFrame } from './DebugCommand';
 import { RunningDebugSessionState } from './DebugSessionState/RunningDebugSessionState';
 import { PausedDebugSessionState } from './DebugSessionState/PausedDebugSessionState';
 
+function reportBridgeError(e: unknown) {
+    // "NotFound" only means the symbol lives in another module
+    if ((e as { kind?: string })?.kind !== "NotFound") {
+        console.error(e);
+    }
+}
+
 export class DebugSession {
 
     sources: WebAssemblyFile[];
@@ -54,10 +61,14 @@ export class DebugSession {
 
     getVariablelistFromAddress(address: number) {
         for (const x of this.sources) {
-            const list = x.dwarf.variable_name_list(address);
+            try {
+                const list = x.dwarf.variable_name_list(address);
 
-            if (list && list.size() > 0) {
-                return list;
+                if (list && list.size() > 0) {
+                    return list;
+                }
+            } catch (e) {
+                reportBridgeError(e);
             }
         }
 
@@ -68,28 +79,51 @@ export class DebugSession {
         const list = [];
 
         for (const x of this.sources) {
-            list.push(x.dwarf.global_variable_name_list(inst));
+            try {
+                list.push(x.dwarf.global_variable_name_list(inst));
+            } catch (e) {
+                reportBridgeError(e);
+            }
         }
 
         return list;
     }
 
-    getVariableValue(expr: string, address: number, state: WebAssemblyDebugState) {
+    getVariableValue(expr: string, address: number, state: WebAssemblyDebugState): VariableInfo | 'optimized out' | 'unavailable' | undefined {
+        let missing: 'optimized out' | 'unavailable' | undefined;
+
         for (const x of this.sources) {
-            const info = x.dwarf.get_variable_info(
-                expr,
-                state.locals,
-                state.globals,
-                state.stacks,
-                address
-            );
-
-            if (info) {
-                return info;
+            try {
+                const lookup = x.dwarf.get_variable_info(
+                    expr,
+                    state.locals,
+                    state.globals,
+                    state.stacks,
+                    address,
+                    // memory is read asynchronously through the protocol
+                    undefined,
+                    // wasm frames have no registers
+                    undefined
+                );
+                const status = lookup.status();
+                const info = lookup.info();
+                lookup.free();
+
+                if (status == VariableStatus.Found && info) {
+                    return info;
+                }
+                if (status == VariableStatus.OptimizedOut) {
+                    missing = 'optimized out';
+                }
+                if (status == VariableStatus.Unavailable) {
+                    missing = missing || 'unavailable';
+                }
+            } catch (e) {
+                reportBridgeError(e);
             }
         }
 
-        return undefined;
+        return missing;
     }
 }
 
@@ -123,17 +123,18 @@ export class PausedDebugSessionState implements DebuggerWorkflowCommand, Debugge
             const type = varlist.at_type_name(i);
             const groupId = varlist.at_group_id(i);
             const childGroupId = varlist.at_chile_group_id(i);
+            const isParameter = varlist.at_is_parameter(i);
             
             if (!variableReference)
             {
                 list.push({
-                    name, displayName, type, childGroupId
+                    name, displayName, type, childGroupId, isParameter
                 })
             }
             else if (variableReference == groupId)
             {
                 list.push({
-                    name, displayName, type, childGroupId
+                    name, displayName, type, childGroupId, isParameter
                 })
             }
         }
@@ -198,6 +199,14 @@ export class PausedDebugSessionState implements DebuggerWorkflowCommand, Debugge
         if (!wasmVariable) {
             return;
         }
+
+        if (wasmVariable == 'optimized out') {
+            return '<optimized out>';
+        }
+
+        if (wasmVariable == 'unavailable') {
+            return '<unavailable>';
+        }
         
         let evaluationResult = wasmVariable.evaluate() || '<failure>';
         let limit = 0;
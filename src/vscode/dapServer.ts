@@ -405,6 +405,7 @@ export class VSCodeDebugSession extends LoggingDebugSession implements DebugAdap
 
 		response.body = {
 			scopes: [
+				new Scope("Arguments", this._variableHandles.create('arguments'), false),
 				new Scope("Locals", this._variableHandles.create('locals'), true),
 				new Scope("Globals", this._variableHandles.create('globals'), true),
 			]
@@ -418,8 +419,10 @@ export class VSCodeDebugSession extends LoggingDebugSession implements DebugAdap
 		
 		const v = this._variableHandles.get(args.variablesReference);
 		
-		if (v === 'locals') {
-			vs = await this.session.listVariable(1000);
+		if (v === 'arguments') {
+			vs = (await this.session.listVariable(1000)).filter(x => x.isParameter);
+		} else if (v === 'locals') {
+			vs = (await this.session.listVariable(1000)).filter(x => !x.isParameter);
 		} else if (v === 'globals') {
 			vs = await this.session.listGlobalVariable(1001);
 		} else if (args.variablesReference < 20000) {